@@ -0,0 +1,35 @@
+#ifndef SNAKE_H
+#define SNAKE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Minimal C ABI for the snake engine. Link against the cdylib built by
+ * `cargo build --release -p snake-core` (libsnake_core.so / .dylib / snake_core.dll). */
+
+#define SNAKE_MOVED 0
+#define SNAKE_ATE 1
+#define SNAKE_DIED 2
+#define SNAKE_WON 3
+#define SNAKE_ATE_OUT_OF_ORDER 4
+#define SNAKE_TIME_UP 5
+#define SNAKE_LOST_LIFE 6
+#define SNAKE_BOSS_DEFEATED 7
+#define SNAKE_RIVAL_FILLED 8
+#define SNAKE_ERROR -1
+
+typedef struct SnakeHandle SnakeHandle;
+
+/* Returns NULL if the board is smaller than 3x3. */
+SnakeHandle *snake_new(uint16_t width, uint16_t height, uint64_t seed);
+void snake_free(SnakeHandle *handle);
+
+/* direction: 0 up, 1 right, 2 down, 3 left, anything else keeps going straight.
+ * Returns one of the SNAKE_* codes. */
+int32_t snake_step(SnakeHandle *handle, int32_t direction);
+
+/* Serializes the state (layout documented in src/snapshot.rs) into a buffer owned
+ * by the handle. It stays valid until the next call with the same handle. */
+const uint8_t *snake_state_buffer(SnakeHandle *handle, size_t *len);
+
+#endif
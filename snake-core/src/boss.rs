@@ -0,0 +1,107 @@
+use crate::direction::Direction;
+use crate::hazard::Beam;
+use crate::point::Point;
+
+// The boss is a square of this many cells per side.
+pub const BOSS_SIZE: u16 = 2;
+// Foods the snake has to eat to defeat the boss.
+pub const BOSS_HITS: u8 = 5;
+// Ticks between the boss's moves while it is unhurt.
+const BOSS_MOVE_EVERY: u8 = 4;
+
+// Note: The boss is a multi-cell obstacle that chases the snake's head. It is
+// positioned by its top left cell and never moves onto a snake or food, so it
+// only gets the snake by cornering it; it speeds up with every hit it takes.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Boss {
+    origin: Point,
+    hits: u8,
+}
+
+impl Boss {
+    pub fn new(origin: Point) -> Self {
+        Self { origin, hits: 0 }
+    }
+
+    pub fn get_origin(&self) -> Point {
+        self.origin
+    }
+
+    pub fn get_hits(&self) -> u8 {
+        self.hits
+    }
+
+    pub fn is_defeated(&self) -> bool {
+        self.hits >= BOSS_HITS
+    }
+
+    pub fn hit(&mut self) {
+        self.hits = (self.hits + 1).min(BOSS_HITS);
+    }
+
+    pub fn move_every(&self) -> u64 {
+        BOSS_MOVE_EVERY.saturating_sub(self.hits).max(1) as u64
+    }
+
+    pub fn cells(&self) -> Vec<Point> {
+        cells_at(self.origin)
+    }
+
+    pub fn covers(&self, point: &Point) -> bool {
+        (self.origin.x..self.origin.x + BOSS_SIZE).contains(&point.x)
+            && (self.origin.y..self.origin.y + BOSS_SIZE).contains(&point.y)
+    }
+
+    // Note: The boss closes in along the axis with the larger gap first, and only
+    // tries directions that bring it closer to `target`.
+    pub fn chase_directions(&self, target: &Point) -> Vec<Direction> {
+        let centre = Point::new(self.origin.x + BOSS_SIZE / 2, self.origin.y + BOSS_SIZE / 2);
+        let (dx, dy) = centre.offset_to(target);
+        let horizontal = match dx.signum() {
+            1 => Some(Direction::Right),
+            -1 => Some(Direction::Left),
+            _ => None,
+        };
+        let vertical = match dy.signum() {
+            1 => Some(Direction::Down),
+            -1 => Some(Direction::Up),
+            _ => None,
+        };
+
+        if dx.abs() >= dy.abs() {
+            horizontal.into_iter().chain(vertical).collect()
+        } else {
+            vertical.into_iter().chain(horizontal).collect()
+        }
+    }
+
+    // Returns where the boss would be after moving one cell, or None if it would
+    // leave a board of the given size.
+    pub fn origin_towards(&self, direction: Direction, width: u16, height: u16) -> Option<Point> {
+        self.origin
+            .neighbour_towards(direction, width - BOSS_SIZE + 1, height - BOSS_SIZE + 1)
+    }
+
+    pub fn move_to(&mut self, origin: Point) {
+        self.origin = origin;
+    }
+
+    // The boss fires along the row or column the target is in, whichever it is
+    // further away from.
+    pub fn aim_at(&self, target: &Point) -> Beam {
+        let (dx, dy) = self.origin.offset_to(target);
+        if dx.abs() >= dy.abs() {
+            Beam::Row(target.y)
+        } else {
+            Beam::Column(target.x)
+        }
+    }
+}
+
+// The cells a boss with its top left cell at `origin` takes up.
+pub fn cells_at(origin: Point) -> Vec<Point> {
+    (0..BOSS_SIZE)
+        .flat_map(|dy| (0..BOSS_SIZE).map(move |dx| Point::new(origin.x + dx, origin.y + dy)))
+        .collect()
+}
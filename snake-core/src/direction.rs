@@ -0,0 +1,54 @@
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+// Note: Without this opposite functionality,
+// the body segments would be placed in front of the head,
+// which would immediately cause a collision when the snake starts moving.
+impl Direction {
+    pub fn all() -> [Self; 4] {
+        [Self::Up, Self::Right, Self::Down, Self::Left]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|direction| direction.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Right => "right",
+            Self::Down => "down",
+            Self::Left => "left",
+        }
+    }
+
+    pub fn turn_left(&self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Right => Self::Up,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+        }
+    }
+
+    pub fn turn_right(&self) -> Self {
+        self.turn_left().opposite()
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Right => Self::Left,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+        }
+    }
+}
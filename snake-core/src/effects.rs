@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Effect {
+    // The player's head passes through snake bodies and obstacles.
+    Invincible,
+}
+
+// Note: Effects run on game time, advanced by the state once per step, so they
+// freeze while the game is paused and replay the same way for a given seed.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedEffects {
+    active: Vec<(Effect, Duration)>,
+}
+
+impl TimedEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Starting an effect that is already active restarts its timer.
+    pub fn add(&mut self, effect: Effect, duration: Duration) {
+        self.active.retain(|(active, _)| *active != effect);
+        self.active.push((effect, duration));
+    }
+
+    pub fn is_active(&self, effect: Effect) -> bool {
+        self.time_left(effect).is_some()
+    }
+
+    pub fn time_left(&self, effect: Effect) -> Option<Duration> {
+        self.active
+            .iter()
+            .find(|(active, _)| *active == effect)
+            .map(|(_, left)| *left)
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) {
+        for (_, left) in self.active.iter_mut() {
+            *left = left.saturating_sub(elapsed);
+        }
+        self.active.retain(|(_, left)| !left.is_zero());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::direction::Direction;
+use crate::grid::Cell;
+use crate::policy::{Policy, RivalLevel};
+use crate::rules::Rules;
+use crate::state::{GameState, StepOutcome};
+
+pub const FEATURE_COUNT: usize = 11;
+
+pub const CELL_EMPTY: u8 = 0;
+pub const CELL_BODY: u8 = 1;
+pub const CELL_HEAD: u8 = 2;
+pub const CELL_FOOD: u8 = 3;
+pub const CELL_OBSTACLE: u8 = 4;
+pub const CELL_RIVAL: u8 = 5;
+
+const REWARD_FOOD: f32 = 1.0;
+const REWARD_DEATH: f32 = -1.0;
+const REWARD_WIN: f32 = 10.0;
+const REWARD_STEP: f32 = -0.01;
+const REWARD_WRONG_FOOD: f32 = -0.5;
+
+// Note: Actions are relative to the way the snake is heading,
+// so an agent can never pick the one invalid move (straight back).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    Straight,
+    TurnLeft,
+    TurnRight,
+}
+
+impl Action {
+    pub fn all() -> [Self; 3] {
+        [Self::Straight, Self::TurnLeft, Self::TurnRight]
+    }
+
+    pub fn apply(&self, direction: Direction) -> Direction {
+        match self {
+            Self::Straight => direction,
+            Self::TurnLeft => direction.turn_left(),
+            Self::TurnRight => direction.turn_right(),
+        }
+    }
+}
+
+// Note: Two encodings of the same moment. `cells` is the whole board, one code
+// per cell in row-major order (see the CELL_* constants), for agents that want a
+// grid tensor. `features` is a small hand-made vector:
+// danger straight/left/right, heading up/right/down/left, food up/right/down/left.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Observation {
+    pub width: u16,
+    pub height: u16,
+    pub cells: Vec<u8>,
+    pub features: [f32; FEATURE_COUNT],
+}
+
+impl Observation {
+    pub fn from_state(state: &GameState) -> Self {
+        let snake = state.get_snake();
+        let head = snake.get_head_point();
+
+        let cells = state
+            .get_grid()
+            .points()
+            .map(|(point, cell)| match cell {
+                Cell::Empty | Cell::Portal => CELL_EMPTY,
+                Cell::Snake if point == head => CELL_HEAD,
+                Cell::Snake
+                    if state
+                        .get_rival()
+                        .is_some_and(|rival| rival.occupies(&point)) =>
+                {
+                    CELL_RIVAL
+                }
+                Cell::Snake => CELL_BODY,
+                Cell::Food => CELL_FOOD,
+                Cell::Obstacle => CELL_OBSTACLE,
+            })
+            .collect();
+
+        let direction = snake.get_direction();
+        let mut features = [0.0; FEATURE_COUNT];
+        for (i, action) in Action::all().iter().enumerate() {
+            features[i] = flag(state.is_fatal_move(snake, action.apply(direction)));
+        }
+        for (i, heading) in Direction::all().iter().enumerate() {
+            features[3 + i] = flag(*heading == direction);
+        }
+        if let Some(food) = state.get_food() {
+            let (dx, dy) = head.offset_to(&food);
+            features[7] = flag(dy < 0);
+            features[8] = flag(dx > 0);
+            features[9] = flag(dy > 0);
+            features[10] = flag(dx < 0);
+        }
+
+        Self {
+            width: state.get_width(),
+            height: state.get_height(),
+            cells,
+            features,
+        }
+    }
+}
+
+// Note: A gym-style wrapper around the headless game, so agents can be trained
+// against the real rules. Every reset starts a new run with the next seed.
+#[derive(Debug)]
+pub struct Env {
+    width: u16,
+    height: u16,
+    rules: Rules,
+    seed: u64,
+    state: GameState,
+    rival_policy: Option<Box<dyn Policy>>,
+    done: bool,
+}
+
+impl Env {
+    pub fn new(width: u16, height: u16, rules: Rules, seed: u64) -> Self {
+        let state = GameState::new(width, height, rules.clone(), seed);
+        Self {
+            width,
+            height,
+            rival_policy: Self::rival_policy(&rules, seed),
+            rules,
+            seed,
+            state,
+            done: false,
+        }
+    }
+
+    pub fn get_state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn reset(&mut self) -> Observation {
+        self.seed = self.seed.wrapping_add(1);
+        self.state = GameState::new(self.width, self.height, self.rules.clone(), self.seed);
+        self.rival_policy = Self::rival_policy(&self.rules, self.seed);
+        self.done = false;
+
+        Observation::from_state(&self.state)
+    }
+
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        if self.done {
+            return (Observation::from_state(&self.state), 0.0, true);
+        }
+
+        let direction = action.apply(self.state.get_snake().get_direction());
+        self.state.turn(direction);
+        if let (Some(policy), Some(rival)) = (self.rival_policy.as_mut(), self.state.get_rival()) {
+            let direction = policy.choose(&self.state, rival);
+            self.state.turn_rival(direction);
+        }
+
+        let reward = match self.state.step() {
+            StepOutcome::Moved => REWARD_STEP,
+            StepOutcome::Ate => REWARD_FOOD,
+            StepOutcome::AteOutOfOrder => REWARD_WRONG_FOOD,
+            StepOutcome::LostLife => REWARD_DEATH,
+            StepOutcome::Died => {
+                self.done = true;
+                REWARD_DEATH
+            }
+            StepOutcome::Won | StepOutcome::BossDefeated => {
+                self.done = true;
+                REWARD_WIN
+            }
+            StepOutcome::TimeUp | StepOutcome::RivalFilled => {
+                self.done = true;
+                0.0
+            }
+        };
+
+        (Observation::from_state(&self.state), reward, self.done)
+    }
+
+    fn rival_policy(rules: &Rules, seed: u64) -> Option<Box<dyn Policy>> {
+        if rules.rival {
+            Some(RivalLevel::Greedy.new_policy(seed))
+        } else {
+            None
+        }
+    }
+}
+
+fn flag(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,131 @@
+use crate::rules::Rules;
+use crate::snapshot;
+use crate::state::{GameState, StepOutcome};
+use std::convert::TryFrom;
+use std::ptr;
+
+pub const SNAKE_MOVED: i32 = 0;
+pub const SNAKE_ATE: i32 = 1;
+pub const SNAKE_DIED: i32 = 2;
+pub const SNAKE_WON: i32 = 3;
+pub const SNAKE_ATE_OUT_OF_ORDER: i32 = 4;
+pub const SNAKE_TIME_UP: i32 = 5;
+pub const SNAKE_LOST_LIFE: i32 = 6;
+pub const SNAKE_BOSS_DEFEATED: i32 = 7;
+pub const SNAKE_RIVAL_FILLED: i32 = 8;
+pub const SNAKE_ERROR: i32 = -1;
+
+// Note: The C ABI hands out an opaque pointer to one of these. The snapshot buffer
+// lives inside the handle, so the pointer returned by `snake_state_buffer` stays
+// valid until the next call that takes the same handle.
+pub struct SnakeHandle {
+    state: GameState,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+#[no_mangle]
+pub extern "C" fn snake_new(width: u16, height: u16, seed: u64) -> *mut SnakeHandle {
+    if width < 3 || height < 3 {
+        return ptr::null_mut();
+    }
+
+    let rules = Rules {
+        food_reachability: true,
+        ..Rules::default()
+    };
+    Box::into_raw(Box::new(SnakeHandle {
+        state: GameState::new(width, height, rules, seed),
+        buffer: Vec::new(),
+        finished: false,
+    }))
+}
+
+/// # Safety
+///
+/// `handle` must come from `snake_new` and must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn snake_free(handle: *mut SnakeHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Turns towards `direction` (0 up, 1 right, 2 down, 3 left, anything else keeps
+/// going straight) and advances the game by one tick. Turning straight back is ignored.
+///
+/// # Safety
+///
+/// `handle` must be a live pointer returned by `snake_new`.
+#[no_mangle]
+pub unsafe extern "C" fn snake_step(handle: *mut SnakeHandle, direction: i32) -> i32 {
+    let handle = match handle.as_mut() {
+        Some(handle) => handle,
+        None => return SNAKE_ERROR,
+    };
+    if handle.finished {
+        return SNAKE_ERROR;
+    }
+
+    let current = handle.state.get_snake().get_direction();
+    if let Some(towards) = u8::try_from(direction)
+        .ok()
+        .and_then(snapshot::direction_from_code)
+    {
+        if towards != current.opposite() {
+            handle.state.turn(towards);
+        }
+    }
+
+    match handle.state.step() {
+        StepOutcome::Moved => SNAKE_MOVED,
+        StepOutcome::Ate => SNAKE_ATE,
+        StepOutcome::AteOutOfOrder => SNAKE_ATE_OUT_OF_ORDER,
+        StepOutcome::LostLife => SNAKE_LOST_LIFE,
+        StepOutcome::Died => {
+            handle.finished = true;
+            SNAKE_DIED
+        }
+        StepOutcome::Won => {
+            handle.finished = true;
+            SNAKE_WON
+        }
+        StepOutcome::TimeUp => {
+            handle.finished = true;
+            SNAKE_TIME_UP
+        }
+        StepOutcome::BossDefeated => {
+            handle.finished = true;
+            SNAKE_BOSS_DEFEATED
+        }
+        StepOutcome::RivalFilled => {
+            handle.finished = true;
+            SNAKE_RIVAL_FILLED
+        }
+    }
+}
+
+/// Writes the state in the layout described in `snapshot.rs` to a buffer owned by
+/// the handle, stores its length in `len` and returns a pointer to it.
+///
+/// # Safety
+///
+/// `handle` must be a live pointer returned by `snake_new` and `len` must be
+/// a valid pointer to write to (or null).
+#[no_mangle]
+pub unsafe extern "C" fn snake_state_buffer(
+    handle: *mut SnakeHandle,
+    len: *mut usize,
+) -> *const u8 {
+    let handle = match handle.as_mut() {
+        Some(handle) => handle,
+        None => return ptr::null(),
+    };
+
+    handle.buffer = snapshot::encode(&handle.state);
+    if let Some(len) = len.as_mut() {
+        *len = handle.buffer.len();
+    }
+
+    handle.buffer.as_ptr()
+}
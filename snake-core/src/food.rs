@@ -0,0 +1,21 @@
+use crate::point::Point;
+
+pub const CHAIN_LENGTH: u8 = 5;
+
+// Note: One of the numbered foods in chain mode, which have to be eaten in order.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainFood {
+    pub point: Point,
+    pub number: u8,
+}
+
+impl ChainFood {
+    pub fn new(point: Point, number: u8) -> Self {
+        Self { point, number }
+    }
+
+    pub fn get_label(&self) -> char {
+        (b'0' + self.number) as char
+    }
+}
@@ -0,0 +1,249 @@
+use crate::direction::Direction;
+use crate::point::Point;
+use std::collections::{HashSet, VecDeque};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cell {
+    Empty,
+    Snake,
+    Food,
+    Obstacle,
+    Portal,
+}
+
+impl Cell {
+    pub fn is_blocking(&self) -> bool {
+        matches!(self, Self::Snake | Self::Obstacle)
+    }
+}
+
+// Note: The terrain under a cell. Walls are mirrored as `Cell::Obstacle` so
+// collisions only need to look at the cell; the other tiles change how the
+// snake moves while its head is on them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tile {
+    Floor,
+    Wall,
+    // Slows the snake down.
+    Mud,
+    // Speeds the snake up, and it slides straight on instead of turning.
+    Ice,
+    // Can only be entered moving in the given direction.
+    OneWay(Direction),
+    // A key the snake picks up by moving over it, which opens the matching doors.
+    Key(u8),
+    // Fatal to run into until the snake holds the key with the same number.
+    Door(u8),
+    // Banks the points at risk in risk mode when the snake's head moves onto it.
+    Safe,
+}
+
+impl Tile {
+    // Note: Multiplies the tick interval while the head is on the tile, in percent.
+    pub fn interval_percent(&self) -> u32 {
+        match self {
+            Self::Mud => 200,
+            Self::Ice => 50,
+            Self::Floor
+            | Self::Wall
+            | Self::OneWay(_)
+            | Self::Key(_)
+            | Self::Door(_)
+            | Self::Safe => 100,
+        }
+    }
+
+    // Keys and closed doors are kept clear of food and spawns.
+    pub fn is_clear(&self) -> bool {
+        !matches!(self, Self::Key(_) | Self::Door(_))
+    }
+
+    pub fn allows_entry(&self, moving: Direction) -> bool {
+        match self {
+            Self::OneWay(direction) => *direction == moving,
+            _ => true,
+        }
+    }
+}
+
+// Note: Which snake last painted a cell in territory mode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Owner {
+    Player,
+    Rival,
+}
+
+// Note: The grid mirrors what is on the board, one cell per point,
+// so lookups are O(1) instead of scanning the snake's body.
+// The game keeps it in sync whenever an entity moves, appears or disappears.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    // The territory layer, kept apart from the cells so painting survives
+    // whatever moves over or appears on a cell.
+    owners: Vec<Option<Owner>>,
+    tiles: Vec<Tile>,
+}
+
+impl Grid {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::Empty; width as usize * height as usize],
+            owners: vec![None; width as usize * height as usize],
+            tiles: vec![Tile::Floor; width as usize * height as usize],
+        }
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    pub fn get(&self, point: &Point) -> Cell {
+        if self.contains(point) {
+            self.cells[self.index(point)]
+        } else {
+            Cell::Obstacle
+        }
+    }
+
+    pub fn set(&mut self, point: &Point, cell: Cell) {
+        if self.contains(point) {
+            let index = self.index(point);
+            self.cells[index] = cell;
+        }
+    }
+
+    // Off the board counts as wall, like `get` counts it as an obstacle.
+    pub fn get_tile(&self, point: &Point) -> Tile {
+        if self.contains(point) {
+            self.tiles[self.index(point)]
+        } else {
+            Tile::Wall
+        }
+    }
+
+    pub fn set_tile(&mut self, point: &Point, tile: Tile) {
+        if self.contains(point) {
+            let index = self.index(point);
+            self.tiles[index] = tile;
+            if tile == Tile::Wall {
+                self.cells[index] = Cell::Obstacle;
+            }
+        }
+    }
+
+    pub fn get_owner(&self, point: &Point) -> Option<Owner> {
+        if self.contains(point) {
+            self.owners[self.index(point)]
+        } else {
+            None
+        }
+    }
+
+    pub fn paint(&mut self, point: &Point, owner: Owner) {
+        if self.contains(point) {
+            let index = self.index(point);
+            self.owners[index] = Some(owner);
+        }
+    }
+
+    pub fn painted_count(&self, owner: Owner) -> usize {
+        self.owners
+            .iter()
+            .filter(|painted| **painted == Some(owner))
+            .count()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = (Point, Cell)> + Clone + '_ {
+        self.cells.iter().enumerate().map(move |(i, cell)| {
+            (
+                Point::new(
+                    (i % self.width as usize) as u16,
+                    (i / self.width as usize) as u16,
+                ),
+                *cell,
+            )
+        })
+    }
+
+    pub fn free_points(&self) -> Vec<Point> {
+        self.iter_free_points().collect()
+    }
+
+    // Note: The same cells as `free_points` in the same order, without building
+    // the list, so picking one on a huge board does not allocate.
+    pub fn iter_free_points(&self) -> impl Iterator<Item = Point> + Clone + '_ {
+        self.points()
+            .filter(move |(point, cell)| *cell == Cell::Empty && self.get_tile(point).is_clear())
+            .map(|(point, _)| point)
+    }
+
+    pub fn is_free(&self, point: &Point) -> bool {
+        self.get(point) == Cell::Empty && self.get_tile(point).is_clear()
+    }
+
+    // One-way tiles are only gone into the way they point, so food is never
+    // placed where the snake cannot get to it.
+    pub fn reachable_from(&self, start: Point) -> HashSet<Point> {
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(start);
+        queue.push_back(start);
+
+        while let Some(point) = queue.pop_front() {
+            for direction in Direction::all().iter().copied() {
+                let neighbour = match point.neighbour_towards(direction, self.width, self.height) {
+                    Some(neighbour) => neighbour,
+                    None => continue,
+                };
+                if !self.get(&neighbour).is_blocking()
+                    && self.get_tile(&neighbour).allows_entry(direction)
+                    && visited.insert(neighbour)
+                {
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        visited
+    }
+
+    // Note: Open cells next to a wall, an obstacle or the snake's body.
+    // Cells on the edge of the board always count, since the border is a wall.
+    pub fn danger_zone(&self) -> Vec<Point> {
+        self.points()
+            .filter(|(point, cell)| {
+                if cell.is_blocking() {
+                    return false;
+                }
+
+                let neighbours = point.neighbours(self.width, self.height);
+                neighbours.len() < 4
+                    || neighbours
+                        .iter()
+                        .any(|neighbour| self.get(neighbour).is_blocking())
+            })
+            .map(|(point, _)| point)
+            .collect()
+    }
+
+    fn index(&self, point: &Point) -> usize {
+        point.y as usize * self.width as usize + point.x as usize
+    }
+}
@@ -0,0 +1,113 @@
+use crate::direction::Direction;
+use crate::grid::Cell;
+use crate::point::Point;
+use crate::snake::Snake;
+use crate::state::GameState;
+use std::collections::HashSet;
+use std::fmt;
+
+// Note: Something about the board that should always hold between ticks but
+// does not, which means a bug in the rules rather than anything the player did.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Violation {
+    // The segment at `index` is not next to the one before it.
+    Gap { snake: &'static str, index: usize },
+    Duplicate { snake: &'static str, point: Point },
+    FoodInSnake { point: Point },
+    // A snake is on a cell the grid does not have marked as snake.
+    GridOutOfSync { point: Point },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gap { snake, index } => {
+                write!(
+                    f,
+                    "{} segment {} is not next to the one before",
+                    snake, index
+                )
+            }
+            Self::Duplicate { snake, point } => {
+                write!(f, "{} has two segments at {},{}", snake, point.x, point.y)
+            }
+            Self::FoodInSnake { point } => {
+                write!(f, "food at {},{} is inside a snake", point.x, point.y)
+            }
+            Self::GridOutOfSync { point } => {
+                write!(
+                    f,
+                    "grid cell {},{} is not marked as snake",
+                    point.x, point.y
+                )
+            }
+        }
+    }
+}
+
+// Note: Checks every segment and food, which takes time and memory in
+// proportion to the snakes' length, so frontends only do this in debug
+// builds. Zen mode and extra lives let a snake pass through itself, so
+// overlapping segments are only reported when neither is on.
+pub fn check(state: &GameState) -> Vec<Violation> {
+    let rules = state.get_rules();
+    let overlap_allowed = rules.zen || rules.lives > 1;
+
+    let mut violations = Vec::new();
+    let snakes = std::iter::once(("snake", state.get_snake()))
+        .chain(state.get_rival().map(|rival| ("rival", rival)));
+    for (name, snake) in snakes {
+        check_snake(state, name, snake, overlap_allowed, &mut violations);
+    }
+
+    let foods = state
+        .get_food()
+        .into_iter()
+        .chain(state.get_extra_food().iter().copied())
+        .chain(state.get_chain_food().iter().map(|food| food.point));
+    for point in foods {
+        let eaten = state.get_snake().occupies(&point)
+            || state
+                .get_rival()
+                .is_some_and(|rival| rival.occupies(&point));
+        if eaten && !violations.contains(&Violation::FoodInSnake { point }) {
+            violations.push(Violation::FoodInSnake { point });
+        }
+    }
+
+    violations
+}
+
+fn check_snake(
+    state: &GameState,
+    name: &'static str,
+    snake: &Snake,
+    overlap_allowed: bool,
+    violations: &mut Vec<Violation>,
+) {
+    let segments = snake.get_segments();
+    let mut seen = HashSet::with_capacity(segments.len());
+    for (index, pair) in segments.windows(2).enumerate() {
+        let (before, segment) = (pair[0].point, pair[1].point);
+        let adjacent = Direction::all()
+            .iter()
+            .any(|direction| state.neighbour_towards(before, *direction) == Some(segment));
+        if !adjacent {
+            violations.push(Violation::Gap {
+                snake: name,
+                index: index + 1,
+            });
+        }
+    }
+
+    for segment in segments {
+        let point = segment.point;
+        if !seen.insert(point) && !overlap_allowed {
+            violations.push(Violation::Duplicate { snake: name, point });
+        }
+        // An invincible head can be on an obstacle, which the grid keeps.
+        if !matches!(state.get_grid().get(&point), Cell::Snake | Cell::Obstacle) {
+            violations.push(Violation::GridOutOfSync { point });
+        }
+    }
+}
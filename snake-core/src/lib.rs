@@ -0,0 +1,28 @@
+pub mod boss;
+pub mod direction;
+pub mod effects;
+pub mod env;
+pub mod ffi;
+pub mod food;
+pub mod grid;
+pub mod hazard;
+pub mod invariants;
+pub mod map;
+pub mod mapcheck;
+pub mod mapcode;
+pub mod mapgen;
+pub mod obstacle;
+pub mod point;
+pub mod policy;
+pub mod procgen;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod random;
+pub mod rules;
+pub mod scoring;
+pub mod segment;
+pub mod snake;
+pub mod snapshot;
+pub mod spawn;
+pub mod state;
+pub mod timeline;
@@ -0,0 +1,376 @@
+use crate::direction::Direction;
+use crate::grid::{Grid, Tile};
+use crate::obstacle::Patrol;
+use crate::point::Point;
+use crate::timeline::TimelineEvent;
+use std::error::Error;
+use std::fmt;
+
+// Note: Maps are plain text. Optional `key = value` header lines come first,
+// then one row of tiles per line:
+//
+//   name = Swamp
+//   patrol = 2,0 2,9 every 3
+//   ..........
+//   ..~~~~....
+//   ....##..**
+//
+// `.` is floor, `#` a wall, `~` mud, `*` ice, `$` a safe zone and `^`, `>`, `v`
+// and `<` one-way tiles that can only be entered moving the way they point. The
+// letters `a` to `f` are keys and `A` to `F` the doors they open. Lines starting
+// with `;` are comments.
+// Every `patrol` line adds an obstacle that walks the straight lines between the
+// given `x,y` waypoints and back, one cell every so many ticks (1 if left out).
+// `spawn = x,y direction` lines say where the snake starts and which way it
+// heads, the first for the player and the second for the rival. `at`, `every`
+// and `on` lines make up the map's timeline (see timeline).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Map {
+    name: String,
+    width: u16,
+    height: u16,
+    tiles: Vec<Tile>,
+    patrols: Vec<Patrol>,
+    spawns: Vec<(Point, Direction)>,
+    timeline: Vec<TimelineEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    NoRows,
+    TooLarge,
+    RaggedRow {
+        line: usize,
+    },
+    UnknownTile {
+        line: usize,
+        column: usize,
+        glyph: char,
+    },
+    BadPatrol {
+        line: usize,
+    },
+    BadSpawn {
+        line: usize,
+    },
+    BadEvent {
+        line: usize,
+    },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoRows => write!(f, "the map has no rows of tiles"),
+            Self::TooLarge => write!(f, "the map is too large"),
+            Self::RaggedRow { line } => {
+                write!(
+                    f,
+                    "line {}: every row has to be as wide as the first one",
+                    line
+                )
+            }
+            Self::UnknownTile {
+                line,
+                column,
+                glyph,
+            } => write!(
+                f,
+                "line {}, column {}: unknown tile '{}'",
+                line, column, glyph
+            ),
+            Self::BadPatrol { line } => write!(
+                f,
+                "line {}: patrols need waypoints on the board, each in line with the one before",
+                line
+            ),
+            Self::BadSpawn { line } => write!(
+                f,
+                "line {}: spawns need a cell on the board and a direction, e.g. 4,10 right",
+                line
+            ),
+            Self::BadEvent { line } => write!(
+                f,
+                "line {}: not a timeline event on the board, e.g. at 300 laser row 5",
+                line
+            ),
+        }
+    }
+}
+
+impl Error for MapError {}
+
+impl Map {
+    // An open board of the given size, which is what a run without a map plays on.
+    pub fn empty(width: u16, height: u16) -> Self {
+        Self {
+            name: String::new(),
+            width,
+            height,
+            tiles: vec![Tile::Floor; width as usize * height as usize],
+            patrols: Vec::new(),
+            spawns: Vec::new(),
+            timeline: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, MapError> {
+        let mut name = String::new();
+        let mut patrols = Vec::new();
+        let mut spawns = Vec::new();
+        let mut timeline = Vec::new();
+        let mut rows: Vec<Vec<Tile>> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            if rows.is_empty() {
+                if let Some((key, value)) = line.split_once('=') {
+                    match key.trim() {
+                        "name" => name = value.trim().to_string(),
+                        "patrol" => patrols.push((
+                            line_number,
+                            parse_patrol(value).ok_or(MapError::BadPatrol { line: line_number })?,
+                        )),
+                        "spawn" => spawns.push((
+                            line_number,
+                            parse_spawn(value).ok_or(MapError::BadSpawn { line: line_number })?,
+                        )),
+                        key @ ("at" | "every" | "on") => timeline.push((
+                            line_number,
+                            TimelineEvent::parse(key, value)
+                                .ok_or(MapError::BadEvent { line: line_number })?,
+                        )),
+                        _ => {}
+                    }
+                    continue;
+                }
+            }
+
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(column, glyph)| {
+                    tile_from_glyph(glyph).ok_or(MapError::UnknownTile {
+                        line: line_number,
+                        column: column + 1,
+                        glyph,
+                    })
+                })
+                .collect::<Result<Vec<Tile>, MapError>>()?;
+            if rows.first().is_some_and(|first| first.len() != row.len()) {
+                return Err(MapError::RaggedRow { line: line_number });
+            }
+            rows.push(row);
+        }
+
+        let width = rows.first().map(|row| row.len()).ok_or(MapError::NoRows)?;
+        if width > u16::MAX as usize || rows.len() > u16::MAX as usize {
+            return Err(MapError::TooLarge);
+        }
+
+        let (width, height) = (width as u16, rows.len() as u16);
+        let mut checked_patrols = Vec::new();
+        for (line, patrol) in patrols {
+            let on_board = patrol.cells().is_some_and(|cells| {
+                cells
+                    .iter()
+                    .all(|point| point.x < width && point.y < height)
+            });
+            if !on_board {
+                return Err(MapError::BadPatrol { line });
+            }
+            checked_patrols.push(patrol);
+        }
+        let mut checked_spawns = Vec::new();
+        for (line, (point, direction)) in spawns {
+            if point.x >= width || point.y >= height {
+                return Err(MapError::BadSpawn { line });
+            }
+            checked_spawns.push((point, direction));
+        }
+        let mut checked_timeline = Vec::new();
+        for (line, event) in timeline {
+            if !event.fits(width, height) {
+                return Err(MapError::BadEvent { line });
+            }
+            checked_timeline.push(event);
+        }
+
+        Ok(Self {
+            name,
+            width,
+            height,
+            tiles: rows.into_iter().flatten().collect(),
+            patrols: checked_patrols,
+            spawns: checked_spawns,
+            timeline: checked_timeline,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn get_patrols(&self) -> &[Patrol] {
+        &self.patrols
+    }
+
+    // Where the player's and then the rival's snake start, if the map says.
+    pub fn get_spawns(&self) -> &[(Point, Direction)] {
+        &self.spawns
+    }
+
+    pub fn set_spawns(&mut self, spawns: Vec<(Point, Direction)>) {
+        self.spawns = spawns;
+    }
+
+    pub fn get_timeline(&self) -> &[TimelineEvent] {
+        &self.timeline
+    }
+
+    pub fn get_tile(&self, point: &Point) -> Tile {
+        if point.x < self.width && point.y < self.height {
+            self.tiles[point.y as usize * self.width as usize + point.x as usize]
+        } else {
+            Tile::Wall
+        }
+    }
+
+    // Points off the map are left alone.
+    pub fn set_tile(&mut self, point: &Point, tile: Tile) {
+        if point.x < self.width && point.y < self.height {
+            self.tiles[point.y as usize * self.width as usize + point.x as usize] = tile;
+        }
+    }
+
+    // Note: The map as it would be written in a file, which parses back into the
+    // same map (comments and the original layout of the header are not kept).
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if !self.name.is_empty() {
+            text.push_str(&format!("name = {}\n", self.name));
+        }
+        for patrol in &self.patrols {
+            let waypoints: Vec<String> = patrol
+                .waypoints
+                .iter()
+                .map(|point| format!("{},{}", point.x, point.y))
+                .collect();
+            text.push_str(&format!(
+                "patrol = {} every {}\n",
+                waypoints.join(" "),
+                patrol.every
+            ));
+        }
+        for (point, direction) in &self.spawns {
+            text.push_str(&format!(
+                "spawn = {},{} {}\n",
+                point.x,
+                point.y,
+                direction.name()
+            ));
+        }
+        for event in &self.timeline {
+            text.push_str(&event.to_line());
+            text.push('\n');
+        }
+        for row in self.tiles.chunks(self.width.max(1) as usize) {
+            text.extend(row.iter().map(|tile| glyph_for_tile(*tile)));
+            text.push('\n');
+        }
+
+        text
+    }
+
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = Grid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let point = Point::new(x, y);
+                grid.set_tile(&point, self.get_tile(&point));
+            }
+        }
+
+        grid
+    }
+}
+
+// Parses `x,y x,y ... [every N]`.
+fn parse_patrol(value: &str) -> Option<Patrol> {
+    let mut waypoints = Vec::new();
+    let mut every = 1;
+    let mut words = value.split_whitespace();
+    while let Some(word) = words.next() {
+        if word == "every" {
+            every = words.next()?.parse().ok()?;
+            continue;
+        }
+
+        let (x, y) = word.split_once(',')?;
+        waypoints.push(Point::new(x.parse().ok()?, y.parse().ok()?));
+    }
+
+    if waypoints.is_empty() || every == 0 {
+        return None;
+    }
+    Some(Patrol { waypoints, every })
+}
+
+// Parses `x,y direction`.
+fn parse_spawn(value: &str) -> Option<(Point, Direction)> {
+    let (point, direction) = value.trim().split_once(' ')?;
+    let (x, y) = point.split_once(',')?;
+    Some((
+        Point::new(x.parse().ok()?, y.parse().ok()?),
+        Direction::parse(direction.trim())?,
+    ))
+}
+
+pub fn tile_from_glyph(glyph: char) -> Option<Tile> {
+    match glyph {
+        '.' => Some(Tile::Floor),
+        '#' => Some(Tile::Wall),
+        '~' => Some(Tile::Mud),
+        '*' => Some(Tile::Ice),
+        '^' => Some(Tile::OneWay(Direction::Up)),
+        '>' => Some(Tile::OneWay(Direction::Right)),
+        'v' => Some(Tile::OneWay(Direction::Down)),
+        '<' => Some(Tile::OneWay(Direction::Left)),
+        'a'..='f' => Some(Tile::Key(glyph as u8 - b'a')),
+        'A'..='F' => Some(Tile::Door(glyph as u8 - b'A')),
+        '$' => Some(Tile::Safe),
+        _ => None,
+    }
+}
+
+pub fn glyph_for_tile(tile: Tile) -> char {
+    match tile {
+        Tile::Floor => '.',
+        Tile::Wall => '#',
+        Tile::Mud => '~',
+        Tile::Ice => '*',
+        Tile::OneWay(Direction::Up) => '^',
+        Tile::OneWay(Direction::Right) => '>',
+        Tile::OneWay(Direction::Down) => 'v',
+        Tile::OneWay(Direction::Left) => '<',
+        Tile::Key(key) => (b'a' + key) as char,
+        Tile::Door(key) => (b'A' + key) as char,
+        Tile::Safe => '$',
+    }
+}
@@ -0,0 +1,391 @@
+use crate::direction::Direction;
+use crate::grid::{Cell, Grid, Tile};
+use crate::map::{glyph_for_tile, Map};
+use crate::point::Point;
+use crate::state::map_spawn;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::fmt;
+
+// A patrol this close to where the snake starts can hit it before the player reacts.
+const SPAWN_MARGIN: u32 = 2;
+// Listing more cells than this makes an issue hard to read.
+const MAX_LISTED_POINTS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    // No free cell for the snake to start on.
+    NoSpawn,
+    // The map's spawn with this number, counting from 0, has no room for a snake.
+    BlockedSpawn { spawn: usize },
+    // The patrol on the given header line, counting from 0, comes by the start.
+    PatrolAtSpawn { patrol: usize },
+    // Cells the snake can never get to from where it starts.
+    Unreachable,
+    // Unreachable cells that outnumber the ones the snake can get to, which is
+    // more likely a start in the wrong place than a pocket to wall off.
+    CutOff,
+    // The snake can get in but not back out, so food there is only eaten by crashing.
+    DeadEnd,
+    // Doors whose key is nowhere on the map, which makes them walls.
+    DoorWithoutKey(u8),
+    KeyWithoutDoor(u8),
+}
+
+// A problem with a map and the cells it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub points: Vec<Point>,
+}
+
+impl Issue {
+    // Whether `repair` fixes it. The others need the map's author to move things around.
+    pub fn is_trivial(&self) -> bool {
+        !matches!(
+            self.kind,
+            IssueKind::NoSpawn
+                | IssueKind::BlockedSpawn { .. }
+                | IssueKind::PatrolAtSpawn { .. }
+                | IssueKind::CutOff
+        )
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            IssueKind::NoSpawn => write!(f, "there is no room for the snake to start")?,
+            IssueKind::BlockedSpawn { spawn } => {
+                write!(f, "spawn {} has no room for a snake", spawn + 1)?
+            }
+            IssueKind::PatrolAtSpawn { patrol } => write!(
+                f,
+                "patrol {} comes within {} cells of the snake's start",
+                patrol + 1,
+                SPAWN_MARGIN
+            )?,
+            IssueKind::Unreachable => {
+                write!(f, "the snake cannot reach {}", cells(self.points.len()))?
+            }
+            IssueKind::CutOff => write!(
+                f,
+                "the snake starts cut off from {}",
+                cells(self.points.len())
+            )?,
+            IssueKind::DeadEnd => write!(
+                f,
+                "dead end of {} the snake cannot turn around in",
+                cells(self.points.len())
+            )?,
+            IssueKind::DoorWithoutKey(key) => {
+                write!(f, "door {} has no key", glyph_for_tile(Tile::Door(key)))?
+            }
+            IssueKind::KeyWithoutDoor(key) => {
+                write!(f, "key {} opens no door", glyph_for_tile(Tile::Key(key)))?
+            }
+        }
+
+        if !self.points.is_empty() {
+            let listed: Vec<String> = self
+                .points
+                .iter()
+                .take(MAX_LISTED_POINTS)
+                .map(|point| format!("{},{}", point.x, point.y))
+                .collect();
+            write!(f, " at {}", listed.join(" "))?;
+        }
+        if self.points.len() > MAX_LISTED_POINTS {
+            write!(f, " and {} more", self.points.len() - MAX_LISTED_POINTS)?;
+        }
+        Ok(())
+    }
+}
+
+fn cells(count: usize) -> String {
+    match count {
+        1 => "1 cell".to_string(),
+        _ => format!("{} cells", count),
+    }
+}
+
+// Note: Checks a map the way the game will play it, from where the snake
+// starts, with the edges of the board as walls (the wrap modifier is not taken
+// into account). Cells are `x,y` from the top left, counting from 0, as in
+// patrol waypoints.
+pub fn check(map: &Map) -> Vec<Issue> {
+    let mut issues = key_issues(map);
+    let grid = start_grid(map);
+    let (width, height) = (map.get_width(), map.get_height());
+    for (spawn, (head, direction)) in map.get_spawns().iter().enumerate() {
+        let tail = head.neighbour_towards(direction.opposite(), width, height);
+        if !grid.is_free(head) || !tail.is_some_and(|tail| grid.is_free(&tail)) {
+            issues.push(Issue {
+                kind: IssueKind::BlockedSpawn { spawn },
+                points: vec![*head],
+            });
+        }
+    }
+    let start = match map_spawn(&grid, map.get_spawns().first().copied(), Direction::Right) {
+        Some((head, _)) => head,
+        None => {
+            issues.insert(
+                0,
+                Issue {
+                    kind: IssueKind::NoSpawn,
+                    points: Vec::new(),
+                },
+            );
+            return issues;
+        }
+    };
+
+    for (patrol, route) in map.get_patrols().iter().enumerate() {
+        let near: Vec<Point> = route
+            .cells()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|point| {
+                let (dx, dy) = point.offset_to(&start);
+                dx.unsigned_abs() + dy.unsigned_abs() <= SPAWN_MARGIN
+            })
+            .collect();
+        if !near.is_empty() {
+            issues.push(Issue {
+                kind: IssueKind::PatrolAtSpawn { patrol },
+                points: near,
+            });
+        }
+    }
+
+    let keys_on_map = keys(map, map_points(map));
+    let (reached, held) = reachable(map, start);
+    let unreachable: Vec<Point> = map_points(map)
+        .filter(|point| !reached.contains(point))
+        .filter(|point| match map.get_tile(point) {
+            Tile::Wall => false,
+            // Reported as a door without a key already.
+            Tile::Door(key) => keys_on_map.contains(&key),
+            _ => true,
+        })
+        .collect();
+    for points in regions(map, unreachable) {
+        let kind = if points.len() > reached.len() {
+            IssueKind::CutOff
+        } else {
+            IssueKind::Unreachable
+        };
+        issues.push(Issue { kind, points });
+    }
+    for points in regions(map, dead_ends(map, reached, &held, start)) {
+        issues.push(Issue {
+            kind: IssueKind::DeadEnd,
+            points,
+        });
+    }
+
+    issues
+}
+
+// Note: Fixes the trivial issues: cells the snake cannot reach or only leave by
+// crashing become walls, as do doors without a key, and keys without a door
+// become floor. Fixing one can bring up others, like doors whose key was walled
+// off, so it goes on until nothing is left to fix. Cells on a patrol route are
+// left as they are, since a wall there would hold the patrol up for good.
+// Returns the repaired map and the issues that were fixed.
+pub fn repair(map: &Map) -> (Map, Vec<Issue>) {
+    let routes: HashSet<Point> = map
+        .get_patrols()
+        .iter()
+        .flat_map(|patrol| patrol.cells().unwrap_or_default())
+        .collect();
+    let mut repaired = map.clone();
+    let mut fixed = Vec::new();
+    loop {
+        let issues: Vec<Issue> = check(&repaired)
+            .into_iter()
+            .filter(|issue| issue.is_trivial())
+            .filter(|issue| issue.points.iter().any(|point| !routes.contains(point)))
+            .collect();
+        if issues.is_empty() {
+            return (repaired, fixed);
+        }
+
+        for issue in &issues {
+            let tile = match issue.kind {
+                IssueKind::KeyWithoutDoor(_) => Tile::Floor,
+                _ => Tile::Wall,
+            };
+            for point in issue.points.iter().filter(|point| !routes.contains(point)) {
+                repaired.set_tile(point, tile);
+            }
+        }
+        fixed.extend(issues);
+    }
+}
+
+// The board as the snake finds it when it starts, with the patrols where they start.
+fn start_grid(map: &Map) -> Grid {
+    let mut grid = map.to_grid();
+    for patrol in map.get_patrols() {
+        if let Some(first) = patrol.cells().and_then(|cells| cells.first().copied()) {
+            grid.set(&first, Cell::Obstacle);
+        }
+    }
+
+    grid
+}
+
+fn key_issues(map: &Map) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let keys_on_map = keys(map, map_points(map));
+    let doors_on_map: BTreeSet<u8> = map_points(map)
+        .filter_map(|point| match map.get_tile(&point) {
+            Tile::Door(key) => Some(key),
+            _ => None,
+        })
+        .collect();
+    for key in doors_on_map.difference(&keys_on_map) {
+        issues.push(Issue {
+            kind: IssueKind::DoorWithoutKey(*key),
+            points: map_points(map)
+                .filter(|point| map.get_tile(point) == Tile::Door(*key))
+                .collect(),
+        });
+    }
+    for key in keys_on_map.difference(&doors_on_map) {
+        issues.push(Issue {
+            kind: IssueKind::KeyWithoutDoor(*key),
+            points: map_points(map)
+                .filter(|point| map.get_tile(point) == Tile::Key(*key))
+                .collect(),
+        });
+    }
+
+    issues
+}
+
+fn map_points(map: &Map) -> impl Iterator<Item = Point> {
+    let (width, height) = (map.get_width(), map.get_height());
+    (0..height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+}
+
+fn keys(map: &Map, points: impl Iterator<Item = Point>) -> BTreeSet<u8> {
+    points
+        .filter_map(|point| match map.get_tile(&point) {
+            Tile::Key(key) => Some(key),
+            _ => None,
+        })
+        .collect()
+}
+
+// Note: Keys open their doors for good, so whenever the search picks up a key
+// it did not hold yet it goes again with the doors it opens. Returns the cells
+// reached and the keys held in the end.
+fn reachable(map: &Map, start: Point) -> (HashSet<Point>, BTreeSet<u8>) {
+    let mut held = BTreeSet::new();
+    loop {
+        let reached = flood(map, start, &held);
+        let picked_up = keys(map, reached.iter().copied());
+        if picked_up == held {
+            return (reached, held);
+        }
+        held = picked_up;
+    }
+}
+
+fn flood(map: &Map, start: Point, held: &BTreeSet<u8>) -> HashSet<Point> {
+    let (width, height) = (map.get_width(), map.get_height());
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    visited.insert(start);
+    queue.push_back(start);
+
+    while let Some(point) = queue.pop_front() {
+        for direction in Direction::all().iter().copied() {
+            if let Some(next) = point.neighbour_towards(direction, width, height) {
+                if can_enter(map, &next, direction, held) && visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    visited
+}
+
+fn can_enter(map: &Map, point: &Point, moving: Direction, held: &BTreeSet<u8>) -> bool {
+    match map.get_tile(point) {
+        Tile::Wall => false,
+        Tile::Door(key) => held.contains(&key),
+        tile => tile.allows_entry(moving),
+    }
+}
+
+// Note: A cell is a dead end when, whichever way the snake comes in, the only
+// way on is back where it came from, which its own body is in. Taking a dead
+// end away can make the cell before it one, so whole blind corridors are found
+// by going over the cells again until nothing changes. The start is never one,
+// since the snake does not have to come in to it.
+fn dead_ends(map: &Map, mut open: HashSet<Point>, held: &BTreeSet<u8>, start: Point) -> Vec<Point> {
+    let (width, height) = (map.get_width(), map.get_height());
+    let mut dead = Vec::new();
+    loop {
+        let found: Vec<Point> = open
+            .iter()
+            .copied()
+            .filter(|point| *point != start)
+            .filter(|point| {
+                let entries: Vec<Point> = Direction::all()
+                    .iter()
+                    .copied()
+                    .filter(|direction| can_enter(map, point, *direction, held))
+                    .filter_map(|direction| {
+                        point.neighbour_towards(direction.opposite(), width, height)
+                    })
+                    .filter(|from| open.contains(from))
+                    .collect();
+                let exits: Vec<Point> = Direction::all()
+                    .iter()
+                    .copied()
+                    .filter_map(|direction| {
+                        let to = point.neighbour_towards(direction, width, height)?;
+                        Some(to)
+                            .filter(|to| open.contains(to) && can_enter(map, to, direction, held))
+                    })
+                    .collect();
+                entries.iter().all(|from| exits.iter().all(|to| to == from))
+            })
+            .collect();
+        if found.is_empty() {
+            return dead;
+        }
+
+        for point in &found {
+            open.remove(point);
+        }
+        dead.extend(found);
+    }
+}
+
+// Groups the points into the regions they make up, each in reading order.
+fn regions(map: &Map, points: Vec<Point>) -> Vec<Vec<Point>> {
+    let (width, height) = (map.get_width(), map.get_height());
+    let mut left: BTreeSet<(u16, u16)> = points.iter().map(|point| (point.y, point.x)).collect();
+    let mut regions = Vec::new();
+    while let Some((y, x)) = left.pop_first() {
+        let mut region = vec![Point::new(x, y)];
+        let mut queue = VecDeque::from([Point::new(x, y)]);
+        while let Some(point) = queue.pop_front() {
+            for neighbour in point.neighbours(width, height) {
+                if left.remove(&(neighbour.y, neighbour.x)) {
+                    region.push(neighbour);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        region.sort_by_key(|point| (point.y, point.x));
+        regions.push(region);
+    }
+
+    regions
+}
@@ -0,0 +1,182 @@
+use crate::map::{Map, MapError};
+use std::error::Error;
+use std::fmt;
+
+const VERSION: u8 = 1;
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+// A copy of earlier text is marked by this byte, followed by its length and how far back it starts.
+const COPY: u8 = 0;
+// Shorter copies would take more bytes than the text they stand for.
+const MIN_COPY: usize = 5;
+const MAX_COPY: usize = 255;
+const WINDOW: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapCodeError {
+    // Mistyped, cut short or not a map code at all.
+    Invalid,
+    Map(MapError),
+}
+
+impl fmt::Display for MapCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "not a valid map code"),
+            Self::Map(error) => write!(f, "the map code holds a broken map: {}", error),
+        }
+    }
+}
+
+impl Error for MapCodeError {}
+
+// Note: A map code is the map's text (see map) squeezed and written in URL-safe
+// base64, so it can be pasted into a chat or a command line. The text is
+// squeezed by replacing repeats with copies of what came before, which is what
+// maps are mostly made of, and a checksum at the end rejects mistyped codes.
+pub fn encode(map: &Map) -> String {
+    let text = map.to_text();
+    let mut bytes = vec![VERSION];
+    bytes.extend(squeeze(text.as_bytes()));
+    bytes.extend(checksum(text.as_bytes()).to_be_bytes());
+
+    to_base64(&bytes)
+}
+
+pub fn decode(code: &str) -> Result<Map, MapCodeError> {
+    let bytes = from_base64(code).ok_or(MapCodeError::Invalid)?;
+    let (version, rest) = bytes.split_first().ok_or(MapCodeError::Invalid)?;
+    if *version != VERSION || rest.len() < 2 {
+        return Err(MapCodeError::Invalid);
+    }
+
+    let (squeezed, check) = rest.split_at(rest.len() - 2);
+    let text = unsqueeze(squeezed).ok_or(MapCodeError::Invalid)?;
+    if checksum(&text).to_be_bytes() != check {
+        return Err(MapCodeError::Invalid);
+    }
+    let text = String::from_utf8(text).map_err(|_| MapCodeError::Invalid)?;
+
+    Map::parse(&text).map_err(MapCodeError::Map)
+}
+
+// Each copy takes the longest match in the window, the nearest one when there are
+// several, and may overlap itself, so a row of the same tile becomes a copy of
+// the tile before it.
+fn squeeze(text: &[u8]) -> Vec<u8> {
+    let mut squeezed = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        let longest = MAX_COPY.min(text.len() - i);
+        let mut best = (0, 0);
+        for start in (i.saturating_sub(WINDOW)..i).rev() {
+            let length = (0..longest)
+                .take_while(|n| text[start + n] == text[i + n])
+                .count();
+            if length > best.0 {
+                best = (length, i - start);
+                if length == longest {
+                    break;
+                }
+            }
+        }
+
+        let (length, distance) = best;
+        if length >= MIN_COPY {
+            squeezed.extend([COPY, length as u8]);
+            squeezed.extend((distance as u16).to_be_bytes());
+            i += length;
+        } else {
+            // A copy of length 0 stands for the marker byte itself.
+            if text[i] == COPY {
+                squeezed.extend([COPY, 0]);
+            } else {
+                squeezed.push(text[i]);
+            }
+            i += 1;
+        }
+    }
+
+    squeezed
+}
+
+fn unsqueeze(squeezed: &[u8]) -> Option<Vec<u8>> {
+    let mut text = Vec::new();
+    let mut i = 0;
+    while i < squeezed.len() {
+        if squeezed[i] != COPY {
+            text.push(squeezed[i]);
+            i += 1;
+            continue;
+        }
+
+        let length = *squeezed.get(i + 1)? as usize;
+        if length == 0 {
+            text.push(COPY);
+            i += 2;
+            continue;
+        }
+        let distance = u16::from_be_bytes([*squeezed.get(i + 2)?, *squeezed.get(i + 3)?]) as usize;
+        if distance == 0 || distance > text.len() {
+            return None;
+        }
+        let start = text.len() - distance;
+        for n in 0..length {
+            text.push(text[start + n]);
+        }
+        i += 4;
+    }
+
+    Some(text)
+}
+
+// FNV-1a, folded to 16 bits.
+fn checksum(bytes: &[u8]) -> u16 {
+    let hash = bytes.iter().fold(0x811c_9dc5u32, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    });
+    ((hash >> 16) ^ (hash & 0xffff)) as u16
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            code.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+
+    code
+}
+
+// Whitespace is skipped, since long codes tend to get wrapped when pasted.
+fn from_base64(code: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u32> = code
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .map(|byte| {
+            ALPHABET
+                .iter()
+                .position(|digit| *digit == byte)
+                .map(|d| d as u32)
+        })
+        .collect::<Option<_>>()?;
+
+    let mut bytes = Vec::new();
+    for chunk in digits.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, digit)| group | digit << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Some(bytes)
+}
@@ -0,0 +1,516 @@
+use crate::direction::Direction;
+use crate::grid::{Cell, Tile};
+use crate::map::Map;
+use crate::mapcheck;
+use crate::point::Point;
+use crate::random;
+use crate::state::find_spawn_near;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashSet, VecDeque};
+
+// Areas are not split into parts smaller than this, or rooms smaller than MIN_ROOM.
+const MIN_AREA: u16 = 6;
+const MIN_ROOM: u16 = 3;
+const CAVE_WALL_PERCENT: u32 = 45;
+const CAVE_SMOOTHING_STEPS: usize = 4;
+// Caves are made again until they keep this much of the board open.
+const MIN_CAVE_OPEN_PERCENT: usize = 40;
+const CAVE_ATTEMPTS: usize = 10;
+// Symmetric boards are made again until one passes mapcheck, this many times at most.
+const SYMMETRY_ATTEMPTS: usize = 10;
+
+// Note: Generated boards, each drawn from a seed so the same seed and size
+// always give the same board. The generated map is named after its kind and
+// seed, which shows up in the run info, so a board played once can be made again.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MapGen {
+    // A maze of one cell wide corridors, with loops instead of dead ends.
+    Maze,
+    // Rooms joined by corridors.
+    Rooms,
+    // Open caves with rough walls.
+    Caves,
+}
+
+impl MapGen {
+    pub fn all() -> [MapGen; 3] {
+        [MapGen::Maze, MapGen::Rooms, MapGen::Caves]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|kind| kind.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapGen::Maze => "maze",
+            MapGen::Rooms => "rooms",
+            MapGen::Caves => "caves",
+        }
+    }
+
+    // Note: Whatever the generator makes goes through mapcheck's repair, which
+    // walls off any cell the snake could not reach from its start and any dead
+    // end, so every generated board is in one piece. A symmetric board is made
+    // again until it passes mapcheck with nothing left to report, and comes out
+    // open if that never happens on a board this size.
+    pub fn generate(&self, width: u16, height: u16, seed: u64, symmetry: Option<Symmetry>) -> Map {
+        let mut rng = random::seeded(seed);
+        let mut name = self.name().to_string();
+        name[..1].make_ascii_uppercase();
+        let name = match symmetry {
+            Some(symmetry) => format!("{} {:x} {}", name, seed, symmetry.name()),
+            None => format!("{} {:x}", name, seed),
+        };
+
+        let symmetry = match symmetry {
+            Some(symmetry) => symmetry,
+            None => {
+                let mut map = self.layout(width, height, &mut rng);
+                map.set_name(&name);
+                return mapcheck::repair(&map).0;
+            }
+        };
+        for _ in 0..SYMMETRY_ATTEMPTS {
+            let mut map = symmetry.apply(&self.layout(width, height, &mut rng));
+            let spawns = match symmetry.spawns(&map) {
+                Some(spawns) => spawns,
+                None => continue,
+            };
+            symmetry.join(&mut map, spawns[0].0);
+            map.set_spawns(spawns);
+            let (mut repaired, _) = mapcheck::repair(&map);
+            if symmetry.holds_for(&repaired) && mapcheck::check(&repaired).is_empty() {
+                repaired.set_name(&name);
+                return repaired;
+            }
+        }
+
+        let mut map = Map::empty(width, height);
+        map.set_spawns(symmetry.spawns(&map).unwrap_or_default());
+        map.set_name(&name);
+        map
+    }
+
+    fn layout<R: Rng>(&self, width: u16, height: u16, rng: &mut R) -> Map {
+        match self {
+            MapGen::Maze => maze(width, height, rng),
+            MapGen::Rooms => rooms(width, height, rng),
+            MapGen::Caves => caves(width, height, rng),
+        }
+    }
+}
+
+// Note: Symmetric boards are for games with a rival on the same board: the
+// second half of the board is the first half turned half way round the middle
+// (point) or mirrored left to right (mirror), and the two snakes start on cells
+// paired the same way, heading the paired ways, so neither has the better start.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Symmetry {
+    Point,
+    Mirror,
+}
+
+impl Symmetry {
+    pub fn all() -> [Symmetry; 2] {
+        [Symmetry::Point, Symmetry::Mirror]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|symmetry| symmetry.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::Point => "point",
+            Symmetry::Mirror => "mirror",
+        }
+    }
+
+    // The cell paired with the given one on a board of the given size.
+    pub fn image(&self, point: Point, width: u16, height: u16) -> Point {
+        match self {
+            Symmetry::Point => Point::new(width - 1 - point.x, height - 1 - point.y),
+            Symmetry::Mirror => Point::new(width - 1 - point.x, point.y),
+        }
+    }
+
+    pub fn image_direction(&self, direction: Direction) -> Direction {
+        match (self, direction) {
+            (Symmetry::Point, direction) => direction.opposite(),
+            (Symmetry::Mirror, Direction::Left | Direction::Right) => direction.opposite(),
+            (Symmetry::Mirror, direction) => direction,
+        }
+    }
+
+    // Note: The check symmetric boards are generated against: every tile is
+    // the same as the one on its paired cell, and the map has two spawns, each
+    // paired with the other. Patrols are not taken into account.
+    pub fn holds_for(&self, map: &Map) -> bool {
+        let (width, height) = (map.get_width(), map.get_height());
+        let tiles_match = points(width, height)
+            .all(|point| map.get_tile(&point) == map.get_tile(&self.image(point, width, height)));
+        let spawns_match = match map.get_spawns() {
+            [(head, direction), rival] => {
+                *rival
+                    == (
+                        self.image(*head, width, height),
+                        self.image_direction(*direction),
+                    )
+            }
+            _ => false,
+        };
+
+        tiles_match && spawns_match
+    }
+
+    // Copies every cell earlier in reading order than its pair onto the pair.
+    fn apply(&self, map: &Map) -> Map {
+        let (width, height) = (map.get_width(), map.get_height());
+        let mut symmetric = map.clone();
+        for point in points(width, height) {
+            let image = self.image(point, width, height);
+            if (image.y, image.x) < (point.y, point.x) {
+                symmetric.set_tile(&point, map.get_tile(&image));
+            }
+        }
+
+        symmetric
+    }
+
+    // Carves a corridor from the start to its pair, along a row and then down
+    // or up a column, and the pair of that corridor too, as the two halves of
+    // a board are not always open to each other.
+    fn join(&self, map: &mut Map, start: Point) {
+        let (width, height) = (map.get_width(), map.get_height());
+        let end = self.image(start, width, height);
+        let corner = Point::new(end.x, start.y);
+        for (a, b) in [(start, corner), (corner, end)].iter().copied() {
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                for y in a.y.min(b.y)..=a.y.max(b.y) {
+                    let point = Point::new(x, y);
+                    map.set_tile(&point, Tile::Floor);
+                    map.set_tile(&self.image(point, width, height), Tile::Floor);
+                }
+            }
+        }
+    }
+
+    // The player starts in the left half, as near the middle of it as fits, and
+    // the rival on the paired cell, in the right half. The player heads up or
+    // down, since heading right would send the two of them at each other.
+    fn spawns(&self, map: &Map) -> Option<Vec<(Point, Direction)>> {
+        let (width, height) = (map.get_width(), map.get_height());
+        let mut grid = map.to_grid();
+        for point in points(width, height).filter(|point| point.x >= width / 2) {
+            grid.set(&point, Cell::Obstacle);
+        }
+        let (head, direction) = find_spawn_near(
+            &grid,
+            &[Direction::Up, Direction::Down],
+            Point::new(width / 4, height / 2),
+        )?;
+
+        Some(vec![
+            (head, direction),
+            (
+                self.image(head, width, height),
+                self.image_direction(direction),
+            ),
+        ])
+    }
+}
+
+// Note: A recursive backtracker on the cells with even coordinates, knocking
+// out the wall between each cell and the next. A perfect maze is all dead ends,
+// which the snake cannot turn around in, so afterwards every cell with a single
+// way out gets another one. An even sized board has a row or column to spare,
+// which is left open as a lane along the edge.
+fn maze<R: Rng>(width: u16, height: u16, rng: &mut R) -> Map {
+    let mut map = filled(width, height);
+    let is_cell = |point: &Point| point.x.is_multiple_of(2) && point.y.is_multiple_of(2);
+    let cells_towards = |point: &Point| -> Vec<(Point, Point)> {
+        let mut next = Vec::new();
+        if point.x >= 2 {
+            next.push((
+                Point::new(point.x - 1, point.y),
+                Point::new(point.x - 2, point.y),
+            ));
+        }
+        if point.y >= 2 {
+            next.push((
+                Point::new(point.x, point.y - 1),
+                Point::new(point.x, point.y - 2),
+            ));
+        }
+        if point.x + 2 < width {
+            next.push((
+                Point::new(point.x + 1, point.y),
+                Point::new(point.x + 2, point.y),
+            ));
+        }
+        if point.y + 2 < height {
+            next.push((
+                Point::new(point.x, point.y + 1),
+                Point::new(point.x, point.y + 2),
+            ));
+        }
+        next
+    };
+
+    let start = Point::new(0, 0);
+    map.set_tile(&start, Tile::Floor);
+    let mut stack = vec![start];
+    while let Some(point) = stack.last().copied() {
+        let unvisited: Vec<(Point, Point)> = cells_towards(&point)
+            .into_iter()
+            .filter(|(_, cell)| map.get_tile(cell) == Tile::Wall)
+            .collect();
+        match unvisited.choose(rng) {
+            Some((between, cell)) => {
+                map.set_tile(between, Tile::Floor);
+                map.set_tile(cell, Tile::Floor);
+                stack.push(*cell);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    for point in points(width, height) {
+        if (width.is_multiple_of(2) && point.x == width - 1)
+            || (height.is_multiple_of(2) && point.y == height - 1)
+        {
+            map.set_tile(&point, Tile::Floor);
+        }
+    }
+
+    for cell in points(width, height).filter(is_cell) {
+        let open = cell
+            .neighbours(width, height)
+            .iter()
+            .filter(|point| map.get_tile(point) != Tile::Wall)
+            .count();
+        if open > 1 {
+            continue;
+        }
+        let walls: Vec<Point> = cells_towards(&cell)
+            .into_iter()
+            .map(|(between, _)| between)
+            .filter(|between| map.get_tile(between) == Tile::Wall)
+            .collect();
+        if let Some(between) = walls.choose(rng) {
+            map.set_tile(between, Tile::Floor);
+        }
+    }
+
+    map
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Area {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+}
+
+// Note: Binary space partitioning: the board is split in two, across its
+// longer side, and each part again until the parts are too small to split.
+// Every part gets a room, and the two halves of every split are joined by a
+// corridor between a room in each, so all rooms hang together.
+fn rooms<R: Rng>(width: u16, height: u16, rng: &mut R) -> Map {
+    let mut map = filled(width, height);
+    let area = Area {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    };
+    split(&mut map, area, rng);
+
+    map
+}
+
+// Carves the rooms of the area and returns a cell in one of them.
+fn split<R: Rng>(map: &mut Map, area: Area, rng: &mut R) -> Point {
+    let (wide, tall) = (area.width >= 2 * MIN_AREA, area.height >= 2 * MIN_AREA);
+    let (first, second) = if wide && (area.width >= area.height || !tall) {
+        let at = rng.gen_range(MIN_AREA, area.width - MIN_AREA + 1);
+        (
+            Area { width: at, ..area },
+            Area {
+                x: area.x + at,
+                width: area.width - at,
+                ..area
+            },
+        )
+    } else if tall {
+        let at = rng.gen_range(MIN_AREA, area.height - MIN_AREA + 1);
+        (
+            Area { height: at, ..area },
+            Area {
+                y: area.y + at,
+                height: area.height - at,
+                ..area
+            },
+        )
+    } else {
+        return room(map, area, rng);
+    };
+
+    let from = split(map, first, rng);
+    let to = split(map, second, rng);
+    corridor(map, from, to, rng);
+    if rng.gen() {
+        from
+    } else {
+        to
+    }
+}
+
+// A room anywhere in the area, leaving a wall on its right and bottom to keep
+// it apart from the next one. Returns its middle.
+fn room<R: Rng>(map: &mut Map, area: Area, rng: &mut R) -> Point {
+    let width = room_size(area.width, rng);
+    let height = room_size(area.height, rng);
+    let x = area.x + rng.gen_range(0, (area.width - width).max(1));
+    let y = area.y + rng.gen_range(0, (area.height - height).max(1));
+    for dy in 0..height {
+        for dx in 0..width {
+            map.set_tile(&Point::new(x + dx, y + dy), Tile::Floor);
+        }
+    }
+
+    Point::new(x + width / 2, y + height / 2)
+}
+
+// Boards too small for a wall next to the room get a room as large as the area.
+fn room_size<R: Rng>(room: u16, rng: &mut R) -> u16 {
+    let most = if room > MIN_ROOM { room - 1 } else { room };
+    rng.gen_range(MIN_ROOM.min(most), most + 1)
+}
+
+// Goes along one axis and then the other, which one first picked at random.
+fn corridor<R: Rng>(map: &mut Map, from: Point, to: Point, rng: &mut R) {
+    let corner = if rng.gen() {
+        Point::new(to.x, from.y)
+    } else {
+        Point::new(from.x, to.y)
+    };
+    for (a, b) in [(from, corner), (corner, to)].iter().copied() {
+        for x in a.x.min(b.x)..=a.x.max(b.x) {
+            for y in a.y.min(b.y)..=a.y.max(b.y) {
+                map.set_tile(&Point::new(x, y), Tile::Floor);
+            }
+        }
+    }
+}
+
+// Note: A cellular automaton: the board starts as random walls and floor, and
+// every step a cell becomes a wall when most of its eight neighbours are walls
+// (off the board counts as wall) and floor when few of them are, which grows
+// the noise into caves. Only the largest cave is kept, and caves that keep too
+// little of the board open are made again.
+fn caves<R: Rng>(width: u16, height: u16, rng: &mut R) -> Map {
+    let mut map = filled(width, height);
+    for _ in 0..CAVE_ATTEMPTS {
+        for point in points(width, height) {
+            let tile = if rng.gen_range(0, 100) < CAVE_WALL_PERCENT {
+                Tile::Wall
+            } else {
+                Tile::Floor
+            };
+            map.set_tile(&point, tile);
+        }
+        for _ in 0..CAVE_SMOOTHING_STEPS {
+            let before = map.clone();
+            for point in points(width, height) {
+                match walls_around(&before, point) {
+                    5..=8 => map.set_tile(&point, Tile::Wall),
+                    0..=3 => map.set_tile(&point, Tile::Floor),
+                    _ => {}
+                }
+            }
+        }
+
+        let largest: HashSet<Point> = largest_cave(&map).into_iter().collect();
+        if largest.len() * 100 >= width as usize * height as usize * MIN_CAVE_OPEN_PERCENT {
+            for point in points(width, height) {
+                if !largest.contains(&point) {
+                    map.set_tile(&point, Tile::Wall);
+                }
+            }
+            return map;
+        }
+    }
+
+    // Boards too small for caves to form are left open.
+    Map::empty(width, height)
+}
+
+fn walls_around(map: &Map, point: Point) -> usize {
+    let mut walls = 0;
+    for dy in -1i32..=1 {
+        for dx in -1i32..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let (x, y) = (point.x as i32 + dx, point.y as i32 + dy);
+            let off_board =
+                x < 0 || y < 0 || x >= map.get_width() as i32 || y >= map.get_height() as i32;
+            if off_board || map.get_tile(&Point::new(x as u16, y as u16)) == Tile::Wall {
+                walls += 1;
+            }
+        }
+    }
+
+    walls
+}
+
+fn largest_cave(map: &Map) -> Vec<Point> {
+    let (width, height) = (map.get_width(), map.get_height());
+    let mut seen = vec![false; width as usize * height as usize];
+    let index = |point: &Point| point.y as usize * width as usize + point.x as usize;
+    let mut largest = Vec::new();
+    for start in points(width, height) {
+        if seen[index(&start)] || map.get_tile(&start) == Tile::Wall {
+            continue;
+        }
+
+        seen[index(&start)] = true;
+        let mut cave = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(point) = queue.pop_front() {
+            for neighbour in point.neighbours(width, height) {
+                if !seen[index(&neighbour)] && map.get_tile(&neighbour) != Tile::Wall {
+                    seen[index(&neighbour)] = true;
+                    cave.push(neighbour);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        if cave.len() > largest.len() {
+            largest = cave;
+        }
+    }
+
+    largest
+}
+
+fn filled(width: u16, height: u16) -> Map {
+    let mut map = Map::empty(width, height);
+    for point in points(width, height) {
+        map.set_tile(&point, Tile::Wall);
+    }
+
+    map
+}
+
+fn points(width: u16, height: u16) -> impl Iterator<Item = Point> {
+    (0..height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+}
@@ -0,0 +1,97 @@
+use crate::point::Point;
+
+// Note: A patrol route as written in a map: straight lines between waypoints,
+// walked one cell every `every` ticks, forwards and then back again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Patrol {
+    pub waypoints: Vec<Point>,
+    pub every: u16,
+}
+
+impl Patrol {
+    // Returns None unless consecutive waypoints share a row or a column.
+    pub fn cells(&self) -> Option<Vec<Point>> {
+        let mut cells = vec![*self.waypoints.first()?];
+        for pair in self.waypoints.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if from.x != to.x && from.y != to.y {
+                return None;
+            }
+
+            let mut current = from;
+            while current != to {
+                current = Point::new(step_towards(current.x, to.x), step_towards(current.y, to.y));
+                cells.push(current);
+            }
+        }
+
+        Some(cells)
+    }
+}
+
+fn step_towards(from: u16, to: u16) -> u16 {
+    match from.cmp(&to) {
+        std::cmp::Ordering::Less => from + 1,
+        std::cmp::Ordering::Greater => from - 1,
+        std::cmp::Ordering::Equal => from,
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MovingObstacle {
+    path: Vec<Point>,
+    index: usize,
+    forwards: bool,
+    every: u16,
+}
+
+impl MovingObstacle {
+    pub fn new(path: Vec<Point>, every: u16) -> Self {
+        Self {
+            path,
+            index: 0,
+            forwards: true,
+            every: every.max(1),
+        }
+    }
+
+    pub fn get_point(&self) -> Point {
+        self.path[self.index]
+    }
+
+    pub fn get_every(&self) -> u16 {
+        self.every
+    }
+
+    // Turns around at either end of the path. Returns None for a path of one cell.
+    pub fn next_point(&self) -> Option<Point> {
+        self.next_index().map(|index| self.path[index])
+    }
+
+    pub fn advance(&mut self) {
+        if let Some(index) = self.next_index() {
+            self.forwards = index > self.index;
+            self.index = index;
+        }
+    }
+
+    fn next_index(&self) -> Option<usize> {
+        if self.path.len() < 2 {
+            return None;
+        }
+
+        let at_end = if self.forwards {
+            self.index + 1 == self.path.len()
+        } else {
+            self.index == 0
+        };
+        let forwards = self.forwards != at_end;
+        Some(if forwards {
+            self.index + 1
+        } else {
+            self.index - 1
+        })
+    }
+}
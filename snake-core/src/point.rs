@@ -0,0 +1,98 @@
+use crate::direction::Direction;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub x: u16,
+    pub y: u16,
+}
+
+impl Point {
+    pub fn new(x: u16, y: u16) -> Self {
+        Self { x, y }
+    }
+
+    pub fn transform(&self, direction: Direction, times: u16) -> Self {
+        let times = times as i32;
+        let transformation = match direction {
+            Direction::Up => (0, -times),
+            Direction::Right => (times, 0),
+            Direction::Down => (0, times),
+            Direction::Left => (-times, 0),
+        };
+
+        Self::new(
+            Self::transform_value(self.x, transformation.0),
+            Self::transform_value(self.y, transformation.1),
+        )
+    }
+
+    pub fn offset_to(&self, other: &Point) -> (i32, i32) {
+        (
+            other.x as i32 - self.x as i32,
+            other.y as i32 - self.y as i32,
+        )
+    }
+
+    // Returns None when the neighbour would be off a board of the given size.
+    pub fn neighbour_towards(
+        &self,
+        direction: Direction,
+        width: u16,
+        height: u16,
+    ) -> Option<Point> {
+        let leaves_board = match direction {
+            Direction::Up => self.y == 0,
+            Direction::Right => self.x + 1 >= width,
+            Direction::Down => self.y + 1 >= height,
+            Direction::Left => self.x == 0,
+        };
+
+        if leaves_board {
+            None
+        } else {
+            Some(self.transform(direction, 1))
+        }
+    }
+
+    // Like `neighbour_towards`, but leaving the board comes back in on the other side.
+    pub fn wrapping_neighbour(&self, direction: Direction, width: u16, height: u16) -> Point {
+        let wrap =
+            |value: u16, step: u16, size: u16| ((value as u32 + step as u32) % size as u32) as u16;
+        match direction {
+            Direction::Up => Point::new(self.x, wrap(self.y, height - 1, height)),
+            Direction::Right => Point::new(wrap(self.x, 1, width), self.y),
+            Direction::Down => Point::new(self.x, wrap(self.y, 1, height)),
+            Direction::Left => Point::new(wrap(self.x, width - 1, width), self.y),
+        }
+    }
+
+    pub fn neighbours(&self, width: u16, height: u16) -> Vec<Point> {
+        let mut neighbours = Vec::with_capacity(4);
+        if self.y > 0 {
+            neighbours.push(self.transform(Direction::Up, 1));
+        }
+        if self.x + 1 < width {
+            neighbours.push(self.transform(Direction::Right, 1));
+        }
+        if self.y + 1 < height {
+            neighbours.push(self.transform(Direction::Down, 1));
+        }
+        if self.x > 0 {
+            neighbours.push(self.transform(Direction::Left, 1));
+        }
+
+        neighbours
+    }
+
+    fn transform_value(value: u16, by: i32) -> u16 {
+        if by.is_negative() && by.unsigned_abs() > value as u32 {
+            panic!(
+                "Transforming value {} by {} would result in a negative number",
+                value, by
+            );
+        } else {
+            (value as i32 + by) as u16
+        }
+    }
+}
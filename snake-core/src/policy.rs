@@ -0,0 +1,247 @@
+use crate::direction::Direction;
+use crate::grid::{Cell, Tile};
+use crate::point::Point;
+use crate::snake::Snake;
+use crate::state::GameState;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Debug;
+
+// Note: A policy is the brain of a computer controlled snake. It is asked for a
+// direction once per tick and gets the snake it steers passed in, so the same
+// policy can drive the rival or the player's own snake.
+pub trait Policy: Debug {
+    fn choose(&mut self, state: &GameState, snake: &Snake) -> Direction;
+
+    // A copy of the policy as it is now, random state included, e.g. for a
+    // replay to go back to.
+    fn clone_box(&self) -> Box<dyn Policy>;
+}
+
+impl Clone for Box<dyn Policy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RivalLevel {
+    Random,
+    Greedy,
+    AStar,
+}
+
+impl RivalLevel {
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.to_lowercase().as_str() {
+            "random" | "easy" | "1" => Some(Self::Random),
+            "greedy" | "medium" | "2" => Some(Self::Greedy),
+            "astar" | "a*" | "hard" | "3" => Some(Self::AStar),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Greedy => "greedy",
+            Self::AStar => "astar",
+        }
+    }
+
+    pub fn new_policy(&self, seed: u64) -> Box<dyn Policy> {
+        match self {
+            Self::Random => Box::new(RandomWalker::new(seed)),
+            Self::Greedy => Box::new(GreedyPolicy),
+            Self::AStar => Box::new(AStarPolicy),
+        }
+    }
+}
+
+// Wanders around, turning at random now and then, but never straight into a crash
+// if it can help it.
+#[derive(Debug, Clone)]
+pub struct RandomWalker {
+    rng: StdRng,
+}
+
+impl RandomWalker {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Policy for RandomWalker {
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn choose(&mut self, state: &GameState, snake: &Snake) -> Direction {
+        let current = snake.get_direction();
+        if !state.is_fatal_move(snake, current) && self.rng.gen_range(0, 5) != 0 {
+            return current;
+        }
+
+        safe_moves(state, snake)
+            .choose(&mut self.rng)
+            .copied()
+            .unwrap_or(current)
+    }
+}
+
+// Takes whichever safe move gets its head closest to the food.
+#[derive(Debug, Clone)]
+pub struct GreedyPolicy;
+
+impl Policy for GreedyPolicy {
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn choose(&mut self, state: &GameState, snake: &Snake) -> Direction {
+        let current = snake.get_direction();
+        let food = match state.get_food() {
+            Some(food) => food,
+            None => return current,
+        };
+
+        safe_moves(state, snake)
+            .into_iter()
+            .min_by_key(|direction| {
+                let next_point = state
+                    .neighbour_towards(snake.get_head_point(), *direction)
+                    .unwrap();
+                (distance(&next_point, &food), *direction != current)
+            })
+            .unwrap_or(current)
+    }
+}
+
+// Follows the shortest path to the food, but only when the first step leaves it
+// enough room to survive afterwards. Otherwise it heads for the most open space.
+#[derive(Debug, Clone)]
+pub struct AStarPolicy;
+
+impl Policy for AStarPolicy {
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn choose(&mut self, state: &GameState, snake: &Snake) -> Direction {
+        let moves = safe_moves(state, snake);
+        if let Some(direction) = state
+            .get_food()
+            .and_then(|food| find_path(state, snake, food))
+            .and_then(|path| path.first().copied())
+            .and_then(|first| {
+                moves.iter().copied().find(|direction| {
+                    state.neighbour_towards(snake.get_head_point(), *direction) == Some(first)
+                })
+            })
+        {
+            if open_space(state, snake, direction) >= snake.len() {
+                return direction;
+            }
+        }
+
+        moves
+            .into_iter()
+            .max_by_key(|direction| open_space(state, snake, *direction))
+            .unwrap_or_else(|| snake.get_direction())
+    }
+}
+
+fn safe_moves(state: &GameState, snake: &Snake) -> Vec<Direction> {
+    let current = snake.get_direction();
+    Direction::all()
+        .iter()
+        .copied()
+        .filter(|direction| *direction != current.opposite())
+        .filter(|direction| !state.is_fatal_move(snake, *direction))
+        .collect()
+}
+
+fn distance(from: &Point, to: &Point) -> u32 {
+    let (dx, dy) = from.offset_to(to);
+    dx.unsigned_abs() + dy.unsigned_abs()
+}
+
+// Counts the cells reachable from where the head would be after moving.
+fn open_space(state: &GameState, snake: &Snake, direction: Direction) -> usize {
+    state
+        .neighbour_towards(snake.get_head_point(), direction)
+        .map_or(0, |next_point| {
+            state.get_grid().reachable_from(next_point).len()
+        })
+}
+
+// Note: In chain mode, paths go around the numbered foods that are not due yet,
+// and doors are only passable with their key.
+fn is_passable(state: &GameState, snake: &Snake, point: &Point) -> bool {
+    if let Tile::Door(key) = state.get_grid().get_tile(point) {
+        if !snake.has_key(key) {
+            return false;
+        }
+    }
+
+    match state.get_grid().get(point) {
+        Cell::Snake => *point == snake.tail_point() && snake.will_vacate_tail(),
+        Cell::Obstacle => false,
+        Cell::Food => !state.get_rules().chain_food || state.get_food() == Some(*point),
+        _ => true,
+    }
+}
+
+// Note: Plain A* over the grid with the Manhattan distance as heuristic.
+// The returned path starts with the first step and ends on `goal`.
+fn find_path(state: &GameState, snake: &Snake, goal: Point) -> Option<Vec<Point>> {
+    let start = snake.get_head_point();
+    let width = state.get_width();
+    let height = state.get_height();
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Point, Point> = HashMap::new();
+    let mut cost: HashMap<Point, u32> = HashMap::new();
+    open.push(Reverse((distance(&start, &goal), start.x, start.y)));
+    cost.insert(start, 0);
+
+    while let Some(Reverse((_, x, y))) = open.pop() {
+        let point = Point::new(x, y);
+        if point == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current) {
+                if *previous == start {
+                    break;
+                }
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let next_cost = cost[&point] + 1;
+        for neighbour in point.neighbours(width, height) {
+            if !is_passable(state, snake, &neighbour) {
+                continue;
+            }
+            if cost.get(&neighbour).is_none_or(|known| next_cost < *known) {
+                cost.insert(neighbour, next_cost);
+                came_from.insert(neighbour, point);
+                open.push(Reverse((
+                    next_cost + distance(&neighbour, &goal),
+                    neighbour.x,
+                    neighbour.y,
+                )));
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,68 @@
+use crate::grid::{Cell, Grid, Tile};
+use crate::point::Point;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// Clusters are never generated this close to the snake's head or closer.
+const VICINITY: u32 = 5;
+const MIN_CLUSTER_SIZE: usize = 2;
+const MAX_CLUSTER_SIZE: usize = 5;
+// Clusters that would split the board are thrown away and tried again this many times.
+const ATTEMPTS: usize = 20;
+
+// Note: A cluster grows from a random empty floor cell outside the snake's
+// vicinity by adding random empty neighbours. It is only accepted if the open
+// part of the board stays in one piece, counting snake bodies as open since they
+// move on, so a cluster can never trap the snake. Returns None if nothing fits.
+pub fn generate_cluster<R: Rng>(grid: &Grid, head: Point, rng: &mut R) -> Option<Vec<Point>> {
+    let (width, height) = (grid.get_width(), grid.get_height());
+    let fits = |point: &Point| {
+        grid.get(point) == Cell::Empty
+            && grid.get_tile(point) == Tile::Floor
+            && distance(point, &head) > VICINITY
+    };
+    let candidates: Vec<Point> = grid.free_points().into_iter().filter(fits).collect();
+
+    for _ in 0..ATTEMPTS {
+        let start = *candidates.choose(rng)?;
+        let size = rng.gen_range(MIN_CLUSTER_SIZE, MAX_CLUSTER_SIZE + 1);
+        let mut cluster = vec![start];
+        while cluster.len() < size {
+            let frontier: Vec<Point> = cluster
+                .iter()
+                .flat_map(|point| point.neighbours(width, height))
+                .filter(|point| fits(point) && !cluster.contains(point))
+                .collect();
+            match frontier.choose(rng) {
+                Some(point) => cluster.push(*point),
+                None => break,
+            }
+        }
+
+        if keeps_board_connected(grid, &cluster, head) {
+            return Some(cluster);
+        }
+    }
+
+    None
+}
+
+fn keeps_board_connected(grid: &Grid, cluster: &[Point], head: Point) -> bool {
+    let mut open = grid.clone();
+    for (point, cell) in grid.points() {
+        if cell == Cell::Snake {
+            open.set(&point, Cell::Empty);
+        }
+    }
+    let before = open.reachable_from(head).len();
+    for point in cluster {
+        open.set(point, Cell::Obstacle);
+    }
+
+    open.reachable_from(head).len() + cluster.len() == before
+}
+
+fn distance(a: &Point, b: &Point) -> u32 {
+    let (dx, dy) = a.offset_to(b);
+    dx.unsigned_abs() + dy.unsigned_abs()
+}
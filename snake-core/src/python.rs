@@ -0,0 +1,89 @@
+// The pyo3 macros expand to conversions that clippy flags as useless.
+#![allow(clippy::useless_conversion)]
+
+use crate::env::{Action, Env, Observation};
+use crate::rules::Rules;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+// Note: The Python side sees a single `GameState` class that wraps the gym-style
+// environment. Actions are 0 (straight), 1 (turn left) and 2 (turn right), and
+// observations are dicts holding the same encoding as `env::Observation`.
+#[pyclass(name = "GameState", unsendable)]
+pub struct PyGameState {
+    env: Env,
+}
+
+#[pymethods]
+impl PyGameState {
+    #[new]
+    #[pyo3(signature = (width = 20, height = 20, seed = 0, rival = false))]
+    fn new(width: u16, height: u16, seed: u64, rival: bool) -> Self {
+        let rules = Rules {
+            food_reachability: true,
+            rival,
+            ..Rules::default()
+        };
+        Self {
+            env: Env::new(width, height, rules, seed),
+        }
+    }
+
+    fn reset(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        let observation = self.env.reset();
+        observation_to_dict(py, &observation)
+    }
+
+    fn step(&mut self, py: Python<'_>, action: u8) -> PyResult<(PyObject, f32, bool)> {
+        let action = match action {
+            0 => Action::Straight,
+            1 => Action::TurnLeft,
+            2 => Action::TurnRight,
+            _ => return Err(PyValueError::new_err("action must be 0, 1 or 2")),
+        };
+        let (observation, reward, done) = self.env.step(action);
+
+        Ok((observation_to_dict(py, &observation)?, reward, done))
+    }
+
+    fn observation(&self, py: Python<'_>) -> PyResult<PyObject> {
+        observation_to_dict(py, &Observation::from_state(self.env.get_state()))
+    }
+
+    #[getter]
+    fn score(&self) -> u64 {
+        self.env.get_state().get_score()
+    }
+
+    #[getter]
+    fn ticks(&self) -> u64 {
+        self.env.get_state().get_ticks()
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.env.get_state().get_width()
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.env.get_state().get_height()
+    }
+}
+
+fn observation_to_dict(py: Python<'_>, observation: &Observation) -> PyResult<PyObject> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("width", observation.width)?;
+    dict.set_item("height", observation.height)?;
+    dict.set_item("cells", observation.cells.clone())?;
+    dict.set_item("features", observation.features.to_vec())?;
+
+    Ok(dict.into())
+}
+
+#[pymodule]
+fn snake_core(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGameState>()?;
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::fmt::Debug;
+
+// Note: Where a game state gets its randomness from. Any generator from the
+// rand crate will do, so a test can plug in one that returns a fixed sequence
+// to check exactly where things spawn. Runs started from a seed use `seeded`,
+// which every recorded seed and challenge code depends on staying the same.
+pub trait Rng: RngCore + Debug + Send {
+    fn box_clone(&self) -> Box<dyn Rng>;
+}
+
+impl<T: RngCore + Debug + Send + Clone + 'static> Rng for T {
+    fn box_clone(&self) -> Box<dyn Rng> {
+        Box::new(self.clone())
+    }
+}
+
+// The box itself is an `Rng` too, so this has to clone what is inside it.
+impl Clone for Box<dyn Rng> {
+    fn clone(&self) -> Self {
+        (**self).box_clone()
+    }
+}
+
+pub fn seeded(seed: u64) -> Box<dyn Rng> {
+    Box::new(StdRng::seed_from_u64(seed))
+}
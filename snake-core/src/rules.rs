@@ -0,0 +1,261 @@
+use crate::boss::BOSS_HITS;
+use crate::food::CHAIN_LENGTH;
+use crate::scoring::Scoring;
+use crate::spawn::SpawnDistribution;
+use crate::state::{CHAIN_PENALTY, MAX_SPEED};
+
+// Note: How often the snake speeds up as the score goes up.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpeedRamp {
+    // The speed stays at the start speed.
+    Off,
+    // One step up for every `MAX_SPEED`th of the board eaten.
+    #[default]
+    Normal,
+    // Twice as often as normal.
+    Fast,
+}
+
+impl SpeedRamp {
+    pub fn all() -> [SpeedRamp; 3] {
+        [SpeedRamp::Off, SpeedRamp::Normal, SpeedRamp::Fast]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|ramp| ramp.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeedRamp::Off => "off",
+            SpeedRamp::Normal => "normal",
+            SpeedRamp::Fast => "fast",
+        }
+    }
+
+    // Foods eaten per speed step on a board of `cells` cells, or None if the
+    // speed never changes. Boards with fewer cells than that speed up on every food.
+    pub fn foods_per_step(&self, cells: u64) -> Option<u64> {
+        let foods = match self {
+            SpeedRamp::Off => return None,
+            SpeedRamp::Normal => cells / MAX_SPEED as u64,
+            SpeedRamp::Fast => cells / MAX_SPEED as u64 / 2,
+        };
+        Some(foods.max(1))
+    }
+}
+
+// Note: What counts towards the next speed step of the speed ramp.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpeedModel {
+    // Points scored, so a run only ever gets faster.
+    #[default]
+    Score,
+    // Segments the snake has grown by, as in some classic variants, so losing
+    // length to a laser or a respawn slows it back down.
+    Length,
+}
+
+impl SpeedModel {
+    pub fn all() -> [SpeedModel; 2] {
+        [SpeedModel::Score, SpeedModel::Length]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|model| model.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeedModel::Score => "score",
+            SpeedModel::Length => "length",
+        }
+    }
+}
+
+// Note: Rules are the settings that change how a run plays out. They are fixed
+// when a run starts and are kept by the game state, unlike frontend settings
+// such as assists, which only change what is drawn.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    // Only place food in cells the snake's head can actually reach.
+    pub food_reachability: bool,
+    // Spawn an AI controlled rival snake that competes for the food.
+    pub rival: bool,
+    // Seconds of play after which uneaten food moves to another cell (0 disables).
+    pub food_expiry_secs: u16,
+    // Spawn numbered foods that have to be eaten in order instead of a single food.
+    pub chain_food: bool,
+    // Territory mode: seconds to paint as much of the board as possible, with the
+    // score being the percentage painted (0 plays the classic game).
+    pub territory_secs: u16,
+    // Lives to start with; crashing with one to spare respawns the snake (0 or 1
+    // plays the classic single life).
+    pub lives: u8,
+    // Seconds between laser beams sweeping a random row or column, cutting the
+    // snakes they cross after a short warning (0 disables).
+    pub laser_secs: u16,
+    // Boss mode: a large creature chases the snake and fires lasers at it, and
+    // eating enough food defeats it and ends the run.
+    pub boss: bool,
+    // Endless mode: foods between new obstacle clusters appearing away from the
+    // snake (0 disables).
+    pub endless_every: u16,
+    // Risk mode: points from food are at risk until the snake's head reaches a
+    // safe zone, and a crash loses whatever is still at risk.
+    pub risk: bool,
+    // Zen mode: the border wraps around, nothing is fatal to the player and the
+    // speed never ramps up.
+    pub zen: bool,
+    // Speed the run starts at, from 0 up to `state::MAX_SPEED`.
+    pub start_speed: u16,
+    // How food picks its cell among the free ones.
+    pub food_spawn: SpawnDistribution,
+    // The border wraps around like in zen mode, while everything else stays fatal.
+    pub wrap_around: bool,
+    // Every tick takes half as long as it would otherwise.
+    pub double_speed: bool,
+    // Foods on the board at once outside of chain mode (0 or 1 plays the classic
+    // single food).
+    pub food_count: u8,
+    // Clusters of walls placed away from the snake when the run starts.
+    pub obstacles: u8,
+    pub speed_ramp: SpeedRamp,
+    pub speed_model: SpeedModel,
+    // How points are awarded for the food eaten.
+    pub scoring: Scoring,
+}
+
+impl Rules {
+    pub fn wraps_around(&self) -> bool {
+        self.zen || self.wrap_around
+    }
+
+    // Note: One line for every rule that changes the classic game, for frontends
+    // to show on a rules screen, so whatever a run is played with is explained
+    // in the game. A new rule only needs its line added here.
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.zen {
+            lines.push("Zen: the border wraps around and nothing is fatal".to_string());
+        }
+        if self.wrap_around && !self.zen {
+            lines.push("The border wraps around to the other side".to_string());
+        }
+        if self.double_speed {
+            lines.push("Double speed: every tick takes half as long".to_string());
+        }
+        if self.food_count > 1 && !self.chain_food {
+            lines.push(format!(
+                "{} foods are on the board at once",
+                self.food_count
+            ));
+        }
+        if self.obstacles > 0 {
+            lines.push(format!(
+                "{} clusters of walls are scattered over the board",
+                self.obstacles
+            ));
+        }
+        if self.rival {
+            lines.push("An AI rival snake competes for the food".to_string());
+        }
+        if self.chain_food {
+            lines.push(format!(
+                "Food comes numbered 1 to {} and has to be eaten in order",
+                CHAIN_LENGTH
+            ));
+        }
+        if self.territory_secs > 0 {
+            lines.push(format!(
+                "Territory: paint as much of the board as you can in {}s",
+                self.territory_secs
+            ));
+        }
+        if self.lives > 1 {
+            lines.push(format!(
+                "{} lives: a crash respawns the snake, invincible for a moment",
+                self.lives
+            ));
+        }
+        if self.laser_secs > 0 {
+            lines.push(format!(
+                "A laser sweeps a row or column every {}s, cutting snakes",
+                self.laser_secs
+            ));
+        }
+        if self.boss {
+            lines.push(format!(
+                "A boss chases the snake; eating {} food defeats it",
+                BOSS_HITS
+            ));
+        }
+        if self.endless_every > 0 {
+            lines.push(format!(
+                "New walls appear every {} food",
+                self.endless_every
+            ));
+        }
+        if self.risk {
+            lines.push("Risk: points only count once the snake reaches a safe zone".to_string());
+        }
+        if self.food_expiry_secs > 0 {
+            lines.push(format!(
+                "Food not eaten within {}s moves elsewhere",
+                self.food_expiry_secs
+            ));
+        }
+        if self.food_reachability {
+            lines.push("Food only spawns where the snake can reach it".to_string());
+        }
+        if self.food_spawn != SpawnDistribution::Uniform {
+            lines.push(format!(
+                "Food tends to spawn {}",
+                self.food_spawn.describe()
+            ));
+        }
+        if self.start_speed > 0 {
+            lines.push(format!("The run starts at speed {}", self.start_speed));
+        }
+        if lines.is_empty() {
+            lines.push("Classic: eat the food, avoid the walls and your tail".to_string());
+        }
+
+        lines
+    }
+
+    pub fn describe_scoring(&self) -> Vec<String> {
+        if self.zen {
+            return vec!["No score, the snake just grows".to_string()];
+        }
+        if self.territory_secs > 0 {
+            return vec!["The score is the percentage of the board painted".to_string()];
+        }
+
+        let mut lines = vec![self.scoring.describe()];
+        if self.chain_food {
+            lines.push(format!("Food out of order costs {} points", CHAIN_PENALTY));
+        }
+        if self.risk {
+            lines.push("A crash loses the points not yet banked in a safe zone".to_string());
+        }
+        lines.push("Filling the board adds 1 point per cell".to_string());
+        let grows = match self.speed_model {
+            SpeedModel::Score => "the score goes up",
+            SpeedModel::Length => "the snake grows longer",
+        };
+        match self.speed_ramp {
+            SpeedRamp::Off => lines.push("The speed never changes".to_string()),
+            SpeedRamp::Normal => lines.push(format!("The snake speeds up as {}", grows)),
+            SpeedRamp::Fast => lines.push(format!("The snake speeds up quickly as {}", grows)),
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,242 @@
+use crate::point::Point;
+use crate::state::CHAIN_PENALTY;
+use std::fmt::Debug;
+use std::time::Duration;
+
+// Distance variant: cells between two foods per extra point.
+const DISTANCE_PER_POINT: u32 = 5;
+// Time variant: food eaten sooner than this after the last is worth a point per second to spare.
+const TIME_BONUS_SECS: u64 = 10;
+// Combo variant: ticks between two foods that keep the combo going.
+const COMBO_TICKS: u64 = 20;
+
+// What happened to the player's snake on a tick, as far as the score goes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ScoreEvent {
+    Moved,
+    Ate,
+    // Chain food eaten out of order.
+    AteOutOfOrder,
+}
+
+// The run at the moment of the event.
+#[derive(Debug, Clone)]
+pub struct TickInfo {
+    // The number of the tick, counting from 1.
+    pub tick: u64,
+    // Game time played before this tick.
+    pub elapsed: Duration,
+    pub head: Point,
+    pub length: usize,
+}
+
+// Note: A score rule decides the points for every tick of a run, so a scoring
+// variant is a small type rather than a change to the engine. Rules can keep
+// whatever they need between ticks, and a negative result takes points away
+// (never below zero). Frontends pick one of the built-in rules through
+// `Rules::scoring`, or plug in their own with `GameState::set_score_rule`.
+pub trait ScoreRule: Debug + Send {
+    // Short and without spaces, since it is recorded with the run.
+    fn name(&self) -> &str;
+
+    fn points(&mut self, event: ScoreEvent, tick: &TickInfo) -> i64;
+
+    // Rules are cloned along with the game state, e.g. by the bots looking ahead.
+    fn box_clone(&self) -> Box<dyn ScoreRule>;
+}
+
+impl Clone for Box<dyn ScoreRule> {
+    fn clone(&self) -> Self {
+        (**self).box_clone()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scoring {
+    #[default]
+    Classic,
+    // Food far from the last one is worth more.
+    Distance,
+    // Food eaten quickly after the last one is worth more.
+    Time,
+    // Food eaten in quick succession builds up a combo.
+    Combo,
+}
+
+impl Scoring {
+    pub fn all() -> [Scoring; 4] {
+        [
+            Scoring::Classic,
+            Scoring::Distance,
+            Scoring::Time,
+            Scoring::Combo,
+        ]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|scoring| scoring.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scoring::Classic => "classic",
+            Scoring::Distance => "distance",
+            Scoring::Time => "time",
+            Scoring::Combo => "combo",
+        }
+    }
+
+    // What a food is worth, for describing the rules to the player.
+    pub fn describe(&self) -> String {
+        match self {
+            Scoring::Classic => "Each food is worth 1 point".to_string(),
+            Scoring::Distance => format!(
+                "Each food is worth 1 point, plus 1 for every {} cells from the last one",
+                DISTANCE_PER_POINT
+            ),
+            Scoring::Time => format!(
+                "Each food is worth 1 point, plus 1 for every second under {}s since the last one",
+                TIME_BONUS_SECS
+            ),
+            Scoring::Combo => format!(
+                "Food within {} ticks of the last builds a combo, worth its length in points",
+                COMBO_TICKS
+            ),
+        }
+    }
+
+    pub fn new_rule(&self) -> Box<dyn ScoreRule> {
+        match self {
+            Scoring::Classic => Box::new(ClassicScore),
+            Scoring::Distance => Box::new(DistanceScore::default()),
+            Scoring::Time => Box::new(TimeScore::default()),
+            Scoring::Combo => Box::new(ComboScore::default()),
+        }
+    }
+}
+
+// Every variant charges the same for chain food eaten out of order.
+fn out_of_order_penalty() -> i64 {
+    -(CHAIN_PENALTY as i64)
+}
+
+// A point a food.
+#[derive(Debug, Clone)]
+pub struct ClassicScore;
+
+impl ScoreRule for ClassicScore {
+    fn name(&self) -> &str {
+        "classic"
+    }
+
+    fn points(&mut self, event: ScoreEvent, _tick: &TickInfo) -> i64 {
+        match event {
+            ScoreEvent::Moved => 0,
+            ScoreEvent::Ate => 1,
+            ScoreEvent::AteOutOfOrder => out_of_order_penalty(),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn ScoreRule> {
+        Box::new(self.clone())
+    }
+}
+
+// The distance is counted in a straight line, so the way the snake took does not matter.
+#[derive(Debug, Clone, Default)]
+pub struct DistanceScore {
+    last_food: Option<Point>,
+}
+
+impl ScoreRule for DistanceScore {
+    fn name(&self) -> &str {
+        "distance"
+    }
+
+    fn points(&mut self, event: ScoreEvent, tick: &TickInfo) -> i64 {
+        match event {
+            ScoreEvent::Moved => 0,
+            ScoreEvent::Ate => {
+                let distance = self.last_food.map_or(0, |last| {
+                    let (dx, dy) = last.offset_to(&tick.head);
+                    dx.unsigned_abs() + dy.unsigned_abs()
+                });
+                self.last_food = Some(tick.head);
+                1 + (distance / DISTANCE_PER_POINT) as i64
+            }
+            ScoreEvent::AteOutOfOrder => out_of_order_penalty(),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn ScoreRule> {
+        Box::new(self.clone())
+    }
+}
+
+// Counted in game time, so pausing does not cost anything.
+#[derive(Debug, Clone, Default)]
+pub struct TimeScore {
+    last_food: Duration,
+}
+
+impl ScoreRule for TimeScore {
+    fn name(&self) -> &str {
+        "time"
+    }
+
+    fn points(&mut self, event: ScoreEvent, tick: &TickInfo) -> i64 {
+        match event {
+            ScoreEvent::Moved => 0,
+            ScoreEvent::Ate => {
+                let secs = tick.elapsed.saturating_sub(self.last_food).as_secs();
+                self.last_food = tick.elapsed;
+                1 + TIME_BONUS_SECS.saturating_sub(secs) as i64
+            }
+            ScoreEvent::AteOutOfOrder => out_of_order_penalty(),
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn ScoreRule> {
+        Box::new(self.clone())
+    }
+}
+
+// Eating out of order breaks the combo as well.
+#[derive(Debug, Clone, Default)]
+pub struct ComboScore {
+    combo: u64,
+    last_food: u64,
+}
+
+impl ScoreRule for ComboScore {
+    fn name(&self) -> &str {
+        "combo"
+    }
+
+    fn points(&mut self, event: ScoreEvent, tick: &TickInfo) -> i64 {
+        match event {
+            ScoreEvent::Moved => 0,
+            ScoreEvent::Ate => {
+                if self.combo > 0 && tick.tick - self.last_food <= COMBO_TICKS {
+                    self.combo += 1;
+                } else {
+                    self.combo = 1;
+                }
+                self.last_food = tick.tick;
+                self.combo as i64
+            }
+            ScoreEvent::AteOutOfOrder => {
+                self.combo = 0;
+                out_of_order_penalty()
+            }
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn ScoreRule> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,23 @@
+use crate::point::Point;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SegmentKind {
+    Normal,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    pub point: Point,
+    pub kind: SegmentKind,
+}
+
+impl Segment {
+    pub fn new(point: Point) -> Self {
+        Self {
+            point,
+            kind: SegmentKind::Normal,
+        }
+    }
+}
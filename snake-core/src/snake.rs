@@ -0,0 +1,138 @@
+use crate::direction::Direction;
+use crate::point::Point;
+use crate::segment::Segment;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snake {
+    body: Vec<Segment>,
+    direction: Direction,
+    digesting: bool,
+    // Numbers of the keys picked up and not used on a door yet.
+    keys: Vec<u8>,
+}
+
+impl Snake {
+    pub fn new(start: Point, length: u16, direction: Direction) -> Self {
+        let opposite = direction.opposite();
+        let body: Vec<Segment> = (0..length)
+            .map(|i| Segment::new(start.transform(opposite, i)))
+            .collect();
+
+        Self {
+            body,
+            direction,
+            digesting: false,
+            keys: Vec::new(),
+        }
+    }
+
+    pub fn get_head_point(&self) -> Point {
+        self.body.first().unwrap().point
+    }
+
+    pub fn tail_point(&self) -> Point {
+        self.body.last().unwrap().point
+    }
+
+    pub fn get_segments(&self) -> &[Segment] {
+        &self.body
+    }
+
+    // Removes the segment at `index` and everything behind it, and returns
+    // the points they occupied. The head is never cut off.
+    pub fn cut_at(&mut self, index: usize) -> Vec<Point> {
+        let index = index.max(1);
+        if index >= self.body.len() {
+            return Vec::new();
+        }
+
+        self.digesting = false;
+        self.body
+            .split_off(index)
+            .into_iter()
+            .map(|segment| segment.point)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Point> {
+        self.body.iter().map(|segment| &segment.point)
+    }
+
+    pub fn len(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty()
+    }
+
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn occupies(&self, point: &Point) -> bool {
+        self.body.iter().any(|segment| segment.point == *point)
+    }
+
+    // Note: The tail moves out of its cell on the next slither unless the snake is digesting,
+    // in which case the body grows by one and the tail stays where it is.
+    pub fn will_vacate_tail(&self) -> bool {
+        !self.digesting
+    }
+
+    // Note: Segment kinds belong to a position along the body rather than to a
+    // cell, so they stay where they are while the points shift one segment back.
+    // This works in place, so the body only ever allocates when it grows past
+    // its capacity. The head moves onto `head`, which the state works out since
+    // the board may wrap around. Returns the point the tail moved out of, if any.
+    pub fn slither(&mut self, head: Point) -> Option<Point> {
+        let tail = self.tail_point();
+        let grows = self.digesting;
+        if grows {
+            self.digesting = false;
+            self.body.push(Segment::new(tail));
+        }
+        for i in (1..self.body.len()).rev() {
+            self.body[i].point = self.body[i - 1].point;
+        }
+        self.body[0].point = head;
+
+        if grows {
+            None
+        } else {
+            Some(tail)
+        }
+    }
+
+    pub fn get_keys(&self) -> &[u8] {
+        &self.keys
+    }
+
+    pub fn has_key(&self, key: u8) -> bool {
+        self.keys.contains(&key)
+    }
+
+    pub fn add_key(&mut self, key: u8) {
+        self.keys.push(key);
+    }
+
+    // Returns false if the snake does not hold the key.
+    pub fn use_key(&mut self, key: u8) -> bool {
+        match self.keys.iter().position(|held| *held == key) {
+            Some(index) => {
+                self.keys.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    pub fn grow(&mut self) {
+        self.digesting = true;
+    }
+}
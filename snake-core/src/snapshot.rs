@@ -0,0 +1,101 @@
+use crate::direction::Direction;
+use crate::grid::Cell;
+use crate::point::Point;
+use crate::segment::Segment;
+use crate::state::GameState;
+
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"SNAK";
+pub const SNAPSHOT_VERSION: u8 = 3;
+
+const NO_FOOD: u16 = u16::MAX;
+
+// Note: A snapshot is a stable binary layout of the game state for other programs
+// to read. Everything is little-endian, in this order:
+//
+//   magic "SNAK" (4 bytes), version (u8), direction (u8: 0 up, 1 right, 2 down, 3 left),
+//   width (u16), height (u16), score (u16), rival score (u16), speed (u16),
+//   ticks (u64), seed (u64), food x (u16), food y (u16) (both 0xFFFF without food),
+//   snake length (u32) followed by that many points (x u16, y u16), head first,
+//   rival length (u32, 0 without a rival) followed by its points,
+//   one byte per cell in row-major order
+//   (0 empty, 1 snake, 2 food, 3 obstacle, 4 portal),
+//   and since version 2 the score (u64) and rival score (u64) in full.
+//
+// New fields are only ever appended, with a version bump. The one exception is
+// the lengths, which were u16 before version 3 and cut off snakes longer than
+// 0xFFFF; a u32 holds every cell of the largest board. The u16 scores at the
+// start stop at 0xFFFF.
+pub fn encode(state: &GameState) -> Vec<u8> {
+    let width = state.get_width();
+    let height = state.get_height();
+    let snake = state.get_snake();
+    let mut bytes = Vec::with_capacity(64 + snake.len() * 4 + width as usize * height as usize);
+
+    bytes.extend_from_slice(SNAPSHOT_MAGIC);
+    bytes.push(SNAPSHOT_VERSION);
+    bytes.push(direction_code(snake.get_direction()));
+    for value in [
+        width,
+        height,
+        state.get_score().min(u16::MAX as u64) as u16,
+        state.get_rival_score().min(u16::MAX as u64) as u16,
+        state.get_speed(),
+    ] {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes.extend_from_slice(&state.get_ticks().to_le_bytes());
+    bytes.extend_from_slice(&state.get_seed().to_le_bytes());
+
+    let food = state.get_food().unwrap_or(Point::new(NO_FOOD, NO_FOOD));
+    bytes.extend_from_slice(&food.x.to_le_bytes());
+    bytes.extend_from_slice(&food.y.to_le_bytes());
+
+    encode_segments(&mut bytes, snake.get_segments());
+    encode_segments(
+        &mut bytes,
+        state
+            .get_rival()
+            .map(|rival| rival.get_segments())
+            .unwrap_or(&[]),
+    );
+
+    bytes.extend(state.get_grid().points().map(|(_, cell)| match cell {
+        Cell::Empty => 0,
+        Cell::Snake => 1,
+        Cell::Food => 2,
+        Cell::Obstacle => 3,
+        Cell::Portal => 4,
+    }));
+    bytes.extend_from_slice(&state.get_score().to_le_bytes());
+    bytes.extend_from_slice(&state.get_rival_score().to_le_bytes());
+
+    bytes
+}
+
+pub fn direction_code(direction: Direction) -> u8 {
+    match direction {
+        Direction::Up => 0,
+        Direction::Right => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+    }
+}
+
+pub fn direction_from_code(code: u8) -> Option<Direction> {
+    match code {
+        0 => Some(Direction::Up),
+        1 => Some(Direction::Right),
+        2 => Some(Direction::Down),
+        3 => Some(Direction::Left),
+        _ => None,
+    }
+}
+
+// A length followed by the points, as both snakes are written.
+pub fn encode_segments(bytes: &mut Vec<u8>, segments: &[Segment]) {
+    bytes.extend_from_slice(&(segments.len() as u32).to_le_bytes());
+    for segment in segments {
+        bytes.extend_from_slice(&segment.point.x.to_le_bytes());
+        bytes.extend_from_slice(&segment.point.y.to_le_bytes());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::direction::Direction;
+use crate::grid::{Cell, Grid};
+use crate::point::Point;
+use rand::Rng;
+
+// How much more likely a free cell is per obstacle next to it with `NearObstacles`.
+const OBSTACLE_WEIGHT: u64 = 8;
+
+// Note: How food picks its cell among the free ones. Every cell gets a weight
+// and is picked with a chance in proportion to it, so no cell is ever ruled
+// out; when every weight is zero the pick falls back to uniform.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpawnDistribution {
+    #[default]
+    Uniform,
+    // The further from the snake's head, the likelier.
+    FarFromSnake,
+    // Cells with fewer open sides, like corners and nooks, are less likely.
+    AvoidCorners,
+    // Cells next to walls and obstacles are likelier, so food clusters around them.
+    NearObstacles,
+}
+
+impl SpawnDistribution {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "uniform" => Some(Self::Uniform),
+            "far" | "far_from_snake" => Some(Self::FarFromSnake),
+            "avoid_corners" => Some(Self::AvoidCorners),
+            "near_obstacles" => Some(Self::NearObstacles),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::FarFromSnake => "far_from_snake",
+            Self::AvoidCorners => "avoid_corners",
+            Self::NearObstacles => "near_obstacles",
+        }
+    }
+
+    // Where food turns up, for describing the rules to the player.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Uniform => "anywhere",
+            Self::FarFromSnake => "far from the snake",
+            Self::AvoidCorners => "away from corners",
+            Self::NearObstacles => "near walls",
+        }
+    }
+
+    pub fn weight(&self, grid: &Grid, head: Point, point: &Point) -> u64 {
+        match self {
+            Self::Uniform => 1,
+            Self::FarFromSnake => {
+                let (dx, dy) = point.offset_to(&head);
+                (1 + (dx.unsigned_abs() + dy.unsigned_abs()) as u64).pow(2)
+            }
+            Self::AvoidCorners => {
+                let open = neighbours(grid, point)
+                    .filter(|neighbour| grid.get(neighbour) != Cell::Obstacle)
+                    .count() as u64;
+                open.pow(3)
+            }
+            Self::NearObstacles => {
+                let obstacles = neighbours(grid, point)
+                    .filter(|neighbour| grid.get(neighbour) == Cell::Obstacle)
+                    .count() as u64;
+                1 + OBSTACLE_WEIGHT * obstacles
+            }
+        }
+    }
+
+    // Note: Goes over the points twice instead of collecting them, so picking
+    // from the free cells of a huge board does not allocate. A uniform pick
+    // draws its index the way `SliceRandom::choose` does from a list of the same
+    // points, so seeds recorded before distributions existed keep their spawns.
+    pub fn pick<R, I>(&self, grid: &Grid, head: Point, points: I, rng: &mut R) -> Option<Point>
+    where
+        R: Rng + ?Sized,
+        I: Iterator<Item = Point> + Clone,
+    {
+        if *self != Self::Uniform {
+            let total: u64 = points
+                .clone()
+                .map(|point| self.weight(grid, head, &point))
+                .sum();
+            if total > 0 {
+                let mut target = rng.gen_range(0, total);
+                return points.into_iter().find(|point| {
+                    let weight = self.weight(grid, head, point);
+                    if target < weight {
+                        true
+                    } else {
+                        target -= weight;
+                        false
+                    }
+                });
+            }
+        }
+
+        let count = points.clone().count();
+        if count == 0 {
+            return None;
+        }
+        let index = rng.gen_range(0, count as u32) as usize;
+        points.into_iter().nth(index)
+    }
+}
+
+// Only the neighbours on the board, so the border counts as neither open nor an obstacle.
+fn neighbours<'a>(grid: &'a Grid, point: &'a Point) -> impl Iterator<Item = Point> + 'a {
+    IntoIterator::into_iter(Direction::all()).filter_map(move |direction| {
+        point.neighbour_towards(direction, grid.get_width(), grid.get_height())
+    })
+}
@@ -0,0 +1,1342 @@
+use crate::boss::{self, Boss};
+use crate::direction::Direction;
+use crate::effects::{Effect, TimedEffects};
+use crate::food::{ChainFood, CHAIN_LENGTH};
+use crate::grid::{Cell, Grid, Owner, Tile};
+use crate::hazard::{Beam, HazardScheduler, Laser};
+use crate::map::Map;
+use crate::obstacle::MovingObstacle;
+use crate::point::Point;
+use crate::procgen;
+use crate::random::{self, Rng};
+use crate::rules::{Rules, SpeedModel};
+use crate::scoring::{ScoreEvent, ScoreRule, TickInfo};
+use crate::snake::Snake;
+use crate::timeline::{Action, Timeline};
+use rand::seq::SliceRandom;
+use rand::Rng as _;
+use std::time::Duration;
+
+// Ticks per second at speed 0 and at MAX_SPEED.
+const MIN_TICKS_PER_SECOND: u32 = 8;
+const MAX_TICKS_PER_SECOND: u32 = 32;
+pub const MAX_SPEED: u16 = 8;
+// Segments a snake starts and respawns with.
+pub const START_LENGTH: u16 = 2;
+const MIN_MANUAL_INTERVAL: u16 = (1000 / MAX_TICKS_PER_SECOND) as u16;
+const MAX_MANUAL_INTERVAL: u16 = 400;
+const MIN_RIVAL_BOARD_SIZE: u16 = 8;
+pub const CHAIN_PENALTY: u64 = 3;
+// Cells a new snake has free straight ahead of it where the board has room.
+const SPAWN_CLEARANCE: u16 = 4;
+pub const RESPAWN_INVINCIBILITY: Duration = Duration::from_secs(2);
+// Seconds between the boss's laser shots, unless the rules set a laser interval.
+const BOSS_LASER_SECS: u16 = 4;
+// Width and height of the safe zones risk mode adds to boards without any.
+const SAFE_ZONE_SIZE: u16 = 2;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepOutcome {
+    Moved,
+    Ate,
+    // Chain mode only: the snake ate a numbered food before the one it was due.
+    AteOutOfOrder,
+    // The snake crashed but had a life left, and respawned.
+    LostLife,
+    Died,
+    Won,
+    // Territory mode only: the time limit ran out.
+    TimeUp,
+    // Boss mode only: the snake ate the food that finished off the boss.
+    BossDefeated,
+    // The rival ate the last food on a full board, which ends the run without a win.
+    RivalFilled,
+}
+
+// What a snake would crash into.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Crash {
+    Border,
+    Obstacle,
+    OwnBody,
+    OtherSnake,
+    LockedDoor,
+    OneWay,
+}
+
+impl Crash {
+    pub fn all() -> [Crash; 6] {
+        [
+            Crash::Border,
+            Crash::Obstacle,
+            Crash::OwnBody,
+            Crash::OtherSnake,
+            Crash::LockedDoor,
+            Crash::OneWay,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Crash::Border => "border",
+            Crash::Obstacle => "obstacle",
+            Crash::OwnBody => "self",
+            Crash::OtherSnake => "snake",
+            Crash::LockedDoor => "door",
+            Crash::OneWay => "one-way",
+        }
+    }
+}
+
+// Note: GameState holds everything needed to simulate a run without a terminal,
+// so the same rules can be driven by the keyboard frontend, bots or tools.
+// All randomness comes from the seeded RNG, so a seed always replays the same run.
+#[derive(Debug, Clone)]
+pub struct GameState {
+    width: u16,
+    height: u16,
+    seed: u64,
+    rng: Box<dyn Rng>,
+    food: Option<Point>,
+    // The foods on the board besides `food` when the rules ask for more than one.
+    extra_food: Vec<Point>,
+    next_food: Option<Point>,
+    // How long the current food has been on the board, in game time.
+    food_age: Duration,
+    // The numbered foods still on the board in chain mode, lowest number first.
+    chain: Vec<ChainFood>,
+    snake: Snake,
+    rival: Option<Snake>,
+    obstacles: Vec<MovingObstacle>,
+    boss: Option<Boss>,
+    grid: Grid,
+    speed: u16,
+    score: u64,
+    // Risk mode only: points from the food eaten since the head was last on a
+    // safe zone, which only join the score once it gets to one.
+    at_risk: u64,
+    rival_score: u64,
+    ticks: u64,
+    // Game time played so far, as the sum of the tick intervals.
+    elapsed: Duration,
+    lives: u8,
+    // Endless mode only: foods left to eat before the next obstacle cluster.
+    foods_until_cluster: u16,
+    effects: TimedEffects,
+    hazards: HazardScheduler,
+    timeline: Timeline,
+    messages: Vec<String>,
+    rules: Rules,
+    // Decides the points for every tick, the one `rules.scoring` names unless replaced.
+    score_rule: Box<dyn ScoreRule>,
+    // Set in casual play, where the player picks the tick interval instead of
+    // the speed ramping up with the score.
+    manual_interval: Option<u16>,
+}
+
+impl GameState {
+    pub fn new(width: u16, height: u16, rules: Rules, seed: u64) -> Self {
+        Self::from_map(&Map::empty(width, height), rules, seed)
+    }
+
+    // Note: The snake starts in the centre unless the map has something in the
+    // way there, in which case it starts on the free spot closest to the centre.
+    pub fn from_map(map: &Map, rules: Rules, seed: u64) -> Self {
+        Self::with_rng(map, rules, seed, random::seeded(seed))
+    }
+
+    // Like `from_map`, drawing everything random from `rng` instead of from the
+    // seed, which is then only what the run reports as its seed.
+    pub fn with_rng(map: &Map, rules: Rules, seed: u64, mut rng: Box<dyn Rng>) -> Self {
+        let (width, height) = (map.get_width(), map.get_height());
+        let mut grid = map.to_grid();
+        let obstacles: Vec<MovingObstacle> = map
+            .get_patrols()
+            .iter()
+            .filter_map(|patrol| Some(MovingObstacle::new(patrol.cells()?, patrol.every)))
+            .collect();
+        for obstacle in &obstacles {
+            grid.set(&obstacle.get_point(), Cell::Obstacle);
+        }
+        let direction = match rng.gen_range(0, 4) {
+            0 => Direction::Up,
+            1 => Direction::Right,
+            2 => Direction::Down,
+            _ => Direction::Left,
+        };
+        let (start, direction) = map_spawn(&grid, map.get_spawns().first().copied(), direction)
+            .unwrap_or((Point::new(width / 2, height / 2), direction));
+        let snake = Snake::new(start, START_LENGTH, direction);
+        for point in snake.iter() {
+            grid.set(point, Cell::Snake);
+        }
+
+        // Note: The rival starts where the map says, or in the top left quarter
+        // heading right, away from the player, and only on boards with room for
+        // the two of them.
+        let rival =
+            if rules.rival && width >= MIN_RIVAL_BOARD_SIZE && height >= MIN_RIVAL_BOARD_SIZE {
+                let (rival_start, rival_direction) = map
+                    .get_spawns()
+                    .get(1)
+                    .copied()
+                    .unwrap_or((Point::new(width / 4, height / 4), Direction::Right));
+                let rival = Snake::new(rival_start, START_LENGTH, rival_direction);
+                if rival.iter().all(|point| grid.get(point) == Cell::Empty) {
+                    for point in rival.iter() {
+                        grid.set(point, Cell::Snake);
+                    }
+                    Some(rival)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+        // Note: The boss starts as far from the snake as it fits, on boards with
+        // room for a rival.
+        let boss = if rules.boss && width >= MIN_RIVAL_BOARD_SIZE && height >= MIN_RIVAL_BOARD_SIZE
+        {
+            find_boss_spawn(&grid, snake.get_head_point()).map(Boss::new)
+        } else {
+            None
+        };
+        if let Some(boss) = &boss {
+            for point in boss.cells() {
+                grid.set(&point, Cell::Obstacle);
+            }
+        }
+        let laser_secs = match rules.laser_secs {
+            0 if boss.is_some() => BOSS_LASER_SECS,
+            secs => secs,
+        };
+
+        let mut state = Self {
+            width,
+            height,
+            seed,
+            rng,
+            food: None,
+            extra_food: Vec::new(),
+            next_food: None,
+            food_age: Duration::ZERO,
+            chain: Vec::new(),
+            snake,
+            rival,
+            obstacles,
+            boss,
+            grid,
+            speed: rules.start_speed.min(MAX_SPEED),
+            score: 0,
+            at_risk: 0,
+            rival_score: 0,
+            ticks: 0,
+            elapsed: Duration::ZERO,
+            lives: rules.lives.max(1),
+            foods_until_cluster: rules.endless_every,
+            effects: TimedEffects::new(),
+            hazards: HazardScheduler::new(Duration::from_secs(laser_secs as u64)),
+            timeline: Timeline::new(map.get_timeline()),
+            messages: Vec::new(),
+            score_rule: rules.scoring.new_rule(),
+            rules,
+            manual_interval: None,
+        };
+        state.place_safe_zones();
+        state.place_obstacle_clusters();
+        if state.rules.chain_food {
+            state.place_chain();
+        } else {
+            state.place_food();
+            state.place_extra_food();
+        }
+        state.paint_territory();
+        state
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn get_snake(&self) -> &Snake {
+        &self.snake
+    }
+
+    pub fn get_rival(&self) -> Option<&Snake> {
+        self.rival.as_ref()
+    }
+
+    pub fn get_obstacles(&self) -> &[MovingObstacle] {
+        &self.obstacles
+    }
+
+    pub fn get_boss(&self) -> Option<&Boss> {
+        self.boss.as_ref()
+    }
+
+    pub fn get_rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn get_grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_food(&self) -> Option<Point> {
+        self.food
+    }
+
+    pub fn get_extra_food(&self) -> &[Point] {
+        &self.extra_food
+    }
+
+    pub fn get_chain_food(&self) -> &[ChainFood] {
+        &self.chain
+    }
+
+    pub fn get_next_food(&self) -> Option<Point> {
+        self.next_food
+    }
+
+    // Returns None when food does not expire under the current rules.
+    pub fn get_food_time_left(&self) -> Option<Duration> {
+        if self.rules.food_expiry_secs == 0 || self.rules.chain_food {
+            return None;
+        }
+
+        self.food?;
+        Some(self.food_expiry().saturating_sub(self.food_age))
+    }
+
+    // Note: With the length speed model the speed is worked out from how much the
+    // snake has grown, counting grown segments the way the score model counts
+    // points, so it goes down again when the snake gets shorter.
+    pub fn get_speed(&self) -> u16 {
+        if self.rules.speed_model != SpeedModel::Length || self.rules.zen {
+            return self.speed;
+        }
+
+        let cells = self.width as u64 * self.height as u64;
+        match self.rules.speed_ramp.foods_per_step(cells) {
+            Some(foods_per_step) => {
+                let grown = (self.snake.len() as u64).saturating_sub(START_LENGTH as u64);
+                (self.speed as u64 + grown / foods_per_step).min(MAX_SPEED as u64) as u16
+            }
+            None => self.speed,
+        }
+    }
+
+    pub fn get_score(&self) -> u64 {
+        self.score
+    }
+
+    pub fn get_score_rule(&self) -> &dyn ScoreRule {
+        self.score_rule.as_ref()
+    }
+
+    // Note: For scoring variants from outside the engine. Set it before the first
+    // step, since the points already scored stay as they are.
+    pub fn set_score_rule(&mut self, rule: Box<dyn ScoreRule>) {
+        self.score_rule = rule;
+    }
+
+    // Risk mode only: the points that a crash would lose.
+    pub fn get_at_risk(&self) -> u64 {
+        self.at_risk
+    }
+
+    pub fn get_rival_score(&self) -> u64 {
+        self.rival_score
+    }
+
+    pub fn get_ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    // Game time played so far.
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn get_lives(&self) -> u8 {
+        self.lives
+    }
+
+    pub fn get_effects(&self) -> &TimedEffects {
+        &self.effects
+    }
+
+    // The laser currently telegraphed or firing, if any.
+    pub fn get_laser(&self) -> Option<&Laser> {
+        self.hazards.get_laser()
+    }
+
+    // The messages the map's timeline had for the last step, which the game shows over the board.
+    pub fn get_messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    pub fn is_invincible(&self) -> bool {
+        self.effects.is_active(Effect::Invincible)
+    }
+
+    // The player's head passes through bodies and obstacles while invincible,
+    // and all the time in zen mode.
+    pub fn passes_through(&self) -> bool {
+        self.rules.zen || self.is_invincible()
+    }
+
+    // Returns None outside of territory mode.
+    pub fn get_time_left(&self) -> Option<Duration> {
+        if self.rules.territory_secs == 0 {
+            return None;
+        }
+
+        let limit = Duration::from_secs(self.rules.territory_secs as u64);
+        Some(limit.saturating_sub(self.elapsed))
+    }
+
+    pub fn get_head_tile(&self) -> Tile {
+        self.grid.get_tile(&self.snake.get_head_point())
+    }
+
+    // The snake slides straight on while its head is on ice.
+    pub fn can_turn(&self) -> bool {
+        self.get_head_tile() != Tile::Ice
+    }
+
+    pub fn turn(&mut self, direction: Direction) {
+        if self.can_turn() {
+            self.snake.set_direction(direction);
+        }
+    }
+
+    pub fn turn_rival(&mut self, direction: Direction) {
+        if let Some(rival) = self.rival.as_mut() {
+            rival.set_direction(direction);
+        }
+    }
+
+    pub fn get_manual_interval(&self) -> Option<u16> {
+        self.manual_interval
+    }
+
+    pub fn set_manual_interval(&mut self, millis: u16) {
+        self.manual_interval = Some(millis.clamp(MIN_MANUAL_INTERVAL, MAX_MANUAL_INTERVAL));
+    }
+
+    // How many ticks a second the run is going at right now.
+    pub fn get_ticks_per_second(&self) -> f64 {
+        1.0 / self.calculate_interval().as_secs_f64()
+    }
+
+    // Note: The speed picks a number of ticks per second, and a tick lasts a
+    // second divided by that. Speed zones scale whichever interval applies, the
+    // manual one included.
+    pub fn calculate_interval(&self) -> Duration {
+        let base = match self.manual_interval {
+            Some(millis) => Duration::from_millis(millis as u64),
+            None => speed_interval(self.get_speed()),
+        };
+
+        let base = if self.rules.double_speed {
+            base / 2
+        } else {
+            base
+        };
+        base * self.get_head_tile().interval_percent() / 100
+    }
+
+    // Note: Filling the board is worth as many extra points as the board has cells.
+    pub fn award_perfect_game_bonus(&mut self) -> u64 {
+        let bonus = self.width as u64 * self.height as u64;
+        self.score += bonus;
+        bonus
+    }
+
+    // Note: While invincible only the border is fatal to the player, and in zen
+    // mode, where the border wraps around, nothing is.
+    pub fn is_next_step_fatal(&self) -> bool {
+        if self.passes_through() {
+            let head = self.snake.get_head_point();
+            return self
+                .neighbour_towards(head, self.snake.get_direction())
+                .is_none();
+        }
+
+        self.is_fatal_move(&self.snake, self.snake.get_direction())
+    }
+
+    // Returns None when the neighbour would be off the board, which never
+    // happens when the border wraps around.
+    pub fn neighbour_towards(&self, point: Point, direction: Direction) -> Option<Point> {
+        if self.rules.wraps_around() {
+            return Some(point.wrapping_neighbour(direction, self.width, self.height));
+        }
+
+        point.neighbour_towards(direction, self.width, self.height)
+    }
+
+    // Note: Moving into the snake's own tail is safe when the tail moves away
+    // during the same step. Any other snake cell, an obstacle, the border, a door without its key or
+    // entering a one-way tile against its arrow is fatal.
+    pub fn is_fatal_move(&self, snake: &Snake, direction: Direction) -> bool {
+        self.crash_cause(snake, direction).is_some()
+    }
+
+    // Returns what the snake would crash into moving towards `direction`, or None
+    // if the move is safe.
+    pub fn crash_cause(&self, snake: &Snake, direction: Direction) -> Option<Crash> {
+        let next_point = match self.neighbour_towards(snake.get_head_point(), direction) {
+            Some(point) => point,
+            None => return Some(Crash::Border),
+        };
+        match self.grid.get_tile(&next_point) {
+            Tile::Door(key) if !snake.has_key(key) => return Some(Crash::LockedDoor),
+            tile if !tile.allows_entry(direction) => return Some(Crash::OneWay),
+            _ => {}
+        }
+
+        match self.grid.get(&next_point) {
+            Cell::Obstacle => Some(Crash::Obstacle),
+            Cell::Snake => {
+                if snake.will_vacate_tail() && next_point == snake.tail_point() {
+                    None
+                } else if snake.occupies(&next_point) {
+                    Some(Crash::OwnBody)
+                } else {
+                    Some(Crash::OtherSnake)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Note: Simulates the next `ticks` steps on a copy of the state, as if no
+    // input was given, and returns the cells the head would pass through along
+    // with whether that path ends in death.
+    pub fn preview_path(&self, ticks: u16) -> (Vec<Point>, bool) {
+        self.preview_turns(ticks, &[])
+    }
+
+    // Like `preview_path`, taking the next of `turns` on every tick the snake can
+    // turn, the way queued turns are taken. Turns that would not change the
+    // direction are skipped.
+    pub fn preview_turns(&self, ticks: u16, turns: &[Direction]) -> (Vec<Point>, bool) {
+        let mut simulation = self.clone();
+        let mut path = Vec::with_capacity(ticks as usize);
+        let mut turns = turns.iter();
+        for _ in 0..ticks {
+            if simulation.can_turn() {
+                let current = simulation.snake.get_direction();
+                if let Some(towards) = turns
+                    .by_ref()
+                    .find(|towards| **towards != current && **towards != current.opposite())
+                {
+                    simulation.turn(*towards);
+                }
+            }
+            match simulation.step() {
+                StepOutcome::Moved | StepOutcome::Ate | StepOutcome::AteOutOfOrder => {
+                    path.push(simulation.snake.get_head_point())
+                }
+                StepOutcome::Died | StepOutcome::LostLife => return (path, true),
+                StepOutcome::Won
+                | StepOutcome::TimeUp
+                | StepOutcome::BossDefeated
+                | StepOutcome::RivalFilled => break,
+            }
+        }
+
+        (path, false)
+    }
+
+    pub fn step(&mut self) -> StepOutcome {
+        self.messages.clear();
+        self.move_obstacles();
+        self.move_boss();
+        if self.is_next_step_fatal() {
+            self.at_risk = 0;
+            if self.lives > 1 && self.respawn() {
+                return StepOutcome::LostLife;
+            }
+            return StepOutcome::Died;
+        }
+
+        self.ticks += 1;
+        let next_point = self
+            .neighbour_towards(self.snake.get_head_point(), self.snake.get_direction())
+            .unwrap();
+        let ate = move_snake(
+            &mut self.grid,
+            &mut self.snake,
+            next_point,
+            self.rival.as_ref(),
+        );
+        let in_order = ate && self.take_food(next_point);
+        self.score_tick(match (ate, in_order) {
+            (true, true) => ScoreEvent::Ate,
+            (true, false) => ScoreEvent::AteOutOfOrder,
+            _ => ScoreEvent::Moved,
+        });
+        if self.get_head_tile() == Tile::Safe {
+            self.bank();
+        }
+        if in_order && self.hit_boss() {
+            self.bank();
+            return StepOutcome::BossDefeated;
+        }
+        if in_order {
+            self.count_down_cluster();
+        }
+        if ate && !self.restock_food() {
+            self.bank();
+            return StepOutcome::Won;
+        }
+
+        if !self.step_rival() {
+            self.bank();
+            return StepOutcome::RivalFilled;
+        }
+
+        self.age_food();
+
+        self.elapsed += self.calculate_interval();
+        self.effects.advance(self.calculate_interval());
+        self.update_lasers();
+        self.run_timeline();
+        if self.rules.territory_secs > 0 {
+            self.paint_territory();
+            if self.get_time_left() == Some(Duration::ZERO) {
+                self.bank();
+                return StepOutcome::TimeUp;
+            }
+        }
+
+        match (ate, in_order) {
+            (true, true) => StepOutcome::Ate,
+            (true, false) => StepOutcome::AteOutOfOrder,
+            _ => StepOutcome::Moved,
+        }
+    }
+
+    // Note: The speed steps up every time the points pass another multiple of
+    // the foods per step, however many points the score rule gave at once.
+    fn score_tick(&mut self, event: ScoreEvent) {
+        let tick = TickInfo {
+            tick: self.ticks,
+            elapsed: self.elapsed,
+            head: self.snake.get_head_point(),
+            length: self.snake.len(),
+        };
+        let change = self.score_rule.points(event, &tick);
+        let before = self.score + self.at_risk;
+        let points = if self.rules.risk {
+            &mut self.at_risk
+        } else {
+            &mut self.score
+        };
+        *points = if change >= 0 {
+            *points + change as u64
+        } else {
+            points.saturating_sub(change.unsigned_abs())
+        };
+
+        let foods_per_step = self
+            .rules
+            .speed_ramp
+            .foods_per_step(self.width as u64 * self.height as u64);
+        let after = self.score + self.at_risk;
+        let ramps_up = !self.rules.zen
+            && self.rules.speed_model == SpeedModel::Score
+            && foods_per_step.is_some_and(|foods| after / foods > before / foods);
+        if ramps_up {
+            self.speed_up();
+        }
+    }
+
+    // Note: Obstacles move before the snakes, on the ticks their patrol says,
+    // and wait for the next one whenever a snake or food is in the way.
+    fn move_obstacles(&mut self) {
+        let tick = self.ticks + 1;
+        for i in 0..self.obstacles.len() {
+            let obstacle = &self.obstacles[i];
+            if !tick.is_multiple_of(obstacle.get_every() as u64) {
+                continue;
+            }
+            let (from, to) = match obstacle.next_point() {
+                Some(to) => (obstacle.get_point(), to),
+                None => continue,
+            };
+            if self.grid.get(&to) != Cell::Empty {
+                continue;
+            }
+
+            self.obstacles[i].advance();
+            self.grid.set(&to, Cell::Obstacle);
+            self.vacate_obstacle_cell(&from);
+        }
+    }
+
+    // Note: The boss moves in the same phase as the other obstacles. Its new cells
+    // have to be empty apart from its own, so it waits when the way is blocked.
+    fn move_boss(&mut self) {
+        let boss = match &self.boss {
+            Some(boss) => boss,
+            None => return,
+        };
+        if !(self.ticks + 1).is_multiple_of(boss.move_every()) {
+            return;
+        }
+
+        let head = self.snake.get_head_point();
+        let origin = boss
+            .chase_directions(&head)
+            .into_iter()
+            .find_map(|direction| {
+                let origin = boss.origin_towards(direction, self.width, self.height)?;
+                boss::cells_at(origin)
+                    .iter()
+                    .all(|point| boss.covers(point) || self.grid.get(point) == Cell::Empty)
+                    .then_some(origin)
+            });
+        let origin = match origin {
+            Some(origin) => origin,
+            None => return,
+        };
+
+        let previous = boss.cells();
+        let boss = self.boss.as_mut().unwrap();
+        boss.move_to(origin);
+        for point in boss.cells() {
+            self.grid.set(&point, Cell::Obstacle);
+        }
+        for point in previous {
+            if !self.boss.as_ref().unwrap().covers(&point) {
+                self.vacate_obstacle_cell(&point);
+            }
+        }
+    }
+
+    // Note: In endless mode every so many foods a new cluster of walls appears
+    // somewhere away from the snake. If none fits the snake gets a reprieve until
+    // the next one is due.
+    fn count_down_cluster(&mut self) {
+        if self.rules.endless_every == 0 {
+            return;
+        }
+
+        self.foods_until_cluster -= 1;
+        if self.foods_until_cluster > 0 {
+            return;
+        }
+        self.foods_until_cluster = self.rules.endless_every;
+        let head = self.snake.get_head_point();
+        if let Some(cluster) = procgen::generate_cluster(&self.grid, head, &mut self.rng) {
+            for point in cluster {
+                self.grid.set_tile(&point, Tile::Wall);
+            }
+        }
+    }
+
+    // Returns true if that was the hit that defeated the boss, which is then
+    // removed from the board.
+    fn hit_boss(&mut self) -> bool {
+        let boss = match self.boss.as_mut() {
+            Some(boss) => boss,
+            None => return false,
+        };
+        boss.hit();
+        if !boss.is_defeated() {
+            return false;
+        }
+
+        let boss = self.boss.take().unwrap();
+        for point in boss.cells() {
+            self.vacate_obstacle_cell(&point);
+        }
+        true
+    }
+
+    // Note: The cell an obstacle leaves goes back to whatever else is there (an
+    // invincible snake passes through obstacles, and a patrol may start on a wall).
+    fn vacate_obstacle_cell(&mut self, point: &Point) {
+        let overlapped = self.snake.occupies(point)
+            || self
+                .rival
+                .as_ref()
+                .is_some_and(|rival| rival.occupies(point));
+        if overlapped {
+            self.grid.set(point, Cell::Snake);
+        } else if self.grid.get_tile(point) != Tile::Wall {
+            self.grid.set(point, Cell::Empty);
+        }
+    }
+
+    // Note: A laser picks a random row or column when it comes due (or the boss
+    // aims it at the snake's head), and while its
+    // beam is on it cuts every snake crossing it at the first body segment in the
+    // line. The head itself is never cut, so a beam costs length but not a life.
+    fn update_lasers(&mut self) {
+        if self.hazards.advance(self.calculate_interval()) {
+            let head = self.snake.get_head_point();
+            let beam = if let Some(boss) = &self.boss {
+                boss.aim_at(&head)
+            } else if self.rng.gen() {
+                Beam::Row(self.rng.gen_range(0, self.height))
+            } else {
+                Beam::Column(self.rng.gen_range(0, self.width))
+            };
+            self.hazards.start(Laser::new(beam));
+        }
+
+        let beam = match self.hazards.get_laser() {
+            Some(laser) if laser.is_firing() => laser.get_beam(),
+            _ => return,
+        };
+        let mut cut = cut_by_beam(&mut self.snake, beam);
+        if let Some(rival) = self.rival.as_mut() {
+            cut.extend(cut_by_beam(rival, beam));
+        }
+        for point in cut {
+            self.clear_cell(&point);
+        }
+    }
+
+    // Note: Runs what the map's timeline has due on the tick just taken. A laser
+    // from the timeline takes over from one the rules have on, and walls go up
+    // on the cells that are empty and on the others once they are.
+    fn run_timeline(&mut self) {
+        if self.timeline.is_empty() {
+            return;
+        }
+
+        for point in self.timeline.take_waiting() {
+            self.build_wall(point);
+        }
+        let head = self.snake.get_head_point();
+        for action in self.timeline.due(self.ticks, head) {
+            match action {
+                Action::Laser(beam) => self.hazards.start(Laser::new(beam)),
+                Action::Wall(point) => self.build_wall(point),
+                Action::WallLine(beam) => self.build_wall_line(beam),
+                Action::CloseColumn => {
+                    if let Some(x) = self.timeline.close_column(self.width) {
+                        self.build_wall_line(Beam::Column(x));
+                    }
+                }
+                Action::CloseRow => {
+                    if let Some(y) = self.timeline.close_row(self.height) {
+                        self.build_wall_line(Beam::Row(y));
+                    }
+                }
+                Action::Say(text) => self.messages.push(text),
+            }
+        }
+    }
+
+    fn build_wall_line(&mut self, line: Beam) {
+        let points: Vec<Point> = match line {
+            Beam::Row(y) => (0..self.width).map(|x| Point::new(x, y)).collect(),
+            Beam::Column(x) => (0..self.height).map(|y| Point::new(x, y)).collect(),
+        };
+        for point in points {
+            self.build_wall(point);
+        }
+    }
+
+    fn build_wall(&mut self, point: Point) {
+        if self.grid.get(&point) == Cell::Empty {
+            self.grid.set_tile(&point, Tile::Wall);
+        } else if self.grid.get_tile(&point) != Tile::Wall {
+            self.timeline.wait_for(point);
+        }
+    }
+
+    // Note: In territory mode the heads paint the cells they move onto, and
+    // both scores are the share of the board painted, in percent.
+    fn paint_territory(&mut self) {
+        if self.rules.territory_secs == 0 {
+            return;
+        }
+
+        for point in self.snake.iter() {
+            self.grid.paint(point, Owner::Player);
+        }
+        if let Some(rival) = &self.rival {
+            for point in rival.iter() {
+                self.grid.paint(point, Owner::Rival);
+            }
+        }
+
+        let cells = self.width as usize * self.height as usize;
+        self.score = (self.grid.painted_count(Owner::Player) * 100 / cells) as u64;
+        self.rival_score = (self.grid.painted_count(Owner::Rival) * 100 / cells) as u64;
+    }
+
+    // Note: The snake comes back at its starting length on the free spot closest to
+    // the centre, and is invincible for a moment so it can get its bearings.
+    // Returns false if there is no room left to respawn.
+    fn respawn(&mut self) -> bool {
+        let (start, direction) = match find_spawn(&self.grid, self.snake.get_direction()) {
+            Some(spawn) => spawn,
+            None => return false,
+        };
+
+        let crashed =
+            std::mem::replace(&mut self.snake, Snake::new(start, START_LENGTH, direction));
+        for point in crashed.iter() {
+            self.clear_cell(point);
+        }
+        for point in self.snake.iter() {
+            self.grid.set(point, Cell::Snake);
+        }
+        for key in crashed.get_keys() {
+            self.snake.add_key(*key);
+        }
+        self.lives -= 1;
+        self.effects.add(Effect::Invincible, RESPAWN_INVINCIBILITY);
+
+        true
+    }
+
+    // Note: Empties a cell a snake has left, unless another body still overlaps it
+    // (which only happens while invincible) or it holds an obstacle.
+    fn clear_cell(&mut self, point: &Point) {
+        let occupied = self.snake.occupies(point)
+            || self
+                .rival
+                .as_ref()
+                .is_some_and(|rival| rival.occupies(point));
+        if !occupied && self.grid.get(point) != Cell::Obstacle {
+            self.grid.set(point, Cell::Empty);
+        }
+    }
+
+    // Note: Runs that end without a crash keep the points still at risk.
+    fn bank(&mut self) {
+        self.score += std::mem::take(&mut self.at_risk);
+    }
+
+    fn speed_up(&mut self) {
+        self.speed = (self.speed + 1).min(MAX_SPEED);
+    }
+
+    // Note: The rival moves after the player and is removed from the board when
+    // it crashes. Returns false if it ate the last food on a full board.
+    fn step_rival(&mut self) -> bool {
+        let rival = match &self.rival {
+            Some(rival) => rival,
+            None => return true,
+        };
+
+        if self.is_fatal_move(rival, rival.get_direction()) {
+            let rival = self.rival.take().unwrap();
+            for point in rival.iter() {
+                self.clear_cell(point);
+            }
+            return true;
+        }
+
+        let next_point = self
+            .neighbour_towards(rival.get_head_point(), rival.get_direction())
+            .unwrap();
+        let rival = self.rival.as_mut().unwrap();
+        if move_snake(&mut self.grid, rival, next_point, Some(&self.snake)) {
+            self.rival_score += 1;
+            self.take_food(next_point);
+            return self.restock_food();
+        }
+
+        true
+    }
+
+    fn food_expiry(&self) -> Duration {
+        Duration::from_secs(self.rules.food_expiry_secs as u64)
+    }
+
+    // Note: Food ages by one tick interval per step rather than by wall-clock time,
+    // so pausing does not count and a seed still replays the same run.
+    // Expired food moves to another free cell, or stays put if there is none.
+    // Chain food does not expire.
+    fn age_food(&mut self) {
+        if self.rules.food_expiry_secs == 0 || self.rules.chain_food {
+            return;
+        }
+
+        self.food_age += self.calculate_interval();
+        if self.food_age < self.food_expiry() {
+            return;
+        }
+
+        if let Some(expired) = self.food {
+            if self.place_food() {
+                self.grid.set(&expired, Cell::Empty);
+            } else {
+                self.food = Some(expired);
+            }
+        }
+    }
+
+    // Note: Removes the food that was eaten at `point` and returns whether it was
+    // the one that was due, which is always the case outside of chain mode.
+    fn take_food(&mut self, point: Point) -> bool {
+        if !self.rules.chain_food {
+            self.extra_food.retain(|food| *food != point);
+            return true;
+        }
+
+        let position = self.chain.iter().position(|food| food.point == point);
+        if let Some(position) = position {
+            self.chain.remove(position);
+        }
+        self.food = self.chain.first().map(|food| food.point);
+
+        position == Some(0)
+    }
+
+    // Returns false when there is no free cell left for new food. The food is
+    // only replaced when it was the one eaten, and extra foods are topped up.
+    fn restock_food(&mut self) -> bool {
+        if !self.rules.chain_food {
+            let eaten = self
+                .food
+                .is_none_or(|food| self.grid.get(&food) != Cell::Food);
+            let placed = !eaten || self.place_food();
+            self.place_extra_food();
+            return placed;
+        }
+
+        if self.chain.is_empty() {
+            self.place_chain()
+        } else {
+            true
+        }
+    }
+
+    // Note: A new chain is only laid out once the previous one is used up, with
+    // as many numbers as there are free cells for, up to the full chain length.
+    fn place_chain(&mut self) -> bool {
+        let mut candidates = self.food_candidates();
+        candidates.shuffle(&mut self.rng);
+        for (number, point) in (1..=CHAIN_LENGTH).zip(candidates) {
+            self.grid.set(&point, Cell::Food);
+            self.chain.push(ChainFood::new(point, number));
+        }
+        self.food = self.chain.first().map(|food| food.point);
+
+        self.food.is_some()
+    }
+
+    // Note: Food is picked from the free cells rather than by retrying random
+    // points, so placement stays fast on a nearly full board. When reachability
+    // is enabled, cells walled off by the snake's body are avoided unless they
+    // are the only ones left.
+    fn food_candidates(&self) -> Vec<Point> {
+        let candidates = self.grid.free_points();
+        if !self.rules.food_reachability {
+            return candidates;
+        }
+
+        let reachable = self.grid.reachable_from(self.snake.get_head_point());
+        let reachable_candidates: Vec<Point> = candidates
+            .iter()
+            .filter(|point| reachable.contains(point))
+            .copied()
+            .collect();
+        if reachable_candidates.is_empty() {
+            candidates
+        } else {
+            reachable_candidates
+        }
+    }
+
+    // Note: The next spawn is always drawn one pickup in advance and used when
+    // it is still a valid candidate, so showing it to the player (or not) never
+    // changes the sequence of spawns for a given seed.
+    // Returns false when there is no free cell at all.
+    fn place_food(&mut self) -> bool {
+        self.food_age = Duration::ZERO;
+        if self.rules.food_reachability {
+            return self.place_reachable_food();
+        }
+
+        self.food = match self.next_food.take() {
+            Some(point) if self.grid.is_free(&point) => Some(point),
+            _ => self.pick_free_point(),
+        };
+
+        if let Some(food_point) = self.food {
+            self.grid.set(&food_point, Cell::Food);
+            self.next_food = self.pick_free_point();
+        }
+
+        self.food.is_some()
+    }
+
+    // Note: Extra foods are picked the same way as the food, but never drawn in
+    // advance or moved when they get old.
+    fn place_extra_food(&mut self) {
+        let wanted = (self.rules.food_count as usize).saturating_sub(1);
+        while self.extra_food.len() < wanted {
+            match self.pick_free_point() {
+                Some(point) => {
+                    self.grid.set(&point, Cell::Food);
+                    self.extra_food.push(point);
+                }
+                None => break,
+            }
+        }
+    }
+
+    // Note: Risk mode needs somewhere to bank points, so boards without safe zones
+    // of their own get one in the middle of each quarter of the board, leaving out
+    // any cells that are not empty floor. They are placed before the obstacle
+    // clusters, which keep off them.
+    fn place_safe_zones(&mut self) {
+        let has_zones = self
+            .grid
+            .points()
+            .any(|(point, _)| self.grid.get_tile(&point) == Tile::Safe);
+        if !self.rules.risk || has_zones {
+            return;
+        }
+
+        for (x, y) in [(1, 1), (3, 1), (1, 3), (3, 3)] {
+            let left = (self.width as u32 * x / 4).saturating_sub(SAFE_ZONE_SIZE as u32 / 2);
+            let top = (self.height as u32 * y / 4).saturating_sub(SAFE_ZONE_SIZE as u32 / 2);
+            for dy in 0..SAFE_ZONE_SIZE {
+                for dx in 0..SAFE_ZONE_SIZE {
+                    let point = Point::new(left as u16 + dx, top as u16 + dy);
+                    if self.grid.get_tile(&point) == Tile::Floor
+                        && self.grid.get(&point) == Cell::Empty
+                    {
+                        self.grid.set_tile(&point, Tile::Safe);
+                    }
+                }
+            }
+        }
+    }
+
+    fn place_obstacle_clusters(&mut self) {
+        let head = self.snake.get_head_point();
+        for _ in 0..self.rules.obstacles {
+            if let Some(cluster) = procgen::generate_cluster(&self.grid, head, &mut self.rng) {
+                for point in cluster {
+                    self.grid.set_tile(&point, Tile::Wall);
+                }
+            }
+        }
+    }
+
+    fn pick_free_point(&mut self) -> Option<Point> {
+        let head = self.snake.get_head_point();
+        self.rules.food_spawn.pick(
+            &self.grid,
+            head,
+            self.grid.iter_free_points(),
+            &mut self.rng,
+        )
+    }
+
+    fn place_reachable_food(&mut self) -> bool {
+        let candidates = self.food_candidates();
+        self.food = match self.next_food.take() {
+            Some(point) if candidates.contains(&point) => Some(point),
+            _ => self.pick_candidate(&candidates),
+        };
+
+        if let Some(food_point) = self.food {
+            self.grid.set(&food_point, Cell::Food);
+            let next_candidates: Vec<Point> = candidates
+                .into_iter()
+                .filter(|point| *point != food_point)
+                .collect();
+            self.next_food = self.pick_candidate(&next_candidates);
+        }
+
+        self.food.is_some()
+    }
+
+    fn pick_candidate(&mut self, candidates: &[Point]) -> Option<Point> {
+        let head = self.snake.get_head_point();
+        self.rules
+            .food_spawn
+            .pick(&self.grid, head, candidates.iter().copied(), &mut self.rng)
+    }
+}
+
+// The spawn the map gives, if a snake fits there, and otherwise the one
+// `find_spawn` picks.
+pub(crate) fn map_spawn(
+    grid: &Grid,
+    spawn: Option<(Point, Direction)>,
+    preferred: Direction,
+) -> Option<(Point, Direction)> {
+    let (width, height) = (grid.get_width(), grid.get_height());
+    spawn
+        .filter(|(head, direction)| {
+            grid.is_free(head)
+                && head
+                    .neighbour_towards(direction.opposite(), width, height)
+                    .is_some_and(|tail| grid.is_free(&tail))
+        })
+        .or_else(|| find_spawn(grid, preferred))
+}
+
+// Ticks a second at `speed`, from 0 up to MAX_SPEED, before double speed and
+// speed zones. Every speed step adds the same number of ticks.
+pub fn speed_ticks_per_second(speed: u16) -> u32 {
+    let step = (MAX_TICKS_PER_SECOND - MIN_TICKS_PER_SECOND) / MAX_SPEED as u32;
+    MIN_TICKS_PER_SECOND + step * speed.min(MAX_SPEED) as u32
+}
+
+// How long a tick takes at `speed`, before double speed and speed zones.
+pub fn speed_interval(speed: u16) -> Duration {
+    Duration::from_secs(1) / speed_ticks_per_second(speed)
+}
+
+// Note: Finds the free spot closest to the centre with room for a two cell snake
+// and SPAWN_CLEARANCE cells to enter straight ahead of it, so it never starts
+// right in front of a wall. `preferred` is tried first at every spot. Boards too
+// cramped for that anywhere get the spot with the most room ahead.
+fn find_spawn(grid: &Grid, preferred: Direction) -> Option<(Point, Direction)> {
+    let centre = Point::new(grid.get_width() / 2, grid.get_height() / 2);
+    let mut directions = Direction::all();
+    directions.sort_by_key(|direction| *direction != preferred);
+    find_spawn_near(grid, &directions, centre)
+}
+
+// Like `find_spawn`, closest to `target` instead of the centre, heading one of
+// `directions` (tried in order).
+pub(crate) fn find_spawn_near(
+    grid: &Grid,
+    directions: &[Direction],
+    target: Point,
+) -> Option<(Point, Direction)> {
+    let (width, height) = (grid.get_width(), grid.get_height());
+    let mut candidates = grid.free_points();
+    candidates.sort_by_key(|point| {
+        let (dx, dy) = point.offset_to(&target);
+        dx.unsigned_abs() + dy.unsigned_abs()
+    });
+
+    let mut best: Option<(u16, Point, Direction)> = None;
+    for head in candidates {
+        for direction in directions.iter().copied() {
+            let tail_free = head
+                .neighbour_towards(direction.opposite(), width, height)
+                .is_some_and(|tail| grid.is_free(&tail));
+            if !tail_free {
+                continue;
+            }
+            let room = clearance(grid, head, direction);
+            if room >= SPAWN_CLEARANCE {
+                return Some((head, direction));
+            }
+            // A snake with no room at all still fits on boards too small for more.
+            if best.is_none_or(|(most, _, _)| room > most) {
+                best = Some((room, head, direction));
+            }
+        }
+    }
+
+    best.map(|(_, head, direction)| (head, direction))
+}
+
+// Counts the cells, up to SPAWN_CLEARANCE, a snake at `head` can move straight into.
+fn clearance(grid: &Grid, head: Point, direction: Direction) -> u16 {
+    let (width, height) = (grid.get_width(), grid.get_height());
+    let mut point = head;
+    let mut room = 0;
+    while room < SPAWN_CLEARANCE {
+        match point.neighbour_towards(direction, width, height) {
+            Some(next)
+                if matches!(grid.get(&next), Cell::Empty | Cell::Food)
+                    && grid.get_tile(&next).is_clear()
+                    && grid.get_tile(&next).allows_entry(direction) =>
+            {
+                point = next;
+                room += 1;
+            }
+            _ => break,
+        }
+    }
+
+    room
+}
+
+// Returns the top left cell of the free spot for a boss furthest from `head`.
+fn find_boss_spawn(grid: &Grid, head: Point) -> Option<Point> {
+    let (width, height) = (grid.get_width(), grid.get_height());
+    grid.free_points()
+        .into_iter()
+        .filter(|origin| {
+            origin.x + boss::BOSS_SIZE <= width
+                && origin.y + boss::BOSS_SIZE <= height
+                && boss::cells_at(*origin)
+                    .iter()
+                    .all(|point| grid.get(point) == Cell::Empty)
+        })
+        .max_by_key(|origin| {
+            let (dx, dy) = origin.offset_to(&head);
+            dx.unsigned_abs() + dy.unsigned_abs()
+        })
+}
+
+// Returns the cells cut off the snake by a firing beam.
+fn cut_by_beam(snake: &mut Snake, beam: Beam) -> Vec<Point> {
+    let index = snake.iter().skip(1).position(|point| beam.covers(point));
+    match index {
+        Some(index) => snake.cut_at(index + 1),
+        None => Vec::new(),
+    }
+}
+
+// Moves the snake one cell onto `next_point`, keeping the grid in sync.
+// Cells that overlap another body or an obstacle (an invincible head passes
+// through both) keep what is there. Keys are picked up on the way, and a door
+// the snake has the key for opens for good. Returns true if the snake ate the food there.
+fn move_snake(
+    grid: &mut Grid,
+    snake: &mut Snake,
+    next_point: Point,
+    other: Option<&Snake>,
+) -> bool {
+    let eats = grid.get(&next_point) == Cell::Food;
+    if let Some(tail_point) = snake.slither(next_point) {
+        let overlapped = snake.occupies(&tail_point)
+            || other.is_some_and(|other| other.occupies(&tail_point))
+            || grid.get(&tail_point) == Cell::Obstacle;
+        if !overlapped {
+            grid.set(&tail_point, Cell::Empty);
+        }
+    }
+    if grid.get(&next_point) != Cell::Obstacle {
+        grid.set(&next_point, Cell::Snake);
+    }
+    match grid.get_tile(&next_point) {
+        Tile::Key(key) => {
+            snake.add_key(key);
+            grid.set_tile(&next_point, Tile::Floor);
+        }
+        Tile::Door(key) if snake.use_key(key) => grid.set_tile(&next_point, Tile::Floor),
+        _ => {}
+    }
+    if eats {
+        snake.grow();
+    }
+
+    eats
+}
@@ -0,0 +1,236 @@
+use crate::hazard::Beam;
+use crate::point::Point;
+
+// Closing the border stops once the board is down to this many open rows or columns.
+const MIN_OPEN_LINES: u16 = 3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Trigger {
+    // On the given tick, counting from 1.
+    At(u64),
+    // On every tick that is a multiple of the given number.
+    Every(u64),
+    // The first time the snake's head gets to the cell.
+    Enter(Point),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    // A laser on the line, telegraphed and fired like the ones the rules schedule.
+    Laser(Beam),
+    Wall(Point),
+    WallLine(Beam),
+    // Walls off the outermost open column, on the left and the right side in turn.
+    CloseColumn,
+    // Walls off the outermost open row, at the top and the bottom in turn.
+    CloseRow,
+    // Text shown over the board, which holds the game for a moment.
+    Say(String),
+}
+
+// Note: A line of a map's timeline, written in the header as
+//
+//   at = 300 laser row 5
+//   every = 200 close column
+//   on = 10,4 say You found the vault!
+//
+// `at` runs the action once on the given tick, `every` on every multiple of
+// the number of ticks and `on` the first time the snake gets to the cell. The
+// actions are `laser row N` and `laser column N`, `wall X,Y`, `wall row N` and
+// `wall column N`, `close column` and `close row`, which shrink the board one
+// line at a time from its edges, and `say` followed by the text to show.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimelineEvent {
+    pub trigger: Trigger,
+    pub action: Action,
+}
+
+impl TimelineEvent {
+    // `key` is the header key, `at`, `every` or `on`.
+    pub fn parse(key: &str, value: &str) -> Option<Self> {
+        let (when, value) = value.trim().split_once(char::is_whitespace)?;
+        let trigger = match key {
+            "at" => Trigger::At(when.parse().ok()?),
+            "every" => match when.parse().ok()? {
+                0 => return None,
+                every => Trigger::Every(every),
+            },
+            "on" => Trigger::Enter(parse_point(when)?),
+            _ => return None,
+        };
+        if let Some(text) = value.trim().strip_prefix("say ") {
+            let action = Action::Say(text.trim().to_string());
+            return Some(Self { trigger, action });
+        }
+
+        let mut words = value.split_whitespace();
+        let (verb, object) = (words.next()?, words.next()?);
+        let action = match (verb, object) {
+            ("laser", _) => Action::Laser(parse_line(object, words.next()?)?),
+            ("wall", "row" | "column") => Action::WallLine(parse_line(object, words.next()?)?),
+            ("wall", _) => Action::Wall(parse_point(object)?),
+            ("close", "column") => Action::CloseColumn,
+            ("close", "row") => Action::CloseRow,
+            _ => return None,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+
+        Some(Self { trigger, action })
+    }
+
+    // Whether the trigger's and the action's cells are all on a board of the given size.
+    pub fn fits(&self, width: u16, height: u16) -> bool {
+        if let Trigger::Enter(point) = self.trigger {
+            if point.x >= width || point.y >= height {
+                return false;
+            }
+        }
+        match &self.action {
+            Action::Laser(beam) | Action::WallLine(beam) => match *beam {
+                Beam::Row(y) => y < height,
+                Beam::Column(x) => x < width,
+            },
+            Action::Wall(point) => point.x < width && point.y < height,
+            Action::CloseColumn | Action::CloseRow | Action::Say(_) => true,
+        }
+    }
+
+    // Whether the event is due on the tick, with the snake's head on `head`.
+    pub fn is_due(&self, tick: u64, head: Point) -> bool {
+        match self.trigger {
+            Trigger::At(at) => tick == at,
+            Trigger::Every(every) => tick.is_multiple_of(every),
+            Trigger::Enter(point) => head == point,
+        }
+    }
+
+    // The header line, which parses back into the same event.
+    pub fn to_line(&self) -> String {
+        let (key, when) = match self.trigger {
+            Trigger::At(at) => ("at", at.to_string()),
+            Trigger::Every(every) => ("every", every.to_string()),
+            Trigger::Enter(point) => ("on", format!("{},{}", point.x, point.y)),
+        };
+        let action = match &self.action {
+            Action::Laser(beam) => format!("laser {}", line_name(*beam)),
+            Action::Wall(point) => format!("wall {},{}", point.x, point.y),
+            Action::WallLine(beam) => format!("wall {}", line_name(*beam)),
+            Action::CloseColumn => "close column".to_string(),
+            Action::CloseRow => "close row".to_string(),
+            Action::Say(text) => format!("say {}", text),
+        };
+        format!("{} = {} {}", key, when, action)
+    }
+}
+
+fn parse_line(kind: &str, number: &str) -> Option<Beam> {
+    let number = number.parse().ok()?;
+    match kind {
+        "row" => Some(Beam::Row(number)),
+        "column" => Some(Beam::Column(number)),
+        _ => None,
+    }
+}
+
+fn parse_point(text: &str) -> Option<Point> {
+    let (x, y) = text.split_once(',')?;
+    Some(Point::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+fn line_name(beam: Beam) -> String {
+    match beam {
+        Beam::Row(y) => format!("row {}", y),
+        Beam::Column(x) => format!("column {}", x),
+    }
+}
+
+// Note: The timeline only says what is due when and where the border has got
+// to; the state carries the actions out. Walls cannot go on a cell with a snake,
+// food or an obstacle on it, so those cells are kept waiting and walled once
+// they are empty.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    events: Vec<TimelineEvent>,
+    // How many columns have been closed on the left and the right, and rows at the top and bottom.
+    closed_columns: (u16, u16),
+    closed_rows: (u16, u16),
+    waiting: Vec<Point>,
+    // Which of the events are `on` triggers that have gone off already.
+    entered: Vec<bool>,
+}
+
+impl Timeline {
+    pub fn new(events: &[TimelineEvent]) -> Self {
+        Self {
+            events: events.to_vec(),
+            closed_columns: (0, 0),
+            closed_rows: (0, 0),
+            waiting: Vec::new(),
+            entered: vec![false; events.len()],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    // The actions due on the tick, with `on` triggers going off only once.
+    pub fn due(&mut self, tick: u64, head: Point) -> Vec<Action> {
+        let mut due = Vec::new();
+        for (event, entered) in self.events.iter().zip(self.entered.iter_mut()) {
+            if !*entered && event.is_due(tick, head) {
+                *entered = matches!(event.trigger, Trigger::Enter(_));
+                due.push(event.action.clone());
+            }
+        }
+
+        due
+    }
+
+    // The next column to close, or None once the board is down to MIN_OPEN_LINES columns.
+    pub fn close_column(&mut self, width: u16) -> Option<u16> {
+        let (left, right) = self.closed_columns;
+        if width.saturating_sub(left + right) <= MIN_OPEN_LINES {
+            return None;
+        }
+
+        if left <= right {
+            self.closed_columns.0 += 1;
+            Some(left)
+        } else {
+            self.closed_columns.1 += 1;
+            Some(width - 1 - right)
+        }
+    }
+
+    pub fn close_row(&mut self, height: u16) -> Option<u16> {
+        let (top, bottom) = self.closed_rows;
+        if height.saturating_sub(top + bottom) <= MIN_OPEN_LINES {
+            return None;
+        }
+
+        if top <= bottom {
+            self.closed_rows.0 += 1;
+            Some(top)
+        } else {
+            self.closed_rows.1 += 1;
+            Some(height - 1 - bottom)
+        }
+    }
+
+    pub fn wait_for(&mut self, point: Point) {
+        if !self.waiting.contains(&point) {
+            self.waiting.push(point);
+        }
+    }
+
+    // Hands over the waiting cells, for the state to wait for those still not empty again.
+    pub fn take_waiting(&mut self) -> Vec<Point> {
+        std::mem::take(&mut self.waiting)
+    }
+}
@@ -0,0 +1,131 @@
+use snake_core::direction::Direction;
+use snake_core::map::Map;
+use snake_core::point::Point;
+use snake_core::rules::Rules;
+use snake_core::state::{GameState, StepOutcome};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+// Follows the cycle from wherever the snake starts, turning aside first when
+// the cycle points straight back into its neck.
+fn steer(state: &mut GameState) {
+    let (width, height) = (state.get_width(), state.get_height());
+    let current = state.get_snake().get_direction();
+    let mut direction = cycle_direction(state.get_snake().get_head_point(), width, height);
+    if direction == current.opposite() {
+        direction = current.turn_left();
+    }
+    state.turn(direction);
+}
+
+// Steps once and returns what happened with the allocations made during the step.
+fn counted_step(state: &mut GameState) -> (StepOutcome, usize) {
+    let before = allocations();
+    let outcome = state.step();
+    (outcome, allocations() - before)
+}
+
+// Note: A cycle through every cell: along the rows in a serpentine from column 1,
+// then back up column 0. Needs an even height.
+fn cycle_direction(head: Point, width: u16, height: u16) -> Direction {
+    let (x, y) = (head.x, head.y);
+    if x == 0 {
+        if y == 0 {
+            Direction::Right
+        } else {
+            Direction::Up
+        }
+    } else if y % 2 == 0 {
+        if x < width - 1 {
+            Direction::Right
+        } else {
+            Direction::Down
+        }
+    } else if x > 1 || y == height - 1 {
+        Direction::Left
+    } else {
+        Direction::Down
+    }
+}
+
+#[test]
+fn moving_on_a_huge_board_does_not_allocate() {
+    let mut state = GameState::from_map(&Map::empty(500, 200), Rules::default(), 7);
+    for _ in 0..20_000 {
+        steer(&mut state);
+        let length = state.get_snake().len();
+        let (outcome, allocated) = counted_step(&mut state);
+        assert!(matches!(outcome, StepOutcome::Moved | StepOutcome::Ate));
+        if state.get_snake().len() == length {
+            assert_eq!(
+                allocated,
+                0,
+                "{:?} allocated at tick {}",
+                outcome,
+                state.get_ticks()
+            );
+        }
+    }
+}
+
+// Note: A tick only allocates when the body outgrows its buffer, which doubles
+// each time, so filling the whole board allocates a handful of times in total.
+#[test]
+fn a_maximal_snake_only_allocates_to_grow() {
+    let (width, height) = (20, 10);
+    let mut state = GameState::from_map(&Map::empty(width, height), Rules::default(), 7);
+    let mut total = 0;
+    loop {
+        steer(&mut state);
+        let length = state.get_snake().len();
+        let (outcome, allocated) = counted_step(&mut state);
+        if state.get_snake().len() == length {
+            assert_eq!(
+                allocated,
+                0,
+                "{:?} allocated at tick {}",
+                outcome,
+                state.get_ticks()
+            );
+        }
+        total += allocated;
+        match outcome {
+            StepOutcome::Moved | StepOutcome::Ate => {}
+            StepOutcome::Won => break,
+            other => panic!("the snake should fill the board, got {:?}", other),
+        }
+    }
+
+    let cells = width as usize * height as usize;
+    assert_eq!(state.get_snake().len(), cells);
+    assert!(total <= (usize::BITS - cells.leading_zeros()) as usize);
+}
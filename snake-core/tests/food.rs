@@ -0,0 +1,51 @@
+use snake_core::map::Map;
+use snake_core::point::Point;
+use snake_core::rules::Rules;
+use snake_core::state::GameState;
+
+// A walled-off room in the top left, whose 16 floor cells the snake, starting
+// in the middle, can never get to.
+const POCKET_MAP: &str = "\
+######......
+#....#......
+#....#......
+#....#......
+#....#......
+######......
+............
+............
+............
+............
+............
+............
+";
+
+fn in_pocket(point: Point) -> bool {
+    (1..=4).contains(&point.x) && (1..=4).contains(&point.y)
+}
+
+fn first_food(rules: &Rules, seed: u64) -> Point {
+    let map = Map::parse(POCKET_MAP).unwrap();
+    GameState::from_map(&map, rules.clone(), seed)
+        .get_food()
+        .unwrap()
+}
+
+#[test]
+fn food_is_never_placed_where_the_head_cannot_reach() {
+    let rules = Rules {
+        food_reachability: true,
+        ..Rules::default()
+    };
+    for seed in 0..500 {
+        let food = first_food(&rules, seed);
+        assert!(!in_pocket(food), "seed {} put food at {:?}", seed, food);
+    }
+}
+
+// Without the rule the pocket gets food, so the test above has something to catch.
+#[test]
+fn food_lands_in_the_pocket_without_the_rule() {
+    let rules = Rules::default();
+    assert!((0..500).any(|seed| in_pocket(first_food(&rules, seed))));
+}
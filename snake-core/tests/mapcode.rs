@@ -0,0 +1,45 @@
+use snake_core::map::Map;
+use snake_core::mapcode::{self, MapCodeError};
+use snake_core::mapgen::MapGen;
+use std::fs;
+use std::path::Path;
+
+fn shipped_maps() -> Vec<Map> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../maps");
+    fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .map(|path| Map::parse(&fs::read_to_string(path).unwrap()).unwrap())
+        .collect()
+}
+
+#[test]
+fn maps_come_back_from_their_codes_unchanged() {
+    let mut maps = shipped_maps();
+    for kind in MapGen::all() {
+        maps.push(kind.generate(40, 20, 3, None));
+    }
+    for map in maps {
+        let decoded = mapcode::decode(&mapcode::encode(&map)).unwrap();
+        assert_eq!(decoded.to_text(), map.to_text());
+    }
+}
+
+#[test]
+fn mistyped_codes_are_refused() {
+    let code = mapcode::encode(&shipped_maps()[0]);
+    let mut mistyped: Vec<char> = code.chars().collect();
+    let middle = mistyped.len() / 2;
+    mistyped[middle] = if mistyped[middle] == 'A' { 'B' } else { 'A' };
+    let mistyped: String = mistyped.into_iter().collect();
+
+    assert_eq!(
+        mapcode::decode(&mistyped).unwrap_err(),
+        MapCodeError::Invalid
+    );
+    assert_eq!(
+        mapcode::decode(&code[..code.len() - 3]).unwrap_err(),
+        MapCodeError::Invalid
+    );
+}
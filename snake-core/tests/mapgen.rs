@@ -0,0 +1,25 @@
+use snake_core::mapcheck;
+use snake_core::mapgen::MapGen;
+
+// Note: Every generated board is meant to be playable as it comes: the snake
+// can start, and every floor cell can be reached from the start and left again.
+#[test]
+fn generated_boards_are_connected() {
+    for kind in MapGen::all() {
+        for (width, height) in [(10, 10), (20, 20), (31, 17), (60, 30)] {
+            for seed in 0..20 {
+                let map = kind.generate(width, height, seed, None);
+                assert_eq!(
+                    mapcheck::check(&map),
+                    Vec::new(),
+                    "{} {}x{} seed {:x}:\n{}",
+                    kind.name(),
+                    width,
+                    height,
+                    seed,
+                    map.to_text()
+                );
+            }
+        }
+    }
+}
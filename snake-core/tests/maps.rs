@@ -0,0 +1,24 @@
+use snake_core::map::Map;
+use snake_core::mapcheck;
+use std::fs;
+use std::path::Path;
+
+// Note: The maps in maps/ are what players try first, so each has to parse and
+// pass `snake-rs map validate` as it is: a map that cuts the snake off from
+// food or walls it in is only found by playing it otherwise.
+#[test]
+fn shipped_maps_validate() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../maps");
+    let mut checked = 0;
+    for entry in fs::read_dir(&directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|extension| extension != "txt") {
+            continue;
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        let map = Map::parse(&text).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+        assert_eq!(mapcheck::check(&map), Vec::new(), "{}", path.display());
+        checked += 1;
+    }
+    assert!(checked > 0, "no maps in {}", directory.display());
+}
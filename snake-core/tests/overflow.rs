@@ -0,0 +1,89 @@
+use snake_core::policy::{GreedyPolicy, Policy};
+use snake_core::rules::{Rules, SpeedRamp};
+use snake_core::state::{GameState, StepOutcome, MAX_SPEED};
+
+// Steers the snake with the greedy policy until the run ends or `ticks` have
+// passed, and returns the last outcome.
+fn play(state: &mut GameState, ticks: u32) -> StepOutcome {
+    let mut policy = GreedyPolicy;
+    let mut outcome = StepOutcome::Moved;
+    for _ in 0..ticks {
+        let direction = policy.choose(state, state.get_snake());
+        state.turn(direction);
+        outcome = state.step();
+        assert!(state.get_speed() <= MAX_SPEED);
+        state.calculate_interval();
+        if !matches!(outcome, StepOutcome::Moved | StepOutcome::Ate) {
+            break;
+        }
+    }
+
+    outcome
+}
+
+// Note: Boards with fewer cells than there are speeds ramp up on every food
+// instead of dividing by zero.
+#[test]
+fn tiny_boards_play_out() {
+    for (width, height) in [
+        (2, 1),
+        (1, 2),
+        (3, 1),
+        (2, 2),
+        (3, 2),
+        (3, 3),
+        (4, 4),
+        (7, 1),
+    ] {
+        for speed_ramp in SpeedRamp::all() {
+            for seed in 0..20 {
+                let rules = Rules {
+                    speed_ramp,
+                    ..Rules::default()
+                };
+                let mut state = GameState::new(width, height, rules, seed);
+                play(&mut state, 1_000);
+            }
+        }
+    }
+}
+
+#[test]
+fn the_speed_ramps_up_on_boards_smaller_than_the_ramp() {
+    let mut state = GameState::new(3, 2, Rules::default(), 1);
+    let outcome = play(&mut state, 1_000);
+    assert!(state.get_score() > 0, "ended with {:?}", outcome);
+    assert!(state.get_speed() > 0);
+}
+
+#[test]
+fn boards_with_more_cells_than_a_u16_keep_playing() {
+    for (width, height) in [(300, 300), (u16::MAX, 2), (2, u16::MAX)] {
+        for wrap_around in [false, true] {
+            let rules = Rules {
+                wrap_around,
+                ..Rules::default()
+            };
+            let mut state = GameState::new(width, height, rules, 7);
+            let outcome = play(&mut state, 2_000);
+            assert!(
+                matches!(outcome, StepOutcome::Moved | StepOutcome::Ate),
+                "{}x{} ended with {:?}",
+                width,
+                height,
+                outcome
+            );
+        }
+    }
+}
+
+#[test]
+fn the_perfect_game_bonus_of_a_huge_board_fits_the_score() {
+    let mut state = GameState::new(300, 300, Rules::default(), 7);
+    let cells = 300 * 300;
+    assert_eq!(state.award_perfect_game_bonus(), cells);
+    assert_eq!(state.get_score(), cells);
+
+    play(&mut state, 100);
+    assert!(state.get_score() >= cells);
+}
@@ -0,0 +1,56 @@
+use snake_core::direction::Direction::{self, *};
+use snake_core::map::Map;
+use snake_core::rules::Rules;
+use snake_core::state::{GameState, StepOutcome};
+
+// A 4x3 room, with the player along the top and the rival along the bottom.
+const ROOM_MAP: &str = "\
+spawn = 1,0 right
+spawn = 1,2 right
+....####
+....####
+....####
+########
+########
+########
+########
+########
+";
+
+// The player's and the rival's turn on each tick, until the rival eats the
+// last food with the board full.
+const TURNS: [(Direction, Direction); 9] = [
+    (Right, Left),
+    (Down, Up),
+    (Right, Right),
+    (Up, Down),
+    (Left, Left),
+    (Down, Up),
+    (Down, Up),
+    (Right, Right),
+    (Up, Down),
+];
+
+#[test]
+fn rival_filling_the_board_is_not_a_win() {
+    let rules = Rules {
+        rival: true,
+        ..Rules::default()
+    };
+    let map = Map::parse(ROOM_MAP).unwrap();
+    let mut state = GameState::from_map(&map, rules, 4);
+
+    let mut outcomes = Vec::new();
+    for (player, rival) in TURNS.iter() {
+        state.turn(*player);
+        state.turn_rival(*rival);
+        outcomes.push(state.step());
+    }
+
+    let (last, earlier) = outcomes.split_last().unwrap();
+    assert!(earlier
+        .iter()
+        .all(|outcome| matches!(outcome, StepOutcome::Moved | StepOutcome::Ate)));
+    assert_eq!(*last, StepOutcome::RivalFilled);
+    assert!(state.get_grid().free_points().is_empty());
+}
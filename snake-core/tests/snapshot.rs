@@ -0,0 +1,95 @@
+use snake_core::point::Point;
+use snake_core::rules::Rules;
+use snake_core::segment::Segment;
+use snake_core::snapshot::{self, SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+use snake_core::state::GameState;
+use std::convert::TryInto;
+
+// Reads the layout back the way another program would, from the description
+// in snapshot.rs.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> &[u8] {
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        taken
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take(1)[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.take(2).try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take(4).try_into().unwrap())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take(8).try_into().unwrap())
+    }
+
+    fn points(&mut self) -> Vec<Point> {
+        let length = self.u32();
+        (0..length)
+            .map(|_| Point::new(self.u16(), self.u16()))
+            .collect()
+    }
+}
+
+#[test]
+fn snapshots_read_back_as_the_state() {
+    let rules = Rules {
+        rival: true,
+        ..Rules::default()
+    };
+    let mut state = GameState::new(20, 15, rules, 7);
+    state.step();
+    let bytes = snapshot::encode(&state);
+    let mut reader = Reader { bytes: &bytes };
+
+    assert_eq!(reader.take(4), SNAPSHOT_MAGIC);
+    assert_eq!(reader.u8(), SNAPSHOT_VERSION);
+    assert_eq!(
+        snapshot::direction_from_code(reader.u8()),
+        Some(state.get_snake().get_direction())
+    );
+    assert_eq!((reader.u16(), reader.u16()), (20, 15));
+    assert_eq!(reader.u16() as u64, state.get_score());
+    assert_eq!(reader.u16() as u64, state.get_rival_score());
+    assert_eq!(reader.u16(), state.get_speed());
+    assert_eq!(reader.u64(), state.get_ticks());
+    assert_eq!(reader.u64(), 7);
+    let food = Point::new(reader.u16(), reader.u16());
+    assert_eq!(Some(food), state.get_food());
+    let snake: Vec<Point> = state.get_snake().iter().copied().collect();
+    assert_eq!(reader.points(), snake);
+    let rival: Vec<Point> = state.get_rival().unwrap().iter().copied().collect();
+    assert_eq!(reader.points(), rival);
+    reader.take(20 * 15);
+    assert_eq!(reader.u64(), state.get_score());
+    assert_eq!(reader.u64(), state.get_rival_score());
+    assert!(reader.bytes.is_empty());
+}
+
+// Note: Boards can hold far longer snakes than a u16 counts, and a length cut
+// short would have every field after the points read from the wrong place.
+#[test]
+fn snakes_longer_than_a_u16_keep_their_length() {
+    let segments: Vec<Segment> = (0..70_000u32)
+        .map(|i| Segment::new(Point::new((i % 300) as u16, (i / 300) as u16)))
+        .collect();
+    let mut bytes = Vec::new();
+    snapshot::encode_segments(&mut bytes, &segments);
+    let mut reader = Reader { bytes: &bytes };
+
+    let points = reader.points();
+    assert_eq!(points.len(), 70_000);
+    assert_eq!(points.last(), Some(&Point::new(99, 233)));
+    assert!(reader.bytes.is_empty());
+}
@@ -0,0 +1,29 @@
+use snake_core::rules::Rules;
+use snake_core::state::{self, GameState, MAX_SPEED};
+use std::time::Duration;
+
+#[test]
+fn every_speed_step_adds_the_same_ticks_per_second() {
+    let ticks: Vec<u32> = (0..=MAX_SPEED).map(state::speed_ticks_per_second).collect();
+    assert_eq!(ticks.first(), Some(&8));
+    assert_eq!(ticks.last(), Some(&32));
+    assert!(ticks
+        .windows(2)
+        .all(|pair| pair[1] - pair[0] == ticks[1] - ticks[0]));
+    assert_eq!(state::speed_ticks_per_second(MAX_SPEED + 1), 32);
+}
+
+#[test]
+fn ticks_last_a_second_divided_by_the_ticks_per_second() {
+    let state = GameState::new(20, 20, Rules::default(), 0);
+    assert_eq!(state.calculate_interval(), Duration::from_millis(125));
+    assert_eq!(state.get_ticks_per_second(), 8.0);
+
+    let rules = Rules {
+        double_speed: true,
+        start_speed: MAX_SPEED,
+        ..Rules::default()
+    };
+    let state = GameState::new(20, 20, rules, 0);
+    assert_eq!(state.get_ticks_per_second(), 64.0);
+}
@@ -0,0 +1,56 @@
+use snake_core::mapcheck;
+use snake_core::mapgen::{MapGen, Symmetry};
+use snake_core::rules::Rules;
+use snake_core::state::GameState;
+
+// Note: Symmetric boards are only fair if the symmetry survives the repair
+// every generated board goes through, so this checks the boards as they come
+// out, on even and odd sizes.
+#[test]
+fn symmetric_boards_stay_symmetric() {
+    for kind in MapGen::all() {
+        for symmetry in Symmetry::all() {
+            for (width, height) in [(20, 20), (21, 15), (40, 20)] {
+                for seed in 0..10 {
+                    let map = kind.generate(width, height, seed, Some(symmetry));
+                    assert!(
+                        symmetry.holds_for(&map),
+                        "{} {} {}x{} seed {:x}:\n{}",
+                        kind.name(),
+                        symmetry.name(),
+                        width,
+                        height,
+                        seed,
+                        map.to_text()
+                    );
+                    assert_eq!(mapcheck::check(&map), Vec::new());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn both_snakes_start_on_the_spawns() {
+    let rules = Rules {
+        rival: true,
+        ..Rules::default()
+    };
+    for kind in MapGen::all() {
+        for symmetry in Symmetry::all() {
+            for seed in 0..10 {
+                let map = kind.generate(20, 20, seed, Some(symmetry));
+                let state = GameState::from_map(&map, rules.clone(), seed);
+                let rival = state.get_rival().unwrap();
+                let spawns = [
+                    (
+                        state.get_snake().get_head_point(),
+                        state.get_snake().get_direction(),
+                    ),
+                    (rival.get_head_point(), rival.get_direction()),
+                ];
+                assert_eq!(map.get_spawns(), spawns);
+            }
+        }
+    }
+}
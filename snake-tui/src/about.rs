@@ -0,0 +1,90 @@
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "audio") {
+        features.push("audio");
+    }
+    if cfg!(feature = "chat") {
+        features.push("chat");
+    }
+    if cfg!(feature = "compression") {
+        features.push("compression");
+    }
+    if cfg!(feature = "async") {
+        features.push("async");
+    }
+    if cfg!(feature = "alloc-stats") {
+        features.push("alloc-stats");
+    }
+
+    features
+}
+
+pub fn version_line() -> String {
+    let features = enabled_features();
+    if features.is_empty() {
+        format!("snake-rs {} (minimal build)", VERSION)
+    } else {
+        format!("snake-rs {} (features: {})", VERSION, features.join(", "))
+    }
+}
+
+// Note: Everything needed to tell whether two results are comparable: the same
+// seed only replays the same game on the same version, mode and board size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunInfo {
+    pub version: String,
+    pub seed: u64,
+    pub mode: String,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl RunInfo {
+    pub fn new(seed: u64, mode: &str, width: u16, height: u16) -> Self {
+        Self {
+            version: VERSION.to_string(),
+            seed,
+            mode: mode.to_string(),
+            width,
+            height,
+        }
+    }
+
+    pub fn get_board_size(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+
+    // Note: The single-line form used in stats and other exported files,
+    // e.g. `version=0.1.0 seed=1f2e mode=classic board=20x20`.
+    pub fn to_line(&self) -> String {
+        format!(
+            "version={} seed={:x} mode={} board={}",
+            self.version,
+            self.seed,
+            self.mode,
+            self.get_board_size()
+        )
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut info = Self::new(0, "", 0, 0);
+        for field in line.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            match key {
+                "version" => info.version = value.to_string(),
+                "seed" => info.seed = u64::from_str_radix(value, 16).ok()?,
+                "mode" => info.mode = value.to_string(),
+                "board" => {
+                    let (width, height) = value.split_once('x')?;
+                    info.width = width.parse().ok()?;
+                    info.height = height.parse().ok()?;
+                }
+                _ => {}
+            }
+        }
+
+        Some(info)
+    }
+}
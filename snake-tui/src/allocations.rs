@@ -0,0 +1,79 @@
+#[cfg(feature = "alloc-stats")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Whether allocations are counted at all, which takes the `alloc-stats` feature.
+pub const COUNTED: bool = cfg!(feature = "alloc-stats");
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static ALLOCATED_BYTES: Cell<usize> = const { Cell::new(0) };
+}
+
+// Note: Wraps the system allocator to count allocations for `--bench-sim`, and
+// is only installed with the `alloc-stats` feature so other builds allocate
+// straight from the system. Only allocations on a thread that called `start`
+// are counted, so the terminal reader and other threads do not show up in what
+// the engine is charged with. Reallocations count as allocations of the new size.
+#[cfg(feature = "alloc-stats")]
+#[derive(Debug)]
+pub struct CountingAllocator;
+
+#[cfg(feature = "alloc-stats")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+// Starts counting the calling thread's allocations.
+pub fn start() {
+    COUNTING.with(|counting| counting.set(true));
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Allocations {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+impl Allocations {
+    // What the calling thread has allocated since it called `start`.
+    pub fn now() -> Self {
+        Self {
+            count: ALLOCATIONS.with(Cell::get),
+            bytes: ALLOCATED_BYTES.with(Cell::get),
+        }
+    }
+
+    pub fn since(&self, earlier: Allocations) -> Self {
+        Self {
+            count: self.count - earlier.count,
+            bytes: self.bytes - earlier.bytes,
+        }
+    }
+
+    pub fn add(&mut self, other: Allocations) {
+        self.count += other.count;
+        self.bytes += other.bytes;
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+fn count(size: usize) {
+    if COUNTING.with(Cell::get) {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        ALLOCATED_BYTES.with(|bytes| bytes.set(bytes.get() + size));
+    }
+}
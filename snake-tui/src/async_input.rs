@@ -0,0 +1,114 @@
+use crate::command::Command;
+use crate::input::Input;
+use crossterm::event::EventStream;
+use futures_util::StreamExt;
+use std::io;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time;
+
+// Note: An input source on a tokio runtime, for `--async-input`: the terminal
+// is read as a stream of events and signals as streams of their own, and every
+// wait for input is the next one raced against a timer. Inputs come in over one
+// channel from tasks on the runtime, so networked inputs (peers, a server, a
+// chat) only need a task of their own sending commands into it. Ticks,
+// rendering and the journal stay on the game loop as they are without it; the
+// runtime only runs while the game waits for input, which is when the game has
+// time for it.
+#[derive(Debug)]
+pub struct AsyncInput {
+    runtime: Runtime,
+    receiver: UnboundedReceiver<Input>,
+}
+
+impl AsyncInput {
+    pub fn start() -> io::Result<Self> {
+        let runtime = Builder::new_current_thread()
+            .enable_io()
+            .enable_time()
+            .build()?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        runtime.spawn(read_terminal(sender.clone()));
+        // Registered before anything runs, so no signal is missed.
+        let signals = runtime.block_on(async { Signals::new() })?;
+        runtime.spawn(watch_signals(signals, sender));
+        Ok(Self { runtime, receiver })
+    }
+
+    // The next input, if one comes within `wait_for`.
+    pub fn next(&mut self, wait_for: Duration) -> Option<Input> {
+        let receiver = &mut self.receiver;
+        self.runtime.block_on(async {
+            // With every task done there is only the timer left.
+            tokio::select! {
+                Some(input) = receiver.recv() => Some(input),
+                _ = time::sleep(wait_for) => None,
+            }
+        })
+    }
+}
+
+// Once the terminal cannot be read any more, the other inputs still come in.
+async fn read_terminal(sender: UnboundedSender<Input>) {
+    let mut events = EventStream::new();
+    while let Some(Ok(event)) = events.next().await {
+        if sender.send(Input::Terminal(event)).is_err() {
+            break;
+        }
+    }
+}
+
+// The signals that stop the game: the ones `kill` sends by default or on
+// request, and the terminal closing.
+#[cfg(unix)]
+struct Signals {
+    terminate: Signal,
+    interrupt: Signal,
+    quit: Signal,
+    hangup: Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+            quit: signal(SignalKind::quit())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.terminate.recv() => {}
+            _ = self.interrupt.recv() => {}
+            _ = self.quit.recv() => {}
+            _ = self.hangup.recv() => {}
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) {
+        tokio::signal::ctrl_c().await.ok();
+    }
+}
+
+// Being told to stop becomes Ctrl+C, as it does for the terminal reader, only
+// as an input of its own so nothing waiting has to wake up to look for it.
+async fn watch_signals(mut signals: Signals, sender: UnboundedSender<Input>) {
+    signals.recv().await;
+    sender.send(Input::Command(Command::ForceQuit)).ok();
+}
@@ -0,0 +1,156 @@
+use crate::challenge;
+use crate::emergency::Journal;
+use crate::menu::Menu;
+use crate::savefile::{self, SaveFile};
+use std::cmp::Reverse;
+use std::io;
+#[cfg(feature = "compression")]
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Note: Autosaves take turns over a few slots, each new one replacing the
+// oldest, so going back to an earlier point of a long run is still possible.
+const AUTOSAVE_FILES: [SaveFile; 3] = [
+    SaveFile::new("autosave1", &[savefile::unchanged]).compressed(),
+    SaveFile::new("autosave2", &[savefile::unchanged]).compressed(),
+    SaveFile::new("autosave3", &[savefile::unchanged]).compressed(),
+];
+
+// A run saved part of the way through, with when and on what score.
+#[derive(Debug, Clone)]
+pub struct Autosave {
+    // Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub score: u64,
+    pub journal: Journal,
+}
+
+impl Autosave {
+    // The autosaves there are, newest first.
+    pub fn load_all() -> Vec<Self> {
+        let mut autosaves: Vec<Self> = AUTOSAVE_FILES
+            .iter()
+            .filter_map(|file| Self::parse(&file.load()?))
+            .collect();
+        autosaves.sort_by_key(|autosave| Reverse(autosave.saved_at));
+        autosaves
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn file_paths() -> Vec<PathBuf> {
+        AUTOSAVE_FILES.iter().filter_map(SaveFile::path).collect()
+    }
+
+    // Goes in an empty slot if there is one, and over the oldest autosave otherwise.
+    pub fn save(journal: &Journal, score: u64) -> io::Result<()> {
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let slot = AUTOSAVE_FILES
+            .iter()
+            .min_by_key(|file| {
+                file.load()
+                    .and_then(|contents| Self::parse(&contents))
+                    .map(|autosave| autosave.saved_at)
+            })
+            .unwrap();
+        let autosave = Self {
+            saved_at,
+            score,
+            journal: journal.clone(),
+        };
+        slot.save(&autosave.to_contents())
+    }
+
+    // e.g. `10-14 13:05  25`, in UTC.
+    pub fn label(&self) -> String {
+        let date = challenge::date_label(self.saved_at / 86_400);
+        let minutes = self.saved_at % 86_400 / 60;
+        format!(
+            "{} {:02}:{:02} {:>3}",
+            &date[5..],
+            minutes / 60,
+            minutes % 60,
+            self.score
+        )
+    }
+
+    // The Autosaves menu, with `autosaves` in order followed by Back.
+    pub fn menu(autosaves: &[Self]) -> Menu {
+        let mut items: Vec<String> = autosaves.iter().map(Self::label).collect();
+        items.push("Back".to_string());
+        let mut menu = Menu::new("Autosaves", items);
+        if autosaves.is_empty() {
+            menu.set_hint("No autosaves yet");
+        }
+
+        menu
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let mut saved_at = None;
+        let mut score = None;
+        for line in contents.lines() {
+            match line.split_once('=') {
+                Some((key, value)) if key.trim() == "saved_at" => {
+                    saved_at = value.trim().parse().ok()
+                }
+                Some((key, value)) if key.trim() == "score" => score = value.trim().parse().ok(),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            saved_at: saved_at?,
+            score: score?,
+            journal: Journal::parse(contents)?,
+        })
+    }
+
+    fn to_contents(&self) -> String {
+        format!(
+            "saved_at = {}\nscore = {}\n{}",
+            self.saved_at,
+            self.score,
+            self.journal.to_contents()
+        )
+    }
+}
+
+// Note: Counts play time rather than wall-clock time, so a paused run is not
+// saved over and over, and the foods eaten since the last autosave.
+#[derive(Debug)]
+pub struct AutosaveTimer {
+    every: Duration,
+    foods: u16,
+    last: Duration,
+    eaten: u16,
+}
+
+impl AutosaveTimer {
+    pub fn new(secs: u16, foods: u16) -> Self {
+        Self {
+            every: Duration::from_secs(secs as u64),
+            foods,
+            last: Duration::ZERO,
+            eaten: 0,
+        }
+    }
+
+    // Called every tick with the run's play time. Returns true when it is time to save.
+    pub fn tick(&mut self, elapsed: Duration, ate: bool) -> bool {
+        if ate {
+            self.eaten += 1;
+        }
+        let by_time = self.every > Duration::ZERO && elapsed >= self.last + self.every;
+        let by_food = self.foods > 0 && self.eaten >= self.foods;
+        if !by_time && !by_food {
+            return false;
+        }
+
+        self.last = elapsed;
+        self.eaten = 0;
+        true
+    }
+}
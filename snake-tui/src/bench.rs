@@ -0,0 +1,88 @@
+use crate::allocations::{self, Allocations};
+use snake_core::policy::{GreedyPolicy, Policy};
+use snake_core::rules::Rules;
+use snake_core::state::{GameState, StepOutcome};
+use std::time::{Duration, Instant};
+
+const BOARD_SIZES: [(u16, u16); 3] = [(20, 20), (100, 50), (500, 200)];
+
+// Note: Plays `ticks` ticks with the greedy policy steering the player's snake
+// on each board size, starting a new game with the next seed whenever one ends.
+// The engine and the autopilot are timed and counted separately, so the
+// engine numbers are what a change to snake-core should be compared on.
+pub fn run(ticks: u64) -> Vec<String> {
+    allocations::start();
+    let mut lines = vec![
+        format!(
+            "Simulated {} ticks per board with the greedy autopilot:",
+            ticks
+        ),
+        format!(
+            "{:<10}{:>12}{:>14}{:>14}{:>16}{:>8}",
+            "board", "ticks/s", "allocs/tick", "bytes/tick", "pilot allocs", "games"
+        ),
+    ];
+    for (width, height) in BOARD_SIZES.iter() {
+        let result = simulate(*width, *height, ticks);
+        let per_tick = |value: usize, precision: usize| {
+            if allocations::COUNTED {
+                format!("{:.*}", precision, value as f64 / ticks.max(1) as f64)
+            } else {
+                "-".to_string()
+            }
+        };
+        lines.push(format!(
+            "{:<10}{:>12.0}{:>14}{:>14}{:>16}{:>8}",
+            format!("{}x{}", width, height),
+            ticks as f64 / result.engine_time.as_secs_f64().max(f64::EPSILON),
+            per_tick(result.engine.count, 2),
+            per_tick(result.engine.bytes, 1),
+            per_tick(result.autopilot.count, 2),
+            result.games
+        ));
+    }
+    lines.push("ticks/s and allocs/tick only count the engine, not the autopilot".to_string());
+    if !allocations::COUNTED {
+        lines.push(
+            "Allocations are only counted in builds with the alloc-stats feature".to_string(),
+        );
+    }
+
+    lines
+}
+
+#[derive(Debug, Default)]
+struct SimulationResult {
+    engine_time: Duration,
+    engine: Allocations,
+    autopilot: Allocations,
+    games: u64,
+}
+
+fn simulate(width: u16, height: u16, ticks: u64) -> SimulationResult {
+    let mut result = SimulationResult {
+        games: 1,
+        ..SimulationResult::default()
+    };
+    let mut state = GameState::new(width, height, Rules::default(), 0);
+    let mut policy = GreedyPolicy;
+    for _ in 0..ticks {
+        let before = Allocations::now();
+        let direction = policy.choose(&state, state.get_snake());
+        state.turn(direction);
+        let chosen = Allocations::now();
+        result.autopilot.add(chosen.since(before));
+
+        let started = Instant::now();
+        let outcome = state.step();
+        result.engine_time += started.elapsed();
+        result.engine.add(Allocations::now().since(chosen));
+
+        if !matches!(outcome, StepOutcome::Moved | StepOutcome::Ate) {
+            state = GameState::new(width, height, Rules::default(), result.games);
+            result.games += 1;
+        }
+    }
+
+    result
+}
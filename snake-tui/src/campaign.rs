@@ -0,0 +1,340 @@
+use crate::config::Config;
+use crate::menu::Menu;
+use crate::savefile::{self, SaveFile, SaveLock};
+use crate::theme::{Skin, Theme};
+use snake_core::map::Map;
+use std::io;
+
+const CAMPAIGN_FILE: SaveFile = SaveFile::new("campaign", &[savefile::unchanged]);
+const CAMPAIGN_BOARD_SIZE: u16 = 20;
+pub const MAX_STARS: u8 = 3;
+
+// Game modes that have to be unlocked in the campaign before they can be
+// picked from the Modes menu.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    Chain,
+    Territory,
+    Lasers,
+    Boss,
+}
+
+impl Mode {
+    pub fn all() -> [Mode; 4] {
+        [Mode::Chain, Mode::Territory, Mode::Lasers, Mode::Boss]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Chain => "Chain food",
+            Mode::Territory => "Territory",
+            Mode::Lasers => "Lasers",
+            Mode::Boss => "Boss",
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        match self {
+            Mode::Chain => config.chain_food = true,
+            Mode::Territory => config.territory_secs = 60,
+            Mode::Lasers => config.laser_secs = 10,
+            Mode::Boss => {
+                config.boss = true;
+                config.lives = config.lives.max(3);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Unlock {
+    Mode(Mode),
+    Theme(Theme),
+    Skin(Skin),
+}
+
+impl Unlock {
+    pub fn label(&self) -> String {
+        match self {
+            Unlock::Mode(mode) => format!("{} mode", mode.name()),
+            Unlock::Theme(theme) => format!("{} theme", theme.name()),
+            Unlock::Skin(skin) => format!("{} skin", skin.name()),
+        }
+    }
+}
+
+// Note: A level is a board plus the rules it is played with. Its stars are
+// earned by reaching the three scores in `stars`, and one star completes it,
+// which unlocks the next level along with the level's rewards.
+#[derive(Debug)]
+pub struct Level {
+    pub name: &'static str,
+    map: Option<&'static str>,
+    pub stars: [u64; MAX_STARS as usize],
+    rules: fn(&mut Config),
+    pub unlocks: &'static [Unlock],
+}
+
+impl Level {
+    pub fn map(&self) -> Map {
+        match self.map {
+            Some(map) => Map::parse(map).unwrap(),
+            None => Map::empty(CAMPAIGN_BOARD_SIZE, CAMPAIGN_BOARD_SIZE),
+        }
+    }
+
+    pub fn config(&self, base: &Config) -> Config {
+        let mut config = base.without_rules();
+        (self.rules)(&mut config);
+
+        config
+    }
+
+    pub fn stars_for(&self, score: u64) -> u8 {
+        self.stars.iter().filter(|target| score >= **target).count() as u8
+    }
+
+    // The screen shown after a run that earned `stars` and unlocked `unlocks`.
+    pub fn result_lines(&self, stars: u8, unlocks: &[Unlock]) -> Vec<String> {
+        let mut lines = vec![
+            self.name.to_string(),
+            if stars > 0 {
+                "Complete!"
+            } else {
+                "Not completed"
+            }
+            .to_string(),
+            format!("Stars {}", star_label(stars)),
+        ];
+        if stars < MAX_STARS {
+            lines.push(format!("Next star at {}", self.stars[stars as usize]));
+        }
+        if !unlocks.is_empty() {
+            lines.push(String::new());
+            lines.push("Unlocked:".to_string());
+            lines.extend(unlocks.iter().map(|unlock| unlock.label()));
+        }
+        lines.push(String::new());
+        lines.push("Any key: back".to_string());
+
+        lines
+    }
+}
+
+pub const LEVELS: [Level; 6] = [
+    Level {
+        name: "First Bite",
+        map: None,
+        stars: [5, 10, 15],
+        rules: |_| {},
+        unlocks: &[Unlock::Skin(Skin::Gold)],
+    },
+    Level {
+        name: "Swamp",
+        map: Some(include_str!("../../maps/swamp.txt")),
+        stars: [5, 10, 20],
+        rules: |_| {},
+        unlocks: &[Unlock::Mode(Mode::Chain)],
+    },
+    Level {
+        name: "Roundabout",
+        map: Some(include_str!("../../maps/roundabout.txt")),
+        stars: [5, 10, 15],
+        rules: |config| config.chain_food = true,
+        unlocks: &[Unlock::Theme(Theme::Night)],
+    },
+    Level {
+        name: "Vault",
+        map: Some(include_str!("../../maps/vault.txt")),
+        stars: [3, 6, 10],
+        rules: |config| config.lives = 2,
+        unlocks: &[Unlock::Mode(Mode::Territory)],
+    },
+    Level {
+        name: "Patrol",
+        map: Some(include_str!("../../maps/patrol.txt")),
+        stars: [5, 10, 15],
+        rules: |config| {
+            config.lives = 3;
+            config.laser_secs = 12;
+        },
+        unlocks: &[Unlock::Mode(Mode::Lasers), Unlock::Skin(Skin::Neon)],
+    },
+    Level {
+        name: "Showdown",
+        map: None,
+        stars: [3, 4, 5],
+        rules: |config| {
+            config.lives = 3;
+            config.boss = true;
+        },
+        unlocks: &[Unlock::Mode(Mode::Boss)],
+    },
+];
+
+// Note: Progress is stored in the same `key = value` format as the stats,
+// with the best stars per level as `level_<number> = <stars>`.
+#[derive(Debug, Default)]
+pub struct Progress {
+    stars: Vec<u8>,
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        Self::parse(&CAMPAIGN_FILE.load().unwrap_or_default())
+    }
+
+    // The whole file, header included, as it goes into a profile bundle.
+    pub fn to_file(&self) -> String {
+        CAMPAIGN_FILE.with_header(&self.to_contents())
+    }
+
+    pub fn from_file(contents: &str) -> Self {
+        Self::parse(&CAMPAIGN_FILE.read(contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut progress = Self {
+            stars: vec![0; LEVELS.len()],
+        };
+        for line in contents.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some(setting) => setting,
+                None => continue,
+            };
+            let level = key
+                .trim()
+                .strip_prefix("level_")
+                .and_then(|n| n.parse().ok());
+            if let (Some(level), Ok(stars)) = (level, value.trim().parse::<u8>()) {
+                if (1..=LEVELS.len()).contains(&level) {
+                    progress.stars[level - 1] = stars.min(MAX_STARS);
+                }
+            }
+        }
+
+        progress
+    }
+
+    // Held from loading until saving, see SaveFile::lock.
+    pub fn lock() -> io::Result<SaveLock> {
+        CAMPAIGN_FILE.lock()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        CAMPAIGN_FILE.save(&self.to_contents())
+    }
+
+    fn to_contents(&self) -> String {
+        self.stars
+            .iter()
+            .enumerate()
+            .map(|(i, stars)| format!("level_{} = {}\n", i + 1, stars))
+            .collect()
+    }
+
+    // Keeps the best stars of each level.
+    pub fn merge(&mut self, other: Self) {
+        for (level, stars) in other.stars.into_iter().enumerate() {
+            self.record(level, stars);
+        }
+    }
+
+    pub fn get_stars(&self, level: usize) -> u8 {
+        self.stars.get(level).copied().unwrap_or(0)
+    }
+
+    pub fn is_completed(&self, level: usize) -> bool {
+        self.get_stars(level) > 0
+    }
+
+    // The first level is always open; every other one opens once the one before it is completed.
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level == 0 || self.is_completed(level - 1)
+    }
+
+    // Keeps the best result. Returns the rewards this result unlocked for the first time.
+    pub fn record(&mut self, level: usize, stars: u8) -> Vec<Unlock> {
+        let newly_completed = !self.is_completed(level) && stars > 0;
+        if let Some(best) = self.stars.get_mut(level) {
+            *best = (*best).max(stars);
+        }
+
+        if newly_completed {
+            LEVELS[level].unlocks.to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn has_unlocked(&self, unlock: Unlock) -> bool {
+        LEVELS
+            .iter()
+            .enumerate()
+            .any(|(i, level)| self.is_completed(i) && level.unlocks.contains(&unlock))
+    }
+
+    // The level select screen, with the stars of every level unlocked so far.
+    pub fn level_menu(&self) -> Menu {
+        let mut items: Vec<String> = LEVELS
+            .iter()
+            .enumerate()
+            .map(|(i, level)| {
+                if self.is_unlocked(i) {
+                    format!(
+                        "{}. {} {}",
+                        i + 1,
+                        level.name,
+                        star_label(self.get_stars(i))
+                    )
+                } else {
+                    format!("{}. Locked", i + 1)
+                }
+            })
+            .collect();
+        items.push("Back".to_string());
+
+        Menu::new("Campaign", items)
+    }
+
+    // The Modes menu, in the order of Mode::all, marking the ones still locked.
+    pub fn mode_menu(&self) -> Menu {
+        let mut items: Vec<String> = Mode::all()
+            .iter()
+            .map(|mode| {
+                if self.has_unlocked(Unlock::Mode(*mode)) {
+                    mode.name().to_string()
+                } else {
+                    format!("{} (locked)", mode.name())
+                }
+            })
+            .collect();
+        items.push("Back".to_string());
+
+        Menu::new("Modes", items)
+    }
+
+    // Locked themes and skins count as the classic ones.
+    pub fn theme(&self, theme: Theme) -> Theme {
+        if theme == Theme::Classic || self.has_unlocked(Unlock::Theme(theme)) {
+            theme
+        } else {
+            Theme::Classic
+        }
+    }
+
+    pub fn skin(&self, skin: Skin) -> Skin {
+        if skin == Skin::Classic || self.has_unlocked(Unlock::Skin(skin)) {
+            skin
+        } else {
+            Skin::Classic
+        }
+    }
+}
+
+// Renders stars as `**.` for two out of three.
+pub fn star_label(stars: u8) -> String {
+    (0..MAX_STARS)
+        .map(|i| if i < stars { '*' } else { '.' })
+        .collect()
+}
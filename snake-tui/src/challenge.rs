@@ -0,0 +1,185 @@
+use crate::savefile::{self, SaveFile, SaveLock};
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEEDS_FILE: SaveFile = SaveFile::new("seeds", &[savefile::unchanged]);
+const MAX_RECENT_SEEDS: usize = 5;
+const MAX_SHARED_CODES: usize = 5;
+const CODE_ALPHABET: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// Note: Everyone gets the same daily seed, derived from the number of days since
+// the Unix epoch (in UTC), so the whole day's challenge is the same board.
+pub fn days_since_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or(0)
+}
+
+pub fn daily_seed(day: u64) -> u64 {
+    // splitmix64, so neighbouring days get unrelated seeds.
+    let mut z = day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+pub fn date_label(day: u64) -> String {
+    let (year, month, day_of_month) = civil_date(day);
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+// The year, month and day of the month for a number of days since the Unix epoch.
+pub fn civil_date(day: u64) -> (i64, i64, i64) {
+    // Howard Hinnant's days-to-civil conversion.
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day_of_month)
+}
+
+// Note: A challenge code is the seed in base 36 followed by a check digit, so a
+// mistyped code is rejected instead of silently starting a different game.
+pub fn encode_code(seed: u64) -> String {
+    let mut digits = Vec::new();
+    let mut rest = seed;
+    loop {
+        digits.push(CODE_ALPHABET[(rest % 36) as usize]);
+        rest /= 36;
+        if rest == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    digits.push(CODE_ALPHABET[check_digit(&digits)]);
+
+    String::from_utf8(digits).unwrap()
+}
+
+pub fn decode_code(code: &str) -> Option<u64> {
+    let digits: Vec<u8> = code
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace() && *byte != b'-')
+        .map(|byte| byte.to_ascii_uppercase())
+        .collect();
+    let (check, digits) = digits.split_last()?;
+    if digits.is_empty() || CODE_ALPHABET[check_digit(digits)] != *check {
+        return None;
+    }
+
+    digits.iter().try_fold(0u64, |seed, digit| {
+        let value = CODE_ALPHABET.iter().position(|d| d == digit)? as u64;
+        seed.checked_mul(36)?.checked_add(value)
+    })
+}
+
+fn check_digit(digits: &[u8]) -> usize {
+    digits
+        .iter()
+        .enumerate()
+        .map(|(i, digit)| (i + 1) * *digit as usize)
+        .sum::<usize>()
+        % 36
+}
+
+// Note: The seeds file uses the same `key = value` lines as the stats file,
+// with one `recent` or `shared` line per seed, newest first.
+#[derive(Debug, Default)]
+pub struct SeedHistory {
+    pub recent: Vec<u64>,
+    pub shared: Vec<u64>,
+}
+
+impl SeedHistory {
+    pub fn load() -> Self {
+        SEEDS_FILE
+            .load()
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    // The whole file, header included, as it goes into a profile bundle.
+    pub fn to_file(&self) -> String {
+        SEEDS_FILE.with_header(&self.to_contents())
+    }
+
+    pub fn from_file(contents: &str) -> Self {
+        Self::parse(&SEEDS_FILE.read(contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut history = Self::default();
+        for line in contents.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                match (key.trim(), u64::from_str_radix(value.trim(), 16)) {
+                    ("recent", Ok(seed)) => history.recent.push(seed),
+                    ("shared", Ok(seed)) => history.shared.push(seed),
+                    _ => {}
+                }
+            }
+        }
+
+        history
+    }
+
+    // Held from loading until saving, see SaveFile::lock.
+    pub fn lock() -> io::Result<SaveLock> {
+        SEEDS_FILE.lock()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        SEEDS_FILE.save(&self.to_contents())
+    }
+
+    fn to_contents(&self) -> String {
+        let mut contents = String::new();
+        for seed in &self.recent {
+            contents.push_str(&format!("recent = {:x}\n", seed));
+        }
+        for seed in &self.shared {
+            contents.push_str(&format!("shared = {:x}\n", seed));
+        }
+        contents
+    }
+
+    // The seeds already here stay first, and the other ones fill up what is left.
+    pub fn merge(&mut self, other: Self) {
+        append_missing(&mut self.recent, other.recent, MAX_RECENT_SEEDS);
+        append_missing(&mut self.shared, other.shared, MAX_SHARED_CODES);
+    }
+
+    pub fn record_played(&mut self, seed: u64) {
+        remember(&mut self.recent, seed, MAX_RECENT_SEEDS);
+    }
+
+    pub fn add_shared(&mut self, seed: u64) {
+        remember(&mut self.shared, seed, MAX_SHARED_CODES);
+    }
+}
+
+fn remember(seeds: &mut Vec<u64>, seed: u64, limit: usize) {
+    seeds.retain(|known| *known != seed);
+    seeds.insert(0, seed);
+    seeds.truncate(limit);
+}
+
+fn append_missing(seeds: &mut Vec<u64>, others: Vec<u64>, limit: usize) {
+    for seed in others {
+        if !seeds.contains(&seed) {
+            seeds.push(seed);
+        }
+    }
+    seeds.truncate(limit);
+}
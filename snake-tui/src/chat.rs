@@ -0,0 +1,235 @@
+use crate::command::Command;
+use crate::input::{InputSource, KeyboardInput};
+use snake_core::direction::Direction;
+use snake_core::state::GameState;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+// Twitch lets anyone read a channel with a `justinfanNNN` nick and no password.
+pub const DEFAULT_NICK: &str = "justinfan4242";
+
+#[derive(Debug, Clone)]
+pub struct ChatServer {
+    pub host: String,
+    pub port: u16,
+    pub channel: String,
+    pub nick: String,
+    // Sent as PASS before logging in, e.g. `oauth:...` on Twitch.
+    pub password: Option<String>,
+}
+
+// A direction somebody voted for in chat.
+#[derive(Debug, Clone)]
+pub struct Vote {
+    pub voter: String,
+    pub direction: Direction,
+}
+
+// Note: Connects and joins the channel, then reads the chat on its own thread
+// so the game never waits for the network. Every message that is a vote is
+// sent down the returned channel, which disconnects when the connection drops.
+pub fn connect(server: &ChatServer) -> io::Result<Receiver<Vote>> {
+    let mut stream = TcpStream::connect((server.host.as_str(), server.port))?;
+    if let Some(password) = &server.password {
+        write!(stream, "PASS {}\r\n", password)?;
+    }
+    write!(stream, "NICK {}\r\n", server.nick)?;
+    write!(stream, "JOIN {}\r\n", server.channel)?;
+
+    let reader = BufReader::new(stream.try_clone()?);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if let Some(token) = line.strip_prefix("PING ") {
+                if write!(stream, "PONG {}\r\n", token).is_err() {
+                    break;
+                }
+                continue;
+            }
+            if let Some(vote) = parse_vote(&line) {
+                if sender.send(vote).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+// Note: Reads a line like `:name!name@host PRIVMSG #channel :left`. Only the
+// first word of the message counts, so `left please` is a vote but `go left` is not.
+fn parse_vote(line: &str) -> Option<Vote> {
+    let rest = line.strip_prefix(':')?;
+    let (prefix, rest) = rest.split_once(' ')?;
+    let (command, rest) = rest.split_once(' ')?;
+    if command != "PRIVMSG" {
+        return None;
+    }
+    let (_, message) = rest.split_once(" :")?;
+    let word = message.split_whitespace().next()?.to_lowercase();
+    let direction = match word.as_str() {
+        "up" | "u" | "w" => Direction::Up,
+        "right" | "r" | "d" => Direction::Right,
+        "down" | "s" => Direction::Down,
+        "left" | "l" | "a" => Direction::Left,
+        _ => return None,
+    };
+    let voter = prefix.split('!').next()?.to_string();
+
+    Some(Vote { voter, direction })
+}
+
+// Note: Votes are counted over a window of ticks, and each chatter has one vote
+// per window: voting again changes it. Windows are measured in ticks, so they
+// line up with the moves the snake makes and stop while the game is paused.
+#[derive(Debug)]
+pub struct VoteWindow {
+    ticks: u64,
+    opened_at: u64,
+    votes: HashMap<String, Direction>,
+}
+
+impl VoteWindow {
+    pub fn new(ticks: u64) -> Self {
+        Self {
+            ticks: ticks.max(1),
+            opened_at: 0,
+            votes: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, vote: Vote) {
+        self.votes.insert(vote.voter, vote.direction);
+    }
+
+    pub fn get_count(&self, direction: Direction) -> usize {
+        self.votes
+            .values()
+            .filter(|vote| **vote == direction)
+            .count()
+    }
+
+    pub fn ticks_left(&self, tick: u64) -> u64 {
+        (self.opened_at + self.ticks).saturating_sub(tick)
+    }
+
+    // A restarted game starts counting ticks again, which ends the window early.
+    pub fn is_due(&self, tick: u64) -> bool {
+        tick < self.opened_at || self.ticks_left(tick) == 0
+    }
+
+    // Note: Opens the next window and returns the direction with the most votes
+    // in this one, or None if nobody voted or the top is a tie.
+    pub fn close(&mut self, tick: u64) -> Option<Direction> {
+        let mut counts: Vec<(usize, Direction)> = Direction::all()
+            .iter()
+            .map(|direction| (self.get_count(*direction), *direction))
+            .collect();
+        counts.sort_by_key(|(count, _)| Reverse(*count));
+        self.opened_at = tick;
+        self.votes.clear();
+
+        match counts.as_slice() {
+            [(0, _), ..] => None,
+            [(first, _), (second, _), ..] if first == second => None,
+            [(_, direction), ..] => Some(*direction),
+            [] => None,
+        }
+    }
+}
+
+// Note: Chat steers the snake, and the keyboard keeps working alongside it so
+// whoever runs the game can still use the menus, pause and quit.
+#[derive(Debug)]
+pub struct ChatInput {
+    keyboard: KeyboardInput,
+    channel: String,
+    votes: Receiver<Vote>,
+    window: VoteWindow,
+    connected: bool,
+    last_winner: Option<Direction>,
+    tick: u64,
+}
+
+impl ChatInput {
+    pub fn new(channel: &str, votes: Receiver<Vote>, window_ticks: u64) -> Self {
+        Self {
+            keyboard: KeyboardInput::new(),
+            channel: channel.to_string(),
+            votes,
+            window: VoteWindow::new(window_ticks),
+            connected: true,
+            last_winner: None,
+            tick: 0,
+        }
+    }
+
+    fn collect_votes(&mut self) {
+        loop {
+            match self.votes.try_recv() {
+                Ok(vote) => self.window.add(vote),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl InputSource for ChatInput {
+    fn next_command(&mut self, state: &GameState, wait_for: Duration) -> Option<Command> {
+        self.collect_votes();
+        self.tick = state.get_ticks();
+        if self.window.is_due(self.tick) {
+            self.last_winner = self.window.close(self.tick);
+            if let Some(direction) = self.last_winner {
+                return Some(Command::Turn(direction));
+            }
+        }
+
+        self.keyboard.next_command(state, wait_for)
+    }
+
+    fn panel(&self) -> Vec<String> {
+        let mut lines = vec![self.channel.clone(), String::new()];
+        for direction in Direction::all().iter() {
+            lines.push(format!(
+                "{:<6}{:>3}",
+                direction_name(*direction),
+                self.window.get_count(*direction)
+            ));
+        }
+        lines.push(String::new());
+        if self.connected {
+            lines.push(format!("Next in {}", self.window.ticks_left(self.tick)));
+        } else {
+            lines.push("Disconnected".to_string());
+        }
+        if let Some(direction) = self.last_winner {
+            lines.push(format!("Last: {}", direction_name(direction)));
+        }
+
+        lines
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "Up",
+        Direction::Right => "Right",
+        Direction::Down => "Down",
+        Direction::Left => "Left",
+    }
+}
@@ -0,0 +1,260 @@
+use crate::challenge;
+use crate::scores::{DateRange, Ranking};
+use clap::{Args, Parser, Subcommand};
+use snake_core::mapgen::{MapGen, Symmetry};
+use snake_core::policy::RivalLevel;
+use std::path::PathBuf;
+
+// Note: Running without a subcommand is the same as `play`, so the play options
+// are also accepted at the top level (e.g. `snake-rs --rival-ai greedy`).
+#[derive(Debug, Parser)]
+#[command(name = "snake-rs", about = "A simple snake game for the terminal")]
+#[command(args_conflicts_with_subcommands = true, disable_version_flag = true)]
+pub struct Cli {
+    /// Print the version and the optional features compiled in
+    #[arg(short = 'V', long)]
+    pub version: bool,
+    /// Run the autopilot without a screen for TICKS ticks on a few board sizes
+    /// and print ticks per second (and allocations per tick with alloc-stats)
+    #[arg(long, value_name = "TICKS", num_args = 0..=1, default_missing_value = "100000")]
+    pub bench_sim: Option<u64>,
+    /// Print the rules a game with these options would be played with (speeds,
+    /// scoring, collisions and modifiers) in FORMAT, which can be json
+    #[arg(long, value_name = "FORMAT", value_parser = ["json"])]
+    pub dump_ruleset: Option<String>,
+    /// Compress the replays, autosaves and interrupted run saved by builds
+    /// without compression
+    #[cfg(feature = "compression")]
+    #[arg(long)]
+    pub compact_replays: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub play: PlayArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Play the game (the default)
+    Play(PlayArgs),
+    /// Watch a recorded replay
+    Replay {
+        /// The replay file to play back
+        file: PathBuf,
+    },
+    /// Show games played, perfect games and the best score
+    Stats,
+    /// Show the best scores and the runs they were set on
+    Highscores {
+        /// Show the hardcore leaderboard, with how each run ended
+        #[arg(long, conflicts_with_all = ["mode", "board", "date", "profile", "rank_by", "page"])]
+        hardcore: bool,
+        /// Only scores whose mode has this part, e.g. chain or level3
+        #[arg(long)]
+        mode: Option<String>,
+        /// Only scores on boards of this size, e.g. 20x20
+        #[arg(long, value_name = "WxH", value_parser = parse_board)]
+        board: Option<(u16, u16)>,
+        /// Only scores set today, this week, month or year (the last 7, 30 or 365 days)
+        #[arg(long, value_parser = parse_date_range)]
+        date: Option<DateRange>,
+        /// Only scores recorded under this player name
+        #[arg(long)]
+        profile: Option<String>,
+        /// Rank by the score or by ranking points, the score scaled to a 20x20 board
+        #[arg(long, value_parser = parse_ranking)]
+        rank_by: Option<Ranking>,
+        /// Which page of ten scores to show
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        page: u16,
+    },
+    /// Host a networked game
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 7878)]
+        port: u16,
+    },
+    /// Run a hotseat tournament: the players take turns playing the same board
+    Tournament {
+        /// Names of the players taking part, in turn order
+        #[arg(required = true, num_args = 2..)]
+        players: Vec<String>,
+        /// Number of rounds, each played on a new board
+        #[arg(long, default_value_t = 3)]
+        rounds: u16,
+    },
+    /// Let a Twitch or IRC chat steer the snake by voting on directions
+    #[cfg(feature = "chat")]
+    Chat(ChatArgs),
+    /// Show or change settings in the config file
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+    /// Turn map files into codes to share and back, or check them for mistakes
+    Map {
+        #[command(subcommand)]
+        action: MapAction,
+    },
+}
+
+#[derive(Debug, Default, Args)]
+pub struct PlayArgs {
+    /// Play against an AI rival snake (random, greedy or astar)
+    #[arg(long, value_name = "LEVEL", value_parser = parse_rival_level)]
+    pub rival_ai: Option<RivalLevel>,
+    /// Play the game with this seed (in hex, as shown on the game over screen)
+    #[arg(long, value_name = "HEX", value_parser = parse_seed, conflicts_with = "code")]
+    pub seed: Option<u64>,
+    /// Play a challenge code shared by someone else; it is kept in the challenge list
+    #[arg(long, value_parser = parse_code)]
+    pub code: Option<u64>,
+    /// Play on the board described in a map file instead of the open 20x20 board
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["map_code", "map_gen"])]
+    pub map: Option<PathBuf>,
+    /// Play on the board a map code shared by someone else holds
+    #[arg(long, value_name = "CODE", conflicts_with = "map_gen")]
+    pub map_code: Option<String>,
+    /// Play on a generated board: maze, rooms or caves
+    #[arg(long, value_name = "KIND", value_parser = parse_map_gen)]
+    pub map_gen: Option<MapGen>,
+    /// Generate the board from this seed (in hex, shown in the map's name) instead of a random one
+    #[arg(long, value_name = "HEX", value_parser = parse_seed, requires = "map_gen")]
+    pub map_seed: Option<u64>,
+    /// Make the generated board symmetric, point or mirror, with paired starts
+    /// for you and the rival
+    #[arg(long, value_name = "KIND", value_parser = parse_symmetry, requires = "map_gen")]
+    pub map_symmetry: Option<Symmetry>,
+    /// Play with the settings and board of a preset: chill, nightmare or one saved
+    /// in the config file (other options still take precedence)
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+    /// Time input, simulation and rendering every frame and print a report on exit
+    #[arg(long)]
+    pub profile: bool,
+    /// Time every turn from its key to the tick that applies it and to the end of
+    /// that tick's rendering, and print a report on exit
+    #[arg(long, hide = true)]
+    pub measure_latency: bool,
+    /// Read the terminal and signals on a tokio runtime instead of a reader thread
+    #[cfg(feature = "async")]
+    #[arg(long)]
+    pub async_input: bool,
+}
+
+// Note: The password, if the server needs one, is read from the
+// SNAKE_CHAT_PASSWORD environment variable so it stays out of the shell history.
+#[cfg(feature = "chat")]
+#[derive(Debug, Args)]
+pub struct ChatArgs {
+    /// The channel to read votes from, e.g. a Twitch user name
+    pub channel: String,
+    /// The IRC server to connect to
+    #[arg(long, default_value = "irc.chat.twitch.tv")]
+    pub server: String,
+    #[arg(long, default_value_t = 6667)]
+    pub port: u16,
+    #[arg(long, default_value = crate::chat::DEFAULT_NICK)]
+    pub nick: String,
+    /// Number of ticks the votes for each move are counted for
+    #[arg(long, value_name = "TICKS", default_value_t = 5)]
+    pub window: u64,
+    #[command(flatten)]
+    pub play: PlayArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Print the path of the config file
+    Path,
+    /// Print the current value of a setting
+    Get { key: String },
+    /// Change a setting, keeping the rest of the file as it is
+    Set { key: String, value: String },
+    /// Bundle the settings, stats, seeds, campaign and hardcore runs into a tar file
+    Export { file: PathBuf },
+    /// Merge a bundle made by `export` into this profile, keeping the best scores of both
+    Import { file: PathBuf },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MapAction {
+    /// Print the code of a map file, to share it without the file
+    Code { file: PathBuf },
+    /// Print the map a code holds, e.g. to save it as a map file
+    Show { code: String },
+    /// Check a map file for cells the snake cannot reach, dead ends and doors without keys
+    Validate {
+        file: PathBuf,
+        /// Repair what can be repaired by walling cells off, and save the map over the file
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Print a generated map (maze, rooms or caves), e.g. to save it as a map file
+    Gen {
+        #[arg(value_parser = parse_map_gen)]
+        kind: MapGen,
+        /// In hex; a random seed is used if left out
+        #[arg(long, value_name = "HEX", value_parser = parse_seed)]
+        seed: Option<u64>,
+        /// Make it symmetric, point or mirror, with paired starts for two snakes
+        #[arg(long, value_name = "KIND", value_parser = parse_symmetry)]
+        symmetry: Option<Symmetry>,
+        #[arg(long, default_value_t = 20)]
+        width: u16,
+        #[arg(long, default_value_t = 20)]
+        height: u16,
+    },
+}
+
+fn parse_rival_level(value: &str) -> Result<RivalLevel, String> {
+    RivalLevel::parse(value).ok_or_else(|| {
+        format!(
+            "unknown rival AI level '{}' (use random, greedy or astar)",
+            value
+        )
+    })
+}
+
+fn parse_map_gen(value: &str) -> Result<MapGen, String> {
+    MapGen::parse(value)
+        .ok_or_else(|| format!("unknown map kind '{}' (use maze, rooms or caves)", value))
+}
+
+fn parse_symmetry(value: &str) -> Result<Symmetry, String> {
+    Symmetry::parse(value)
+        .ok_or_else(|| format!("unknown symmetry '{}' (use point or mirror)", value))
+}
+
+fn parse_board(value: &str) -> Result<(u16, u16), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("'{}' is not a board size like 20x20", value))?;
+    match (width.parse(), height.parse()) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(format!("'{}' is not a board size like 20x20", value)),
+    }
+}
+
+fn parse_date_range(value: &str) -> Result<DateRange, String> {
+    DateRange::parse(value).ok_or_else(|| {
+        format!(
+            "unknown date range '{}' (use all, today, week, month or year)",
+            value
+        )
+    })
+}
+
+fn parse_ranking(value: &str) -> Result<Ranking, String> {
+    Ranking::parse(value)
+        .ok_or_else(|| format!("unknown ranking '{}' (use score or points)", value))
+}
+
+fn parse_seed(value: &str) -> Result<u64, String> {
+    u64::from_str_radix(value, 16).map_err(|error| format!("not a hex seed: {}", error))
+}
+
+fn parse_code(value: &str) -> Result<u64, String> {
+    challenge::decode_code(value)
+        .ok_or_else(|| format!("'{}' is not a valid challenge code", value))
+}
@@ -0,0 +1,27 @@
+use snake_core::direction::Direction;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Command {
+    Quit,
+    ForceQuit,
+    Pause,
+    // Note: Raised by the system (e.g. focus loss) rather than by the player,
+    // so it never resumes a game that is already paused.
+    AutoPause,
+    Restart,
+    // Restart on the seed that is being played instead of a new one.
+    RestartSameSeed,
+    ToggleHelp,
+    ShowRules,
+    ToggleDebug,
+    ToggleDangerZone,
+    ToggleMusic,
+    SpeedUp,
+    SpeedDown,
+    Screenshot,
+    Confirm,
+    Cancel,
+    Turn(Direction),
+    // A turn for one of the players sharing the keyboard, numbered from 0.
+    TurnPlayer(usize, Direction),
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::io;
+#[cfg(feature = "compression")]
+use std::io::{Read, Write};
+use std::path::Path;
+#[cfg(feature = "compression")]
+use std::process;
+
+// Every gzip file starts with these, and no text file the game writes does.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Note: Replays and saves with a run's turns in them grow with every tick, so
+// with the `compression` feature they are written gzipped. Gzip ends with a
+// CRC-32 and the length of what went in, which are checked as the file is read,
+// so a file that was cut short or damaged is refused instead of played back
+// wrong. Plain files are always read too, so nothing has to be converted
+// first, and `--compact-replays` converts the ones there are.
+pub fn read(path: &Path) -> io::Result<String> {
+    decode(&fs::read(path)?)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path.display(), error)))
+}
+
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+pub fn decode(bytes: &[u8]) -> io::Result<String> {
+    if !is_compressed(bytes) {
+        return String::from_utf8(bytes.to_vec())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+    }
+
+    #[cfg(feature = "compression")]
+    {
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(bytes).read_to_string(&mut text)?;
+        Ok(text)
+    }
+    #[cfg(not(feature = "compression"))]
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "compressed, which needs the compression feature",
+    ))
+}
+
+// Gzipped with the `compression` feature, and as it is without it.
+pub fn encode(text: &str) -> Vec<u8> {
+    #[cfg(feature = "compression")]
+    {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+    #[cfg(not(feature = "compression"))]
+    text.as_bytes().to_vec()
+}
+
+// Rewrites a plain file gzipped, and returns its size before and after, or None
+// if it was compressed already. What is written is read back and compared
+// before it replaces the file.
+#[cfg(feature = "compression")]
+pub fn compact(path: &Path) -> io::Result<Option<(u64, u64)>> {
+    let bytes = fs::read(path)?;
+    if is_compressed(&bytes) {
+        return Ok(None);
+    }
+    let text = decode(&bytes)?;
+    let compressed = encode(&text);
+    if decode(&compressed)? != text {
+        return Err(io::Error::other(format!(
+            "{} did not read back the same compressed",
+            path.display()
+        )));
+    }
+
+    // Written next to the file and renamed over it, so it is never seen half written.
+    let temporary = path.with_extension(format!("{}.tmp", process::id()));
+    fs::write(&temporary, &compressed)?;
+    fs::rename(&temporary, path)?;
+
+    Ok(Some((bytes.len() as u64, compressed.len() as u64)))
+}
@@ -0,0 +1,480 @@
+use crate::input::KeyProfile;
+use crate::modifier::Modifiers;
+use crate::paths;
+use crate::preset::Preset;
+use crate::theme::{Skin, Theme};
+use snake_core::policy::RivalLevel;
+use snake_core::rules::{Rules, SpeedModel, SpeedRamp};
+use snake_core::scoring::Scoring;
+use snake_core::spawn::SpawnDistribution;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const CONFIG_FILE_NAME: &str = "config";
+const LEGACY_CONFIG_FILE_NAME: &str = ".snake-rs.conf";
+const HARDCORE_START_SPEED: u16 = 5;
+
+// Note: The config file is a plain list of `key = value` lines.
+// Lines starting with `#` are comments, and unknown keys are ignored
+// so that older binaries can still read files written by newer ones.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub confirm_quit: bool,
+    // Seconds without input after which the game pauses on its own (0 disables).
+    pub idle_pause_secs: u64,
+    // Seconds without input after which the game pauses if the next step is fatal (0 disables).
+    pub idle_danger_pause_secs: u64,
+    // Only place food in cells the snake's head can actually reach.
+    pub food_reachability: bool,
+    // Where food is likely to spawn: uniform, far_from_snake, avoid_corners or near_obstacles.
+    pub food_spawn: SpawnDistribution,
+    // Seconds before uneaten food moves elsewhere, blinking before it does (0 disables).
+    pub food_expiry_secs: u16,
+    // Play with numbered foods that have to be eaten in order.
+    pub chain_food: bool,
+    // Territory mode: seconds to paint as much of the board as possible (0 disables).
+    pub territory_secs: u16,
+    // Lives per run; the snake respawns after a crash while it has one to spare.
+    pub lives: u8,
+    // Seconds between laser sweeps, each telegraphed for a second before firing (0 disables).
+    pub laser_secs: u16,
+    // Boss mode: eat enough food to defeat a boss that chases the snake and fires lasers.
+    pub boss: bool,
+    // Endless mode: foods between new obstacle clusters (0 disables).
+    pub endless_every: u16,
+    // Risk mode: points only count once the snake reaches a safe zone, and a crash loses the rest.
+    pub risk: bool,
+    // Foods on the board at once (chain mode always has its own five).
+    pub food_count: u8,
+    // Clusters of walls scattered over the board when a run starts.
+    pub obstacles: u8,
+    // Speed the run starts at, from 0 to 8 (hardcore starts at 5 or faster).
+    pub start_speed: u16,
+    // How quickly the snake speeds up: off, normal or fast.
+    pub speed_ramp: SpeedRamp,
+    // What the speed ramp counts: score, or length to speed up as the snake grows and slow down as it shrinks.
+    pub speed_model: SpeedModel,
+    // How food is scored: classic, distance, time or combo.
+    pub scoring: Scoring,
+    // Zen mode: wrap-around border, no crashes and no score, just slithering (not recorded in stats).
+    pub zen: bool,
+    // Hardcore mode: no pausing or restarting, a high starting speed and a separate leaderboard.
+    pub hardcore: bool,
+    // Modifiers stacked on every run, e.g. `wrap,fog,mirror,double_speed`, which multiply the score.
+    pub modifiers: Modifiers,
+    // Animate the changes between the menu, the board and the game over screen.
+    pub transitions: bool,
+    // Keep ANSI colors in screenshots instead of saving plain text.
+    pub screenshot_colors: bool,
+    // Start every run in casual (non-scored) play with a fixed tick interval.
+    pub casual: bool,
+    // Preferred tick interval for casual play, updated whenever it is changed in-game.
+    pub casual_interval_ms: u16,
+    // Assist: mark where the next food will spawn.
+    pub clairvoyance: bool,
+    // Assist: show an arrow pointing from the snake's head towards the food.
+    pub hint_arrow: bool,
+    // Assist: number of ticks of the snake's current course to preview (0 disables).
+    pub path_preview: u16,
+    // Assist: shade cells next to walls and the snake's body (toggle in-game with Z).
+    pub danger_zone: bool,
+    // Light up the snake's head while a turn waits for the next tick.
+    pub turn_flash: bool,
+    // Background music, with the audio feature (toggle in-game with M).
+    pub music: bool,
+    // Volume of the music from 0 to 100.
+    pub music_volume: u8,
+    // Colors of the board and of the snake, once unlocked in the campaign.
+    pub theme: Theme,
+    pub skin: Skin,
+    // Colors (256-color palette numbers) the snake goes through from the slowest
+    // speed to the fastest; empty uses the theme's.
+    pub speed_colors: Vec<u8>,
+    // Difficulty of the AI rival snake, if there should be one.
+    pub rival_ai: Option<RivalLevel>,
+    // Score that wins a race in versus mode.
+    pub versus_target: u16,
+    // Keys each player steers with in versus mode: arrows, wasd, ijkl or numpad.
+    pub player_keys: [KeyProfile; 2],
+    // Seconds of play and foods eaten between autosaves, whichever comes first (0 disables either).
+    pub autosave_secs: u16,
+    pub autosave_foods: u16,
+    // The name scores are recorded under, which tells them from the ones imported from other profiles.
+    pub player: String,
+    // Named sets of settings from `preset.<name>` lines, in the order of the file.
+    pub presets: Vec<Preset>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            confirm_quit: true,
+            idle_pause_secs: 0,
+            idle_danger_pause_secs: 0,
+            food_reachability: true,
+            food_spawn: SpawnDistribution::Uniform,
+            food_expiry_secs: 0,
+            chain_food: false,
+            territory_secs: 0,
+            lives: 1,
+            laser_secs: 0,
+            boss: false,
+            endless_every: 0,
+            risk: false,
+            food_count: 1,
+            obstacles: 0,
+            start_speed: 0,
+            speed_ramp: SpeedRamp::Normal,
+            speed_model: SpeedModel::Score,
+            scoring: Scoring::Classic,
+            zen: false,
+            hardcore: false,
+            modifiers: Modifiers::default(),
+            transitions: true,
+            screenshot_colors: false,
+            casual: false,
+            casual_interval_ms: 128,
+            clairvoyance: false,
+            hint_arrow: false,
+            path_preview: 0,
+            danger_zone: false,
+            turn_flash: true,
+            music: true,
+            music_volume: 50,
+            theme: Theme::Classic,
+            skin: Skin::Classic,
+            speed_colors: Vec::new(),
+            rival_ai: None,
+            versus_target: 10,
+            player_keys: [KeyProfile::Wasd, KeyProfile::Arrows],
+            autosave_secs: 30,
+            autosave_foods: 0,
+            player: "player".to_string(),
+            presets: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let mut config = Self::default();
+        if let Some(contents) = Self::read_file() {
+            for line in contents.lines() {
+                config.apply_line(line);
+            }
+        }
+
+        config
+    }
+
+    // Note: Campaign levels and weekly challenges keep the player's assists and
+    // controls but not their rules, and are never casual, so every player plays
+    // them the same way.
+    pub fn without_rules(&self) -> Config {
+        let defaults = Config::default();
+        let mut config = self.clone();
+        config.food_expiry_secs = defaults.food_expiry_secs;
+        config.chain_food = defaults.chain_food;
+        config.territory_secs = defaults.territory_secs;
+        config.lives = defaults.lives;
+        config.laser_secs = defaults.laser_secs;
+        config.boss = defaults.boss;
+        config.endless_every = defaults.endless_every;
+        config.risk = defaults.risk;
+        config.food_count = defaults.food_count;
+        config.obstacles = defaults.obstacles;
+        config.start_speed = defaults.start_speed;
+        config.speed_ramp = defaults.speed_ramp;
+        config.speed_model = defaults.speed_model;
+        config.scoring = defaults.scoring;
+        config.zen = defaults.zen;
+        config.hardcore = defaults.hardcore;
+        config.modifiers = defaults.modifiers;
+        config.rival_ai = defaults.rival_ai;
+        config.casual = false;
+
+        config
+    }
+
+    pub fn rules(&self) -> Rules {
+        let mut rules = Rules {
+            food_reachability: self.food_reachability,
+            rival: self.rival_ai.is_some(),
+            food_expiry_secs: self.food_expiry_secs,
+            chain_food: self.chain_food,
+            territory_secs: self.territory_secs,
+            lives: self.lives,
+            laser_secs: self.laser_secs,
+            boss: self.boss,
+            endless_every: self.endless_every,
+            risk: self.risk,
+            zen: self.zen,
+            start_speed: if self.hardcore {
+                self.start_speed.max(HARDCORE_START_SPEED)
+            } else {
+                self.start_speed
+            },
+            food_spawn: self.food_spawn,
+            food_count: self.food_count,
+            obstacles: self.obstacles,
+            speed_ramp: self.speed_ramp,
+            speed_model: self.speed_model,
+            scoring: self.scoring,
+            ..Rules::default()
+        };
+        self.modifiers.apply(&mut rules);
+
+        rules
+    }
+
+    // The built-in presets followed by the player's own, which replace built-in
+    // ones of the same name.
+    pub fn get_presets(&self) -> Vec<Preset> {
+        let mut presets: Vec<Preset> = Preset::builtin()
+            .into_iter()
+            .filter(|builtin| !self.presets.iter().any(|own| own.name == builtin.name))
+            .collect();
+        presets.extend(self.presets.iter().cloned());
+        presets
+    }
+
+    pub fn find_preset(&self, name: &str) -> Option<Preset> {
+        self.get_presets()
+            .into_iter()
+            .find(|preset| preset.name == name)
+    }
+
+    // Note: Only the line for `key` is rewritten (or appended), so comments and
+    // other settings in the file are left as the player wrote them.
+    pub fn save_value(key: &str, value: &str) -> io::Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no config directory could be determined",
+            )
+        })?;
+        let contents = Self::read_file().unwrap_or_default();
+        let setting = format!("{} = {}", key, value);
+
+        let mut found = false;
+        let mut lines: Vec<String> = contents
+            .lines()
+            .map(|line| match line.split_once('=') {
+                Some((line_key, _)) if !found && line_key.trim() == key => {
+                    found = true;
+                    setting.clone()
+                }
+                _ => line.to_string(),
+            })
+            .collect();
+        if !found {
+            lines.push(setting);
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, lines.join("\n") + "\n")
+    }
+
+    // Note: Every setting with its current value, in the same form as the file.
+    // Presets are not settings of their own and are left out.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("confirm_quit", self.confirm_quit.to_string()),
+            ("idle_pause_secs", self.idle_pause_secs.to_string()),
+            (
+                "idle_danger_pause_secs",
+                self.idle_danger_pause_secs.to_string(),
+            ),
+            ("food_reachability", self.food_reachability.to_string()),
+            ("food_spawn", self.food_spawn.name().to_string()),
+            ("food_expiry_secs", self.food_expiry_secs.to_string()),
+            ("chain_food", self.chain_food.to_string()),
+            ("territory_secs", self.territory_secs.to_string()),
+            ("lives", self.lives.to_string()),
+            ("laser_secs", self.laser_secs.to_string()),
+            ("boss", self.boss.to_string()),
+            ("endless_every", self.endless_every.to_string()),
+            ("risk", self.risk.to_string()),
+            ("food_count", self.food_count.to_string()),
+            ("obstacles", self.obstacles.to_string()),
+            ("start_speed", self.start_speed.to_string()),
+            ("speed_ramp", self.speed_ramp.name().to_string()),
+            ("speed_model", self.speed_model.name().to_string()),
+            ("scoring", self.scoring.name().to_string()),
+            ("zen", self.zen.to_string()),
+            ("hardcore", self.hardcore.to_string()),
+            ("modifiers", self.modifiers.name()),
+            ("transitions", self.transitions.to_string()),
+            ("screenshot_colors", self.screenshot_colors.to_string()),
+            ("casual", self.casual.to_string()),
+            ("casual_interval_ms", self.casual_interval_ms.to_string()),
+            ("clairvoyance", self.clairvoyance.to_string()),
+            ("hint_arrow", self.hint_arrow.to_string()),
+            ("path_preview", self.path_preview.to_string()),
+            ("danger_zone", self.danger_zone.to_string()),
+            ("turn_flash", self.turn_flash.to_string()),
+            ("music", self.music.to_string()),
+            ("music_volume", self.music_volume.to_string()),
+            ("theme", self.theme.name().to_string()),
+            ("skin", self.skin.name().to_string()),
+            (
+                "speed_colors",
+                if self.speed_colors.is_empty() {
+                    "theme".to_string()
+                } else {
+                    let colors: Vec<String> = self
+                        .speed_colors
+                        .iter()
+                        .map(|color| color.to_string())
+                        .collect();
+                    colors.join(",")
+                },
+            ),
+            (
+                "rival_ai",
+                self.rival_ai
+                    .map(|level| level.name().to_string())
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            ("versus_target", self.versus_target.to_string()),
+            ("p1_keys", self.player_keys[0].name().to_string()),
+            ("p2_keys", self.player_keys[1].name().to_string()),
+            ("autosave_secs", self.autosave_secs.to_string()),
+            ("autosave_foods", self.autosave_foods.to_string()),
+            ("player", self.player.clone()),
+        ]
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+    }
+
+    // A config file in the old place is read until the first change saves it in the new one.
+    pub fn read_file() -> Option<String> {
+        fs::read_to_string(Self::path()?)
+            .ok()
+            .or_else(|| fs::read_to_string(paths::legacy_path(LEGACY_CONFIG_FILE_NAME)?).ok())
+    }
+
+    fn apply_line(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let (key, value) = (key.trim(), value.trim());
+            match key.strip_prefix("preset.") {
+                Some(name) if Preset::is_valid_name(name) => {
+                    self.presets.retain(|preset| preset.name != name);
+                    self.presets.push(Preset::parse(name, value));
+                }
+                Some(_) => {}
+                None => self.apply_setting(key, value),
+            }
+        }
+    }
+
+    pub fn apply_setting(&mut self, key: &str, value: &str) {
+        match key {
+            "confirm_quit" => set_bool(&mut self.confirm_quit, value),
+            "idle_pause_secs" => set_u64(&mut self.idle_pause_secs, value),
+            "idle_danger_pause_secs" => set_u64(&mut self.idle_danger_pause_secs, value),
+            "food_reachability" => set_bool(&mut self.food_reachability, value),
+            "food_spawn" => {
+                self.food_spawn = SpawnDistribution::parse(value).unwrap_or(self.food_spawn)
+            }
+            "food_expiry_secs" => set_u16(&mut self.food_expiry_secs, value),
+            "chain_food" => set_bool(&mut self.chain_food, value),
+            "territory_secs" => set_u16(&mut self.territory_secs, value),
+            "lives" => set_u8(&mut self.lives, value),
+            "laser_secs" => set_u16(&mut self.laser_secs, value),
+            "boss" => set_bool(&mut self.boss, value),
+            "endless_every" => set_u16(&mut self.endless_every, value),
+            "risk" => set_bool(&mut self.risk, value),
+            "food_count" => set_u8(&mut self.food_count, value),
+            "obstacles" => set_u8(&mut self.obstacles, value),
+            "start_speed" => set_u16(&mut self.start_speed, value),
+            "speed_ramp" => self.speed_ramp = SpeedRamp::parse(value).unwrap_or(self.speed_ramp),
+            "speed_model" => {
+                self.speed_model = SpeedModel::parse(value).unwrap_or(self.speed_model)
+            }
+            "scoring" => self.scoring = Scoring::parse(value).unwrap_or(self.scoring),
+            "zen" => set_bool(&mut self.zen, value),
+            "hardcore" => set_bool(&mut self.hardcore, value),
+            "modifiers" => self.modifiers = Modifiers::parse(value),
+            "transitions" => set_bool(&mut self.transitions, value),
+            "screenshot_colors" => set_bool(&mut self.screenshot_colors, value),
+            "casual" => set_bool(&mut self.casual, value),
+            "casual_interval_ms" => set_u16(&mut self.casual_interval_ms, value),
+            "clairvoyance" => set_bool(&mut self.clairvoyance, value),
+            "hint_arrow" => set_bool(&mut self.hint_arrow, value),
+            "path_preview" => set_u16(&mut self.path_preview, value),
+            "danger_zone" => set_bool(&mut self.danger_zone, value),
+            "turn_flash" => set_bool(&mut self.turn_flash, value),
+            "music" => set_bool(&mut self.music, value),
+            "music_volume" => set_u8(&mut self.music_volume, value),
+            "theme" => self.theme = Theme::parse(value).unwrap_or(self.theme),
+            "skin" => self.skin = Skin::parse(value).unwrap_or(self.skin),
+            "speed_colors" => set_colors(&mut self.speed_colors, value),
+            "rival_ai" => self.rival_ai = RivalLevel::parse(value),
+            "versus_target" => set_u16(&mut self.versus_target, value),
+            "p1_keys" | "p2_keys" => {
+                let player = if key == "p1_keys" { 0 } else { 1 };
+                if let Some(profile) = KeyProfile::parse(value) {
+                    self.player_keys[player] = profile;
+                }
+            }
+            "autosave_secs" => set_u16(&mut self.autosave_secs, value),
+            "autosave_foods" => set_u16(&mut self.autosave_foods, value),
+            // Scores keep it in a single field, so it cannot have spaces in it.
+            "player" if !value.is_empty() && !value.contains(char::is_whitespace) => {
+                self.player = value.to_string()
+            }
+            _ => {}
+        }
+    }
+}
+
+fn set_bool(field: &mut bool, value: &str) {
+    match value {
+        "true" | "yes" | "on" | "1" => *field = true,
+        "false" | "no" | "off" | "0" => *field = false,
+        _ => {}
+    }
+}
+
+// A comma separated list of palette numbers, or `theme` for the theme's own.
+fn set_colors(field: &mut Vec<u8>, value: &str) {
+    if value == "theme" {
+        field.clear();
+        return;
+    }
+
+    let colors: Result<Vec<u8>, _> = value.split(',').map(|color| color.trim().parse()).collect();
+    match colors {
+        Ok(colors) if !colors.is_empty() => *field = colors,
+        _ => {}
+    }
+}
+
+fn set_u8(field: &mut u8, value: &str) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
+
+fn set_u16(field: &mut u16, value: &str) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
+
+fn set_u64(field: &mut u64, value: &str) {
+    if let Ok(parsed) = value.parse() {
+        *field = parsed;
+    }
+}
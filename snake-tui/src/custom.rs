@@ -0,0 +1,189 @@
+use crate::config::Config;
+use crate::menu::Menu;
+use crate::modifier::Modifier;
+use crate::preset::Preset;
+use snake_core::rules::SpeedRamp;
+use snake_core::state::MAX_SPEED;
+
+const MAX_FOOD_COUNT: u8 = 9;
+const MAX_OBSTACLES: u8 = 20;
+
+// One line of the custom game screen. Wrap-around is the wall mode rather than
+// a modifier of its own there.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Setting {
+    Width,
+    Height,
+    Walls,
+    Food,
+    Obstacles,
+    StartSpeed,
+    SpeedRamp,
+    Modifier(Modifier),
+}
+
+impl Setting {
+    pub fn all() -> Vec<Setting> {
+        let mut settings = vec![
+            Setting::Width,
+            Setting::Height,
+            Setting::Walls,
+            Setting::Food,
+            Setting::Obstacles,
+            Setting::StartSpeed,
+            Setting::SpeedRamp,
+        ];
+        settings.extend(
+            Modifier::all()
+                .iter()
+                .filter(|modifier| **modifier != Modifier::WrapAround)
+                .map(|modifier| Setting::Modifier(*modifier)),
+        );
+
+        settings
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Setting::Width => "Width",
+            Setting::Height => "Height",
+            Setting::Walls => "Walls",
+            Setting::Food => "Food",
+            Setting::Obstacles => "Obstacles",
+            Setting::StartSpeed => "Start speed",
+            Setting::SpeedRamp => "Speed ramp",
+            // Short enough for the smallest boards.
+            Setting::Modifier(Modifier::MirrorControls) => "Mirror",
+            Setting::Modifier(modifier) => modifier.label(),
+        }
+    }
+}
+
+// Note: What the custom game screen edits: a copy of the player's config, whose
+// other settings carry over, and the size of the empty board it is played on,
+// kept between the smallest board and the largest one the terminal fits.
+#[derive(Debug, Clone)]
+pub struct CustomGame {
+    pub config: Config,
+    pub width: u16,
+    pub height: u16,
+    min_size: (u16, u16),
+    max_size: (u16, u16),
+}
+
+impl CustomGame {
+    pub fn new(
+        config: Config,
+        size: (u16, u16),
+        min_size: (u16, u16),
+        max_size: (u16, u16),
+    ) -> Self {
+        let mut game = Self {
+            config,
+            width: size.0,
+            height: size.1,
+            min_size,
+            max_size,
+        };
+        game.config.food_count = game.config.food_count.clamp(1, MAX_FOOD_COUNT);
+        game.config.obstacles = game.config.obstacles.min(MAX_OBSTACLES);
+        game.config.start_speed = game.config.start_speed.min(MAX_SPEED);
+        game.width = game.width.clamp(min_size.0, max_size.0.max(min_size.0));
+        game.height = game.height.clamp(min_size.1, max_size.1.max(min_size.1));
+        game
+    }
+
+    pub fn value(&self, setting: Setting) -> String {
+        let config = &self.config;
+        match setting {
+            Setting::Width => self.width.to_string(),
+            Setting::Height => self.height.to_string(),
+            Setting::Walls if config.modifiers.contains(Modifier::WrapAround) => "wrap".to_string(),
+            Setting::Walls => "solid".to_string(),
+            Setting::Food => config.food_count.to_string(),
+            Setting::Obstacles => config.obstacles.to_string(),
+            Setting::StartSpeed => config.start_speed.to_string(),
+            Setting::SpeedRamp => config.speed_ramp.name().to_string(),
+            Setting::Modifier(modifier) if config.modifiers.contains(modifier) => "on".to_string(),
+            Setting::Modifier(_) => "off".to_string(),
+        }
+    }
+
+    // Note: Numbers stop at their limits, while choices go round.
+    pub fn adjust(&mut self, setting: Setting, forward: bool) {
+        let config = &mut self.config;
+        match setting {
+            Setting::Width => {
+                self.width = step(self.width, forward, self.min_size.0, self.max_size.0)
+            }
+            Setting::Height => {
+                self.height = step(self.height, forward, self.min_size.1, self.max_size.1)
+            }
+            Setting::Walls => config.modifiers.toggle(Modifier::WrapAround),
+            Setting::Food => {
+                config.food_count =
+                    step(config.food_count as u16, forward, 1, MAX_FOOD_COUNT as u16) as u8
+            }
+            Setting::Obstacles => {
+                config.obstacles =
+                    step(config.obstacles as u16, forward, 0, MAX_OBSTACLES as u16) as u8
+            }
+            Setting::StartSpeed => {
+                config.start_speed = step(config.start_speed, forward, 0, MAX_SPEED)
+            }
+            Setting::SpeedRamp => {
+                let ramps = SpeedRamp::all();
+                let current = ramps
+                    .iter()
+                    .position(|ramp| *ramp == config.speed_ramp)
+                    .unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % ramps.len()
+                } else {
+                    (current + ramps.len() - 1) % ramps.len()
+                };
+                config.speed_ramp = ramps[next];
+            }
+            Setting::Modifier(modifier) => config.modifiers.toggle(modifier),
+        }
+    }
+
+    // The screen: a line for every one of Setting::all, followed by Start, Save
+    // as preset and Back.
+    pub fn menu(&self) -> Menu {
+        let mut items: Vec<String> = Setting::all()
+            .iter()
+            .map(|setting| format!("{}: {}", setting.label(), self.value(*setting)))
+            .collect();
+        items.extend(
+            ["Start", "Save as preset", "Back"]
+                .iter()
+                .map(|item| item.to_string()),
+        );
+        let mut menu = Menu::new("Custom game", items);
+        menu.set_hint("A/D: change");
+
+        menu
+    }
+
+    pub fn to_preset(&self, name: &str) -> Preset {
+        let config = &self.config;
+        let mut preset = Preset::new(name);
+        preset.set("board", &format!("{}x{}", self.width, self.height));
+        preset.set("food_count", &config.food_count.to_string());
+        preset.set("obstacles", &config.obstacles.to_string());
+        preset.set("start_speed", &config.start_speed.to_string());
+        preset.set("speed_ramp", config.speed_ramp.name());
+        preset.set("modifiers", &config.modifiers.name());
+
+        preset
+    }
+}
+
+fn step(value: u16, forward: bool, min: u16, max: u16) -> u16 {
+    if forward {
+        value.saturating_add(1).min(max.max(min))
+    } else {
+        value.saturating_sub(1).max(min)
+    }
+}
@@ -0,0 +1,179 @@
+use crate::savefile::{self, SaveFile};
+use snake_core::direction::Direction;
+use std::fs;
+use std::io;
+#[cfg(feature = "compression")]
+use std::path::PathBuf;
+
+const EMERGENCY_FILE: SaveFile = SaveFile::new("interrupted", &[savefile::unchanged]).compressed();
+
+// Something the player did that changed how the run went, besides the ticks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Event {
+    Turn(Direction),
+    // A casual speed picked during the run, in milliseconds per tick.
+    Interval(u16),
+    // An arrow key pressed, with how many milliseconds after the last tick. Only
+    // replays show these; the turns they led to are recorded on their own.
+    Key(Direction, u16),
+}
+
+// Note: A run is replayed exactly from its seed, its settings and its map, so
+// rather than the whole state the emergency save keeps those and what the
+// player did on which tick. Resuming plays the ticks again up to the point the
+// run was left at. The file is plain `key = value` lines:
+//
+//   seed = 1f2e
+//   steps = 120
+//   turn = 14 up
+//   interval = 60 84
+//   key = 14 up 35
+//   config.lives = 3
+//   map = ..........
+#[derive(Debug, Clone)]
+pub struct Journal {
+    seed: u64,
+    map: String,
+    settings: Vec<(String, String)>,
+    steps: u64,
+    // Each event with the number of steps taken before it.
+    events: Vec<(u64, Event)>,
+}
+
+impl Journal {
+    pub fn new(seed: u64, map: String, settings: Vec<(&'static str, String)>) -> Self {
+        Self {
+            seed,
+            map,
+            settings: settings
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+            steps: 0,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_map(&self) -> &str {
+        &self.map
+    }
+
+    pub fn get_settings(&self) -> &[(String, String)] {
+        &self.settings
+    }
+
+    pub fn get_steps(&self) -> u64 {
+        self.steps
+    }
+
+    // The events that came before the given step, in the order they happened.
+    pub fn events_before(&self, step: u64) -> impl Iterator<Item = Event> + '_ {
+        self.events
+            .iter()
+            .filter(move |(at, _)| *at == step)
+            .map(|(_, event)| *event)
+    }
+
+    pub fn record(&mut self, event: Event) {
+        self.events.push((self.steps, event));
+    }
+
+    pub fn stepped(&mut self) {
+        self.steps += 1;
+    }
+
+    // Returns None when there is no interrupted run, or it could not be read.
+    pub fn load() -> Option<Self> {
+        Self::parse(&EMERGENCY_FILE.load()?)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        EMERGENCY_FILE.save(&self.to_contents())
+    }
+
+    #[cfg(feature = "compression")]
+    pub fn file_path() -> Option<PathBuf> {
+        EMERGENCY_FILE.path()
+    }
+
+    // Once the player has been offered the run, it is gone either way.
+    pub fn discard() {
+        if let Some(path) = EMERGENCY_FILE.path() {
+            fs::remove_file(path).ok();
+        }
+    }
+
+    pub fn parse(contents: &str) -> Option<Self> {
+        let mut journal = Self::new(0, String::new(), Vec::new());
+        let mut seed = None;
+        for line in contents.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue,
+            };
+            match key {
+                "seed" => seed = u64::from_str_radix(value, 16).ok(),
+                "steps" => journal.steps = value.parse().ok()?,
+                "turn" => {
+                    let (at, name) = value.split_once(' ')?;
+                    let direction = Direction::parse(name)?;
+                    journal
+                        .events
+                        .push((at.parse().ok()?, Event::Turn(direction)));
+                }
+                "interval" => {
+                    let (at, millis) = value.split_once(' ')?;
+                    journal
+                        .events
+                        .push((at.parse().ok()?, Event::Interval(millis.parse().ok()?)));
+                }
+                "key" => {
+                    let mut fields = value.split_whitespace();
+                    let at = fields.next()?.parse().ok()?;
+                    let direction = Direction::parse(fields.next()?)?;
+                    let millis = fields.next()?.parse().ok()?;
+                    journal.events.push((at, Event::Key(direction, millis)));
+                }
+                "map" => {
+                    journal.map.push_str(value);
+                    journal.map.push('\n');
+                }
+                _ => {
+                    if let Some(setting) = key.strip_prefix("config.") {
+                        journal
+                            .settings
+                            .push((setting.to_string(), value.to_string()));
+                    }
+                }
+            }
+        }
+        journal.seed = seed?;
+
+        Some(journal)
+    }
+
+    pub fn to_contents(&self) -> String {
+        let mut contents = format!("seed = {:x}\nsteps = {}\n", self.seed, self.steps);
+        for (at, event) in &self.events {
+            contents.push_str(&match event {
+                Event::Turn(direction) => format!("turn = {} {}\n", at, direction.name()),
+                Event::Interval(millis) => format!("interval = {} {}\n", at, millis),
+                Event::Key(direction, millis) => {
+                    format!("key = {} {} {}\n", at, direction.name(), millis)
+                }
+            });
+        }
+        for (key, value) in &self.settings {
+            contents.push_str(&format!("config.{} = {}\n", key, value));
+        }
+        for line in self.map.lines() {
+            contents.push_str(&format!("map = {}\n", line));
+        }
+
+        contents
+    }
+}
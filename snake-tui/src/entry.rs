@@ -0,0 +1,142 @@
+use snake_core::direction::Direction;
+
+// What letters of a name can be, in the order they come up. A blank is dropped.
+pub const NAME_CHARACTERS: &str = "abcdefghijklmnopqrstuvwxyz0123456789-_ ";
+// The same for replay notes, which keep their blanks.
+pub const NOTE_CHARACTERS: &str = "abcdefghijklmnopqrstuvwxyz0123456789 .,!?'-";
+
+// Note: Text is entered a letter at a time like on an arcade high score
+// table, so any input source can enter it: up and down change the letter
+// under the cursor, and right and left move the cursor, adding a letter at
+// the end until there are `max_length` of them.
+#[derive(Debug)]
+pub struct TextEntry {
+    characters: Vec<char>,
+    // Positions in `characters`, one for every letter of the text.
+    letters: Vec<usize>,
+    cursor: usize,
+    max_length: usize,
+}
+
+impl TextEntry {
+    // Letters of `initial` that are not among `characters` are left out.
+    pub fn new(initial: &str, characters: &str, max_length: usize) -> Self {
+        let characters: Vec<char> = characters.chars().collect();
+        let mut letters: Vec<usize> = initial
+            .chars()
+            .filter_map(|letter| characters.iter().position(|known| *known == letter))
+            .collect();
+        if letters.is_empty() {
+            letters.push(0);
+        }
+
+        Self {
+            characters,
+            cursor: letters.len() - 1,
+            letters,
+            max_length,
+        }
+    }
+
+    pub fn get_text(&self) -> String {
+        self.letters.iter().map(|i| self.characters[*i]).collect()
+    }
+
+    pub fn press(&mut self, direction: Direction) {
+        let count = self.characters.len();
+        match direction {
+            Direction::Up => {
+                self.letters[self.cursor] = (self.letters[self.cursor] + count - 1) % count
+            }
+            Direction::Down => self.letters[self.cursor] = (self.letters[self.cursor] + 1) % count,
+            Direction::Right => {
+                if self.cursor + 1 == self.letters.len() && self.letters.len() < self.max_length {
+                    self.letters.push(0);
+                }
+                self.cursor = (self.cursor + 1).min(self.letters.len() - 1);
+            }
+            Direction::Left => self.cursor = self.cursor.saturating_sub(1),
+        }
+    }
+
+    pub fn lines(&self, title: &str) -> Vec<String> {
+        let marker: String = (0..self.letters.len())
+            .map(|i| if i == self.cursor { '^' } else { ' ' })
+            .collect();
+        vec![
+            title.to_string(),
+            String::new(),
+            self.get_text(),
+            marker,
+            String::new(),
+            "W/S: letter".to_string(),
+            "A/D: move".to_string(),
+            "Enter: done".to_string(),
+        ]
+    }
+}
+
+// Note: Numbers are entered a digit at a time, as many digits as `max` has,
+// so any input source can enter one: up and down change the digit under the
+// cursor, and right and left move the cursor.
+#[derive(Debug)]
+pub struct NumberEntry {
+    digits: Vec<u8>,
+    cursor: usize,
+    max: u64,
+}
+
+impl NumberEntry {
+    pub fn new(value: u64, max: u64) -> Self {
+        let width = max.to_string().len();
+        let digits: Vec<u8> = format!("{:0width$}", value, width = width)
+            .bytes()
+            .map(|digit| digit - b'0')
+            .collect();
+
+        Self {
+            cursor: digits.len() - 1,
+            digits,
+            max,
+        }
+    }
+
+    // At most `max`.
+    pub fn get_value(&self) -> u64 {
+        self.get_text().parse::<u64>().unwrap().min(self.max)
+    }
+
+    pub fn press(&mut self, direction: Direction) {
+        let digit = &mut self.digits[self.cursor];
+        match direction {
+            Direction::Up => *digit = (*digit + 1) % 10,
+            Direction::Down => *digit = (*digit + 9) % 10,
+            Direction::Right => self.cursor = (self.cursor + 1).min(self.digits.len() - 1),
+            Direction::Left => self.cursor = self.cursor.saturating_sub(1),
+        }
+    }
+
+    pub fn lines(&self, title: &str) -> Vec<String> {
+        let marker: String = (0..self.digits.len())
+            .map(|i| if i == self.cursor { '^' } else { ' ' })
+            .collect();
+        vec![
+            title.to_string(),
+            format!("0 to {}", self.max),
+            String::new(),
+            self.get_text(),
+            marker,
+            String::new(),
+            "W/S: digit".to_string(),
+            "A/D: move".to_string(),
+            "Enter: done".to_string(),
+        ]
+    }
+
+    fn get_text(&self) -> String {
+        self.digits
+            .iter()
+            .map(|digit| (b'0' + digit) as char)
+            .collect()
+    }
+}
@@ -0,0 +1,168 @@
+use crossterm::style::{Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FrameCell {
+    pub glyph: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl FrameCell {
+    pub fn blank() -> Self {
+        Self {
+            glyph: ' ',
+            fg: Color::Reset,
+            bg: Color::Reset,
+        }
+    }
+}
+
+// Note: A part of the frame that drawing is confined to, with coordinates
+// relative to its top left corner, so the same drawing code can put several
+// boards side by side. Anything drawn outside of it is cut off.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Viewport {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+// Note: The frame is what will be on screen once it is presented. Drawing always
+// goes into the frame first, which lets the renderer only write the cells that
+// changed and makes the current picture readable (e.g. for screenshots).
+#[derive(Debug, Eq, PartialEq)]
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<FrameCell>,
+    // Where drawing goes, or None for the whole frame. Reading always uses
+    // coordinates on the whole frame.
+    viewport: Option<Viewport>,
+}
+
+// Note: Copying into a frame of the same size reuses its cells, since a copy
+// of the frame is handed to the renderer's thread every time it is presented.
+impl Clone for Frame {
+    fn clone(&self) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+            viewport: self.viewport,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.width = source.width;
+        self.height = source.height;
+        self.cells.clone_from(&source.cells);
+        self.viewport = source.viewport;
+    }
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![FrameCell::blank(); width as usize * height as usize],
+            viewport: None,
+        }
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        self.viewport = viewport;
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = FrameCell::blank();
+        }
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> Option<&FrameCell> {
+        self.index(x, y).map(|index| &self.cells[index])
+    }
+
+    pub fn put(&mut self, x: u16, y: u16, glyph: char, fg: Color) {
+        if let Some(index) = self.draw_index(x, y) {
+            self.cells[index].glyph = glyph;
+            self.cells[index].fg = fg;
+        }
+    }
+
+    pub fn set(&mut self, x: u16, y: u16, cell: FrameCell) {
+        if let Some(index) = self.draw_index(x, y) {
+            self.cells[index] = cell;
+        }
+    }
+
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, fg: Color) {
+        for (i, glyph) in text.chars().enumerate() {
+            self.put(x + i as u16, y, glyph, fg);
+        }
+    }
+
+    pub fn set_background(&mut self, x: u16, y: u16, bg: Color) {
+        if let Some(index) = self.draw_index(x, y) {
+            self.cells[index].bg = bg;
+        }
+    }
+
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::with_capacity(self.cells.len() + self.height as usize);
+        for row in self.cells.chunks(self.width as usize) {
+            let line: String = row.iter().map(|cell| cell.glyph).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        text
+    }
+
+    pub fn to_ansi_text(&self) -> String {
+        let mut text = String::new();
+        for row in self.cells.chunks(self.width as usize) {
+            let mut last: Option<(Color, Color)> = None;
+            for cell in row {
+                if last != Some((cell.fg, cell.bg)) {
+                    text.push_str(&SetForegroundColor(cell.fg).to_string());
+                    text.push_str(&SetBackgroundColor(cell.bg).to_string());
+                    last = Some((cell.fg, cell.bg));
+                }
+                text.push(cell.glyph);
+            }
+            text.push_str(&ResetColor.to_string());
+            text.push('\n');
+        }
+
+        text
+    }
+
+    fn draw_index(&self, x: u16, y: u16) -> Option<usize> {
+        match self.viewport {
+            Some(viewport) if x < viewport.width && y < viewport.height => {
+                self.index(viewport.x + x, viewport.y + y)
+            }
+            Some(_) => None,
+            None => self.index(x, y),
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+}
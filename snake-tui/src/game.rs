@@ -0,0 +1,3005 @@
+use crate::about::{self, RunInfo};
+use crate::autosave::{Autosave, AutosaveTimer};
+use crate::campaign::{Mode, Progress, Unlock, LEVELS};
+use crate::challenge::{self, SeedHistory};
+use crate::command::Command;
+use crate::config::Config;
+use crate::custom::{CustomGame, Setting};
+use crate::emergency::{Event, Journal};
+use crate::entry::{NumberEntry, TextEntry, NAME_CHARACTERS, NOTE_CHARACTERS};
+use crate::hardcore::{Death, Leaderboard};
+use crate::input::{self, IdleTimer, InputSource, KeyProfile, KeyboardInput, TurnQueue};
+use crate::latency::LatencyMeter;
+use crate::loading::Startup;
+use crate::menu::{wrap, Menu};
+use crate::modifier::{Modifier, Multiplier};
+#[cfg(feature = "audio")]
+use crate::music::Music;
+use crate::pacer::TickPacer;
+use crate::preset::{self, Preset};
+use crate::profiler::{Phase, Profiler};
+use crate::renderer::Renderer;
+use crate::replay::{self, Control, Playback, Replay, ReplayBrowser, Run};
+use crate::scores::{ScoreBrowser, ScoreEntry, ScoreHistory};
+use crate::stats::Stats;
+use crate::theme::{self, Theme};
+use crate::tournament::Tournament;
+use crate::transition::Transition;
+use crate::versus::{Layout, Race, Racer, PLAYER_NAMES};
+use crate::weekly::{Weekly, WeeklyBests};
+use crossterm::style::Color;
+use crossterm::terminal::size;
+use rand::Rng;
+use snake_core::boss::BOSS_HITS;
+use snake_core::direction::Direction;
+use snake_core::grid::{Cell, Owner, Tile};
+use snake_core::hazard::LaserPhase;
+#[cfg(debug_assertions)]
+use snake_core::invariants::{self, Violation};
+use snake_core::map::Map;
+use snake_core::policy::Policy;
+use snake_core::rules::{Rules, SpeedModel, SpeedRamp};
+use snake_core::segment::{Segment, SegmentKind};
+use snake_core::state::{self, GameState, StepOutcome};
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::fs;
+use std::io::{self, Stdout};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const CELEBRATION_FRAMES: usize = 30;
+const CELEBRATION_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+const DIALOG_POLL_INTERVAL: Duration = Duration::from_secs(60);
+const NOTICE_DURATION: Duration = Duration::from_secs(2);
+const LOADING_POLL_INTERVAL: Duration = Duration::from_millis(15);
+const MAP_MESSAGE_DURATION: Duration = Duration::from_secs(3);
+const CASUAL_INTERVAL_STEP: u16 = 16;
+const FOOD_EXPIRY_WARNING: Duration = Duration::from_secs(3);
+// Frames per phase of the food's pulse, which is faster once it is about to expire.
+const FOOD_PULSE_FRAMES: u64 = 4;
+const EXPIRING_FOOD_PULSE_FRAMES: u64 = 2;
+const RAINBOW: [Color; 6] = [
+    Color::Red,
+    Color::Yellow,
+    Color::Green,
+    Color::Cyan,
+    Color::Blue,
+    Color::Magenta,
+];
+const MAIN_MENU_ITEMS: [&str; 14] = [
+    "Play",
+    "Load autosave",
+    "Versus",
+    "Custom",
+    "Presets",
+    "Campaign",
+    "Modes",
+    "Modifiers",
+    "Challenges",
+    "High scores",
+    "Replays",
+    "Rules",
+    "About",
+    "Quit",
+];
+const DANGER_ZONE_COLOR: Color = Color::AnsiValue(236);
+const TURN_FLASH_COLOR: Color = Color::AnsiValue(240);
+const MUD_COLOR: Color = Color::AnsiValue(58);
+const ICE_COLOR: Color = Color::AnsiValue(24);
+const SAFE_ZONE_COLOR: Color = Color::AnsiValue(22);
+const KEY_COLORS: [Color; 6] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Red,
+    Color::Green,
+    Color::Blue,
+    Color::Magenta,
+];
+const PLAYER_TERRITORY_COLOR: Color = Color::AnsiValue(22);
+const RIVAL_TERRITORY_COLOR: Color = Color::AnsiValue(53);
+// Soft colors the snake slowly cycles through in zen mode.
+const ZEN_COLORS: [Color; 8] = [
+    Color::AnsiValue(114),
+    Color::AnsiValue(115),
+    Color::AnsiValue(116),
+    Color::AnsiValue(117),
+    Color::AnsiValue(153),
+    Color::AnsiValue(183),
+    Color::AnsiValue(182),
+    Color::AnsiValue(186),
+];
+const ZEN_COLOR_INTERVAL: Duration = Duration::from_millis(1500);
+const LASER_WARNING_COLOR: Color = Color::AnsiValue(52);
+const LASER_BEAM_COLOR: Color = Color::AnsiValue(196);
+const BOSS_COLOR: Color = Color::Magenta;
+// How far around the snake's head the board can be seen with the fog modifier.
+const FOG_RADIUS: i32 = 4;
+// Room for the input source's panel to the right of the board, gap included.
+const PANEL_WIDTH: u16 = 18;
+// The smallest board with room for the snake and its status bar ("Score: 0").
+const MIN_BOARD_WIDTH: u16 = 8;
+const MIN_BOARD_HEIGHT: u16 = 3;
+// The border around the board, and the score and status lines below it.
+const FRAME_EXTRA_WIDTH: u16 = 2;
+const FRAME_EXTRA_HEIGHT: u16 = 4;
+const HELP_LINES: [&str; 12] = [
+    "WASD/arrows: turn",
+    "P: pause",
+    "I: rules",
+    "R: restart (shift: same seed)",
+    "+/-: casual speed",
+    "Z: danger zones",
+    "M: music",
+    "F3: debug info",
+    "F12: screenshot",
+    "H: close help",
+    "Q/Esc: quit",
+    "Ctrl+C: force quit",
+];
+
+// How a run is played again straight from the game over screen.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Rerun {
+    NewSeed,
+    SameSeed,
+}
+
+// What was picked from the Challenges menu.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ChallengeChoice {
+    Seed(u64),
+    Weekly,
+}
+
+// Note: What the game loop should do after a command has been dispatched.
+enum Flow {
+    Continue,
+    // Something shown changed, without holding up the tick.
+    Render,
+    // The screen changed and the current tick should start over.
+    Redraw,
+    Quit,
+}
+
+// Note: Why a board cannot be played. There is no scrolling, so the whole
+// board with its border and status bar has to fit in the terminal.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BoardError {
+    NoCells {
+        width: u16,
+        height: u16,
+    },
+    TooSmall {
+        width: u16,
+        height: u16,
+    },
+    TooLarge {
+        width: u16,
+        height: u16,
+        columns: u16,
+        rows: u16,
+    },
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCells { width, height } => {
+                write!(f, "a {}x{} board has no cells to play on", width, height)
+            }
+            Self::TooSmall { width, height } => write!(
+                f,
+                "a {}x{} board is too small, boards need to be at least {}x{}",
+                width, height, MIN_BOARD_WIDTH, MIN_BOARD_HEIGHT
+            ),
+            Self::TooLarge {
+                width,
+                height,
+                columns,
+                rows,
+            } => write!(
+                f,
+                "a {}x{} board needs a terminal of at least {}x{}, but this one is {}x{}",
+                width,
+                height,
+                width + FRAME_EXTRA_WIDTH,
+                height + FRAME_EXTRA_HEIGHT,
+                columns,
+                rows
+            ),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+#[derive(Debug)]
+pub struct Game {
+    renderer: Renderer,
+    config: Config,
+    original_terminal_size: (u16, u16),
+    map: Map,
+    width: u16,
+    height: u16,
+    state: GameState,
+    input: Box<dyn InputSource>,
+    rival_policy: Option<Box<dyn Policy>>,
+    turns: TurnQueue,
+    idle: IdleTimer,
+    pacer: TickPacer,
+    // Casual runs (with a manually picked speed) are not recorded in stats.
+    casual: bool,
+    show_help: bool,
+    show_debug: bool,
+    show_danger_zone: bool,
+    notice: Option<(String, Instant)>,
+    // Where the replay being watched has got to, shown below the board.
+    playback_line: Option<String>,
+    // The arrow keys lit up in the corner of a replay being watched.
+    replay_keys: Option<Vec<Direction>>,
+    // Set by Ctrl+C, which leaves the whole program instead of returning to the menu.
+    force_quit: bool,
+    // The seed of the challenge being played, which restarting on the same seed replays.
+    challenge: Option<u64>,
+    // The campaign level being played, if any.
+    level: Option<usize>,
+    // The weekly challenge being played, if any.
+    weekly: Option<Weekly>,
+    // How the last run ended, or None if the player quit it.
+    outcome: Option<StepOutcome>,
+    // Set on the game over screen when the player asked to play again.
+    rerun: Option<Rerun>,
+    progress: Progress,
+    // The tournament player whose turn it is, if any.
+    player: Option<String>,
+    profiler: Option<Profiler>,
+    latency: Option<LatencyMeter>,
+    // What is needed to pick the run back up if it is interrupted, see emergency.
+    journal: Option<Journal>,
+    // Loading in the background until the first screen, see Startup.
+    startup: Option<Startup>,
+    interrupted: Option<Journal>,
+    autosave: AutosaveTimer,
+    #[cfg(feature = "audio")]
+    music: Option<Music>,
+    // Reused for the text drawn every frame, so a frame does not allocate.
+    line: String,
+    // Broken invariants already shown in this run, which are not shown again.
+    #[cfg(debug_assertions)]
+    reported_violations: Vec<Violation>,
+}
+
+impl Game {
+    pub fn new(stdout: Stdout, map: Map, config: Config) -> Result<Self, BoardError> {
+        let original_terminal_size: (u16, u16) = size().unwrap();
+        let (width, height) = (map.get_width(), map.get_height());
+        validate_board(width, height, original_terminal_size)?;
+        let seed = rand::thread_rng().gen();
+        let state = GameState::from_map(&map, config.rules(), seed);
+        let rival_policy = config.rival_ai.map(|level| level.new_policy(seed));
+        let startup = Startup::start(&config);
+
+        let mut game = Self {
+            renderer: Renderer::new(
+                stdout,
+                width + FRAME_EXTRA_WIDTH,
+                height + FRAME_EXTRA_HEIGHT,
+            ),
+            config,
+            original_terminal_size,
+            map,
+            width,
+            height,
+            state,
+            input: Box::new(KeyboardInput::new()),
+            rival_policy,
+            turns: TurnQueue::new(),
+            idle: IdleTimer::new(),
+            pacer: TickPacer::new(),
+            casual: false,
+            show_help: false,
+            show_debug: false,
+            show_danger_zone: false,
+            notice: None,
+            playback_line: None,
+            replay_keys: None,
+            force_quit: false,
+            challenge: None,
+            level: None,
+            weekly: None,
+            outcome: None,
+            rerun: None,
+            progress: Progress::default(),
+            player: None,
+            profiler: None,
+            latency: None,
+            journal: None,
+            startup: Some(startup),
+            interrupted: None,
+            autosave: AutosaveTimer::new(0, 0),
+            #[cfg(feature = "audio")]
+            music: None,
+            line: String::new(),
+            #[cfg(debug_assertions)]
+            reported_violations: Vec::new(),
+        };
+        game.show_danger_zone = game.config.danger_zone;
+        game.apply_casual_config();
+        Ok(game)
+    }
+
+    // Takes over the terminal, and then waits for the startup to finish loading.
+    fn prepare(&mut self) {
+        self.renderer.prepare();
+        self.finish_loading();
+    }
+
+    // Note: The splash is only drawn when the startup is still loading by the
+    // time the terminal is ready, with a bar of what is done so far. Keys
+    // pressed meanwhile are dropped, apart from Ctrl+C.
+    fn finish_loading(&mut self) {
+        let mut startup = match self.startup.take() {
+            Some(startup) => startup,
+            None => return,
+        };
+        loop {
+            let (done, total) = startup.get_done();
+            if done == total {
+                break;
+            }
+            let bar = format!("{}{}", "#".repeat(done), "-".repeat(total - done));
+            self.draw_screen(&["Snake".to_string(), String::new(), bar]);
+            let command = self.input.next_command(&self.state, LOADING_POLL_INTERVAL);
+            if command == Some(Command::ForceQuit) {
+                self.force_quit = true;
+            }
+        }
+
+        let loaded = startup.finish();
+        self.progress = loaded.progress;
+        self.interrupted = loaded.interrupted;
+        #[cfg(feature = "audio")]
+        {
+            self.music = loaded.music;
+        }
+    }
+
+    // Note: Games are started from the main menu, and finishing or quitting one
+    // goes back to it. The results are printed once the terminal is restored.
+    pub fn run(&mut self) {
+        self.prepare();
+
+        let mut results = Vec::new();
+        if self.challenge.is_some() {
+            self.restart();
+            results.push(self.play());
+        } else if let Some(journal) = self.interrupted.take() {
+            // Kept for the next launch when the player force quits instead of answering.
+            let mut lines = wrap("Resume interrupted game?", self.width as usize);
+            lines.extend(["".to_string(), "y/n".to_string()]);
+            self.draw_screen(&lines);
+            let resume = self.wait_for_answer();
+            if !self.force_quit {
+                Journal::discard();
+                if resume {
+                    self.resume(journal, &mut results);
+                }
+            }
+        }
+
+        let items = MAIN_MENU_ITEMS
+            .iter()
+            .map(|item| item.to_string())
+            .collect();
+        let mut menu = Menu::new("Snake", items);
+        while !self.force_quit {
+            self.queue_transition(Transition::Fade);
+            let choice = match self.choose_from_menu(&mut menu) {
+                Some(choice) => choice,
+                None => break,
+            };
+            match MAIN_MENU_ITEMS[choice] {
+                "Play" => {
+                    self.challenge = None;
+                    self.restart();
+                    results.push(self.play());
+                }
+                "Load autosave" => self.load_autosave(&mut results),
+                "Versus" => self.play_versus(&mut results),
+                "Custom" => self.build_custom_game(&mut results),
+                "Presets" => self.play_preset(&mut results),
+                "Campaign" => self.play_campaign(&mut results),
+                "Modes" => self.play_mode(&mut results),
+                "Modifiers" => self.choose_modifiers(),
+                "Challenges" => match self.choose_challenge() {
+                    Some(ChallengeChoice::Seed(seed)) => {
+                        self.challenge = Some(seed);
+                        self.restart();
+                        results.push(self.play());
+                    }
+                    Some(ChallengeChoice::Weekly) => self.play_weekly(&mut results),
+                    None => {}
+                },
+                "High scores" => self.show_high_scores(),
+                "Replays" => self.show_replays(&mut results),
+                "Rules" => {
+                    let rules = self.config.rules();
+                    self.show_rules(&rules);
+                }
+                "About" => self.show_about(),
+                _ => break,
+            }
+        }
+
+        self.renderer.restore(self.original_terminal_size);
+        for result in results {
+            println!("{}", result);
+        }
+    }
+
+    // Note: In every round each player plays the round's seed in turn, with a screen
+    // in between saying whose turn it is. Quitting a turn keeps the score reached
+    // so far, and Ctrl+C stops the whole tournament.
+    pub fn run_tournament(&mut self, tournament: &mut Tournament) {
+        self.prepare();
+
+        let players: Vec<String> = tournament.get_players().map(str::to_string).collect();
+        'rounds: while !tournament.is_finished() {
+            let round = format!(
+                "Round {}/{}",
+                tournament.get_round() + 1,
+                tournament.get_rounds()
+            );
+            let seed = rand::thread_rng().gen();
+            for (i, name) in players.iter().enumerate() {
+                let lines = [
+                    round.clone(),
+                    String::new(),
+                    format!("{}'s turn", name),
+                    String::new(),
+                    "Any key: start".to_string(),
+                ];
+                self.draw_screen(&lines);
+                self.wait_for_key();
+                if self.force_quit {
+                    break 'rounds;
+                }
+
+                self.player = Some(name.clone());
+                self.challenge = Some(seed);
+                self.restart();
+                self.play();
+                self.player = None;
+                tournament.record_score(i, self.state.get_score());
+                if self.force_quit {
+                    break 'rounds;
+                }
+            }
+
+            let winners = tournament.finish_round();
+            let title = if tournament.is_finished() {
+                match tournament.get_champion() {
+                    Some(champion) => format!("{} is the champion!", champion.name),
+                    None => "It's a tie!".to_string(),
+                }
+            } else {
+                format!("{} won by {}", round, winners.join(" and "))
+            };
+            self.show_standings(&title, tournament);
+        }
+
+        self.challenge = None;
+        self.renderer.restore(self.original_terminal_size);
+    }
+
+    fn show_standings(&mut self, title: &str, tournament: &Tournament) {
+        let mut lines = vec![title.to_string(), String::new()];
+        for (i, standing) in tournament.get_standings().iter().enumerate() {
+            lines.push(format!(
+                "{}. {} {}W {}",
+                i + 1,
+                standing.name,
+                standing.round_wins,
+                standing.total_score
+            ));
+        }
+        lines.push(String::new());
+        lines.push("Any key: continue".to_string());
+
+        self.draw_screen(&lines);
+        self.wait_for_key();
+    }
+
+    // Note: Versus is played on two boards sharing the keyboard, side by side or
+    // stacked depending on the shape of the terminal. Each board ticks at its own
+    // speed, and pausing or quitting holds both.
+    fn play_versus(&mut self, results: &mut Vec<String>) {
+        let (frame_width, frame_height) = (
+            self.width + FRAME_EXTRA_WIDTH,
+            self.height + FRAME_EXTRA_HEIGHT,
+        );
+        let layout = match Layout::new(frame_width, frame_height, self.original_terminal_size) {
+            Some(layout) => layout,
+            None => {
+                let lines = [
+                    "Versus needs room".to_string(),
+                    "for two boards".to_string(),
+                    String::new(),
+                    "Any key: back".to_string(),
+                ];
+                self.draw_screen(&lines);
+                self.wait_for_key();
+                return;
+            }
+        };
+
+        if !self.choose_player_keys() {
+            return;
+        }
+
+        let seed = rand::thread_rng().gen();
+        let mut race = Race::new(
+            &self.map,
+            &self.config.rules(),
+            seed,
+            self.config.versus_target,
+        );
+        let keyboard = std::mem::replace(
+            &mut self.input,
+            Box::new(KeyboardInput::split(&self.config.player_keys)),
+        );
+        self.renderer
+            .resize(layout.get_width(), layout.get_height());
+        self.render_race(&mut race, &layout);
+        race.start();
+
+        let result = 'race: loop {
+            if let Some(result) = race.get_result() {
+                break Some(result);
+            }
+
+            let deadline = race.next_deadline().unwrap();
+            let mut now = Instant::now();
+            while now < deadline {
+                if let Some(command) = self.input.next_command(&self.state, deadline - now) {
+                    match command {
+                        Command::TurnPlayer(player, towards) => {
+                            let pushed = race.push_turn(player, self.steer(towards));
+                            if pushed {
+                                self.render_race(&mut race, &layout);
+                            }
+                        }
+                        Command::Quit
+                        | Command::ForceQuit
+                        | Command::Pause
+                        | Command::AutoPause
+                        | Command::ToggleDebug
+                        | Command::Screenshot => {
+                            if let Flow::Quit = self.dispatch(command) {
+                                break 'race None;
+                            }
+                            self.render_race(&mut race, &layout);
+                            race.start();
+                            continue 'race;
+                        }
+                        _ => {}
+                    }
+                }
+                now = Instant::now();
+            }
+
+            race.tick(now, &mut self.rival_policy);
+            self.expire_notice();
+            self.render_race(&mut race, &layout);
+        };
+
+        self.input = keyboard;
+        self.renderer.resize(self.frame_width(), frame_height);
+        results.push(race.summary());
+        if let Some(result) = result {
+            self.queue_transition(Transition::Curtain);
+            self.draw_screen(&race.result_lines(result));
+            self.wait_for_key();
+        }
+    }
+
+    // Note: The setup screen of a race, where each player picks the keys they
+    // steer with. A change is saved in the config straight away, and the race
+    // cannot start while two players would share keys. Returns false on Back.
+    fn choose_player_keys(&mut self) -> bool {
+        let profiles = KeyProfile::all();
+        let mut selected = 0;
+        while !self.force_quit {
+            let keys = self.config.player_keys;
+            let mut items: Vec<String> = keys
+                .iter()
+                .zip(PLAYER_NAMES)
+                .map(|(profile, name)| format!("{}: {}", name, profile.label()))
+                .collect();
+            items.extend(["Start", "Back"].iter().map(|item| item.to_string()));
+            let title = format!("First to {}", self.config.versus_target.max(1));
+            let mut menu = Menu::new(&title, items);
+            let conflict = input::find_conflict(&keys);
+            match conflict {
+                Some((first, second)) => menu.set_hint(&format!(
+                    "{} and {} clash",
+                    PLAYER_NAMES[first], PLAYER_NAMES[second]
+                )),
+                None => menu.set_hint("A/D: change"),
+            }
+            menu.select(selected);
+            self.draw_menu(&menu);
+
+            let change = match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(Direction::Up)) => {
+                    menu.select_previous();
+                    None
+                }
+                Some(Command::Turn(Direction::Down)) => {
+                    menu.select_next();
+                    None
+                }
+                Some(Command::Turn(direction)) if selected < keys.len() => {
+                    Some(direction == Direction::Right)
+                }
+                Some(Command::Confirm) if selected < keys.len() => Some(true),
+                Some(Command::Confirm) if selected == keys.len() => {
+                    if conflict.is_none() {
+                        return true;
+                    }
+                    None
+                }
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    None
+                }
+                Some(Command::Confirm) | Some(Command::Quit) => return false,
+                _ => None,
+            };
+            selected = menu.get_selected();
+
+            if let Some(forward) = change {
+                let current = profiles
+                    .iter()
+                    .position(|profile| *profile == keys[selected])
+                    .unwrap();
+                let next = if forward {
+                    (current + 1) % profiles.len()
+                } else {
+                    (current + profiles.len() - 1) % profiles.len()
+                };
+                self.config.player_keys[selected] = profiles[next];
+                let key = format!("p{}_keys", selected + 1);
+                if let Err(error) = Config::save_value(&key, profiles[next].name()) {
+                    self.notice = Some((format!("Config not saved: {}", error), Instant::now()));
+                }
+            }
+        }
+
+        false
+    }
+
+    // Note: Swaps the racer's board in as the one being played while `f` runs, so
+    // the game's own drawing works on it unchanged.
+    fn with_racer<T>(&mut self, racer: &mut Racer, f: impl FnOnce(&mut Self) -> T) -> T {
+        std::mem::swap(&mut self.state, &mut racer.state);
+        std::mem::swap(&mut self.turns, &mut racer.turns);
+        let result = f(self);
+        std::mem::swap(&mut self.state, &mut racer.state);
+        std::mem::swap(&mut self.turns, &mut racer.turns);
+        result
+    }
+
+    // Each board is drawn into its own part of the frame, with the player's name
+    // in front of its score.
+    fn render_race(&mut self, race: &mut Race, layout: &Layout) {
+        self.renderer.frame_mut().clear();
+        for (i, racer) in race.racers.iter_mut().enumerate() {
+            self.renderer
+                .frame_mut()
+                .set_viewport(Some(layout.boards[i]));
+            self.player = Some(PLAYER_NAMES[i].to_string());
+            self.with_racer(racer, |game| game.draw_board());
+            if !racer.is_playing() {
+                self.renderer
+                    .frame_mut()
+                    .put_str(0, self.height + 3, "Out", Color::Red);
+            }
+        }
+        self.player = None;
+        self.renderer.frame_mut().set_viewport(Some(layout.bar));
+        race.draw_bar(self.renderer.frame_mut(), layout, self.height);
+        self.renderer.frame_mut().set_viewport(None);
+        self.renderer.present();
+    }
+
+    // The panel of the new input source, if it has one, is shown next to the board.
+    #[cfg(feature = "chat")]
+    pub fn set_input(&mut self, input: Box<dyn InputSource>) {
+        self.input = input;
+        self.renderer
+            .resize(self.frame_width(), self.height + FRAME_EXTRA_HEIGHT);
+    }
+
+    // Times every frame from now on, for the report printed by `--profile`.
+    pub fn start_profiling(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+
+    pub fn get_profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    // Times every turn from its key to the screen from now on, for `--measure-latency`.
+    pub fn start_measuring_latency(&mut self) {
+        self.latency = Some(LatencyMeter::new());
+    }
+
+    pub fn get_latency_meter(&self) -> Option<&LatencyMeter> {
+        self.latency.as_ref()
+    }
+
+    pub fn set_challenge(&mut self, seed: u64) {
+        self.challenge = Some(seed);
+    }
+
+    // Note: Restarting from the game over screen plays again straight away, with
+    // the same settings, so one call can play several runs. Each run's result is
+    // recorded as it ends, and the results come back one per line.
+    fn play(&mut self) -> String {
+        let mut results = vec![self.play_run()];
+        while let Some(rerun) = self.rerun.take() {
+            self.rerun(rerun);
+            results.push(self.play_run());
+        }
+
+        results.join("\n")
+    }
+
+    fn play_run(&mut self) -> String {
+        self.discard_profile_frame();
+        self.queue_transition(Transition::Wipe);
+        self.render();
+
+        self.pacer = TickPacer::new();
+        self.idle.touch();
+        if let Some(latency) = &mut self.latency {
+            latency.discard();
+        }
+        let outcome = 'game: loop {
+            let interval = self.state.calculate_interval();
+            let mut deadline = self.pacer.next_deadline(interval);
+            let mut now = Instant::now();
+
+            while now < deadline {
+                let wait_for = deadline - now;
+                if let Some(command) = self.input.next_command(&self.state, wait_for) {
+                    let started = Instant::now();
+                    let flow = self.dispatch(command);
+                    self.profile(Phase::Input, started);
+                    // After the command, as pausing, quitting and the rules wait on a dialog.
+                    self.idle.touch();
+                    match flow {
+                        Flow::Continue => {}
+                        Flow::Render => self.render(),
+                        Flow::Redraw => {
+                            // The command may have waited on a dialog, which is not work done in the frame.
+                            self.discard_profile_frame();
+                            self.render();
+                            deadline = self.pacer.restart(interval);
+                        }
+                        Flow::Quit => break 'game None,
+                    }
+                }
+                now = Instant::now();
+            }
+            self.pacer.tick(interval);
+
+            if self.expire_notice() {
+                self.render();
+            }
+
+            if self.show_help {
+                continue;
+            }
+
+            // Queued turns wait while the snake slides over ice.
+            let started = Instant::now();
+            let mut received = None;
+            if self.state.can_turn() {
+                if let Some((towards, at)) =
+                    self.turns.next_turn(self.state.get_snake().get_direction())
+                {
+                    self.state.turn(towards);
+                    if let Some(journal) = &mut self.journal {
+                        journal.record(Event::Turn(towards));
+                    }
+                    received = Some(at);
+                }
+            }
+            self.profile(Phase::Input, started);
+
+            if self.should_idle_pause() {
+                if self.pause() {
+                    break None;
+                }
+                self.idle.touch();
+                self.discard_profile_frame();
+                self.render();
+                continue;
+            }
+
+            let started = Instant::now();
+            self.steer_rival();
+            let step = self.state.step();
+            if let Some(journal) = &mut self.journal {
+                journal.stepped();
+            }
+            self.profile(Phase::Simulation, started);
+            if let (Some(latency), Some(received)) = (&mut self.latency, received) {
+                latency.turn_applied(received);
+            }
+            #[cfg(feature = "audio")]
+            self.set_music_intensity(self.state.get_speed());
+
+            match step {
+                StepOutcome::Moved | StepOutcome::Ate | StepOutcome::AteOutOfOrder => self.render(),
+                StepOutcome::LostLife => {
+                    let lives = self.state.get_lives();
+                    let notice =
+                        format!("Crashed! {} {} left", lives, plural(lives, "life", "lives"));
+                    self.notice = Some((notice, Instant::now()));
+                    self.turns = TurnQueue::new();
+                    self.render();
+                }
+                outcome => break Some(outcome),
+            }
+            if !self.state.get_messages().is_empty() && self.show_map_messages() {
+                break None;
+            }
+            let ate = matches!(step, StepOutcome::Ate | StepOutcome::AteOutOfOrder);
+            self.autosave_if_due(ate);
+            if let Some(profiler) = &mut self.profiler {
+                profiler.end_frame();
+            }
+            if let Some(latency) = &mut self.latency {
+                latency.tick_rendered();
+            }
+            #[cfg(debug_assertions)]
+            if self.pause_on_broken_invariants() {
+                break None;
+            }
+        };
+
+        self.outcome = outcome;
+        let finished = self.save_journal(outcome.is_none());
+        #[cfg(feature = "audio")]
+        self.set_music_intensity(0);
+        let won = outcome == Some(StepOutcome::Won);
+        let bonus = if won {
+            self.state.award_perfect_game_bonus()
+        } else {
+            0
+        };
+        if won {
+            self.celebrate();
+            self.render();
+            self.draw_message("You Win!");
+            self.input.next_command(&self.state, Duration::from_secs(5));
+        }
+
+        let run = self.run_info();
+        let title = match outcome {
+            Some(StepOutcome::Won) => "You Win!",
+            Some(StepOutcome::TimeUp) => "Time's Up!",
+            Some(StepOutcome::BossDefeated) => "Boss Defeated!",
+            Some(StepOutcome::RivalFilled) => "Board Full!",
+            _ => "Game Over!",
+        };
+        if outcome.is_some() {
+            self.show_game_over(title, &run);
+        }
+
+        let history_lock = SeedHistory::lock();
+        let mut history = SeedHistory::load();
+        history.record_played(run.seed);
+        let history_error = history.save().err();
+        drop(history_lock);
+
+        let score = self.config.modifiers.score(self.state.get_score());
+        let secs = self.state.get_elapsed().as_secs();
+        let replay_error =
+            finished.and_then(|journal| Replay::new(journal, score, secs, &run).save().err());
+        if self.config.zen {
+            return format!(
+                "Zen session over, your snake grew to length {} ({})",
+                self.state.get_snake().len(),
+                run.to_line()
+            );
+        }
+        if self.casual {
+            return format!(
+                "Game Over! Your casual score is {} ({})",
+                score,
+                run.to_line()
+            );
+        }
+
+        let hardcore_result = if self.config.hardcore && outcome == Some(StepOutcome::Died) {
+            Some(self.record_hardcore_death(&run))
+        } else {
+            None
+        };
+
+        let _stats_lock = Stats::lock();
+        let mut stats = Stats::load();
+        stats.record_game(score, won, &run);
+        let mut result = if won {
+            format!(
+                "You Win! Your score is {} (including a perfect game bonus of {}, {} perfect games so far, {})",
+                score,
+                bonus,
+                stats.perfect_games,
+                run.to_line()
+            )
+        } else {
+            format!("Game Over! Your score is {} ({})", score, run.to_line())
+        };
+        if let Err(error) = stats.save() {
+            result.push_str(&format!("\nCould not save stats: {}", error));
+        }
+        let _scores_lock = ScoreHistory::lock();
+        let mut scores = ScoreHistory::load();
+        scores.record(ScoreEntry::new(score, &self.config.player, &run));
+        if let Err(error) = scores.save() {
+            result.push_str(&format!("\nCould not save the score: {}", error));
+        }
+        if let Some(error) = history_error {
+            result.push_str(&format!("\nCould not save played seeds: {}", error));
+        }
+        if let Some(error) = replay_error {
+            result.push_str(&format!("\nCould not save the replay: {}", error));
+        }
+        if let Some(hardcore_result) = hardcore_result {
+            result.push('\n');
+            result.push_str(&hardcore_result);
+        }
+
+        result
+    }
+
+    // Note: Called right after the fatal step, before anything moves again, so
+    // the crash can still be worked out from the state. Returns a line for the results.
+    fn record_hardcore_death(&self, run: &RunInfo) -> String {
+        let snake = self.state.get_snake();
+        let cause = self
+            .state
+            .crash_cause(snake, snake.get_direction())
+            .map_or("unknown", |crash| crash.name());
+        let death = Death {
+            score: self.config.modifiers.score(self.state.get_score()),
+            length: snake.len(),
+            ticks: self.state.get_ticks(),
+            secs: self.state.get_elapsed().as_secs(),
+            cause: cause.to_string(),
+            run: run.clone(),
+        };
+
+        let _leaderboard_lock = Leaderboard::lock();
+        let mut leaderboard = Leaderboard::load();
+        let place = leaderboard.record(death);
+        if let Err(error) = leaderboard.save() {
+            return format!("Could not save the hardcore leaderboard: {}", error);
+        }
+        match place {
+            Some(place) => format!(
+                "Hardcore: died to {}, #{} on the leaderboard",
+                cause,
+                place + 1
+            ),
+            None => format!("Hardcore: died to {}", cause),
+        }
+    }
+
+    fn run_info(&self) -> RunInfo {
+        let mut mode = vec![if self.casual { "casual" } else { "classic" }.to_string()];
+        if self.config.chain_food {
+            mode.push("chain".to_string());
+        }
+        if self.config.territory_secs > 0 {
+            mode.push("territory".to_string());
+        }
+        if self.config.lives > 1 {
+            mode.push(format!("lives{}", self.config.lives));
+        }
+        if self.config.laser_secs > 0 {
+            mode.push("lasers".to_string());
+        }
+        if self.config.boss {
+            mode.push("boss".to_string());
+        }
+        if self.config.endless_every > 0 {
+            mode.push("endless".to_string());
+        }
+        if self.config.risk {
+            mode.push("risk".to_string());
+        }
+        if self.config.food_count > 1 {
+            mode.push(format!("food{}", self.config.food_count));
+        }
+        if self.config.obstacles > 0 {
+            mode.push(format!("obstacles{}", self.config.obstacles));
+        }
+        if self.config.start_speed > 0 {
+            mode.push(format!("speed{}", self.config.start_speed));
+        }
+        if self.config.speed_ramp != SpeedRamp::Normal {
+            mode.push(format!("ramp-{}", self.config.speed_ramp.name()));
+        }
+        if self.config.speed_model != SpeedModel::Score {
+            mode.push(format!("speed-by-{}", self.config.speed_model.name()));
+        }
+        let scoring = self.state.get_score_rule().name();
+        if scoring != "classic" {
+            mode.push(format!("scoring-{}", scoring));
+        }
+        if self.config.zen {
+            mode.push("zen".to_string());
+        }
+        if self.config.hardcore {
+            mode.push("hardcore".to_string());
+        }
+        mode.extend(
+            self.config
+                .modifiers
+                .iter()
+                .map(|modifier| modifier.name().to_string()),
+        );
+        if let Some(level) = self.level {
+            mode.push(format!("level{}", level + 1));
+        }
+        if let Some(weekly) = self.weekly {
+            mode.push(format!("weekly-{}", weekly.get_label()));
+        }
+        if !self.map.get_name().is_empty() {
+            mode.push(format!("map-{}", self.map.get_name().replace(' ', "-")));
+        }
+        if let Some(level) = self.config.rival_ai {
+            mode.push(level.name().to_string());
+        }
+
+        RunInfo::new(
+            self.state.get_seed(),
+            &mode.join("+"),
+            self.width,
+            self.height,
+        )
+    }
+
+    // Note: Returns the index of the chosen item, or None if the player backed out.
+    fn choose_from_menu(&mut self, menu: &mut Menu) -> Option<usize> {
+        loop {
+            self.draw_menu(menu);
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(Direction::Up)) => menu.select_previous(),
+                Some(Command::Turn(Direction::Down)) => menu.select_next(),
+                Some(Command::Confirm) => return Some(menu.get_selected()),
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return None;
+                }
+                Some(Command::Quit) => return None,
+                _ => {}
+            }
+        }
+    }
+
+    // Note: Lists today's daily challenge, the seeds played most recently and the
+    // codes shared by others, and returns the seed of the one picked.
+    fn choose_challenge(&mut self) -> Option<ChallengeChoice> {
+        let history = SeedHistory::load();
+        let day = challenge::days_since_epoch();
+        let mut choices = vec![
+            ChallengeChoice::Seed(challenge::daily_seed(day)),
+            ChallengeChoice::Weekly,
+        ];
+        let mut items = vec![
+            format!("Daily {}", challenge::date_label(day)),
+            format!("Weekly {}", Weekly::for_day(day).get_label()),
+        ];
+        for seed in &history.recent {
+            choices.push(ChallengeChoice::Seed(*seed));
+            items.push(format!("Seed {}", challenge::encode_code(*seed)));
+        }
+        for seed in &history.shared {
+            choices.push(ChallengeChoice::Seed(*seed));
+            items.push(format!("Code {}", challenge::encode_code(*seed)));
+        }
+        items.push("Back".to_string());
+
+        let choice = self.choose_from_menu(&mut Menu::new("Challenges", items))?;
+        choices.get(choice).copied()
+    }
+
+    // Note: The weekly challenge is played like a campaign level, on its own
+    // board and rules, and only finished runs that were not made casual count
+    // towards the week's best.
+    fn play_weekly(&mut self, results: &mut Vec<String>) {
+        let weekly = Weekly::this_week();
+        let label = weekly.get_label();
+        if !self.show_weekly_intro(&weekly) {
+            return;
+        }
+
+        let config = weekly.config(&self.config);
+        let own_config = std::mem::replace(&mut self.config, config);
+        let own_map = self.set_map(weekly.map());
+        self.weekly = Some(weekly);
+        self.challenge = Some(weekly.get_seed());
+        self.restart();
+        results.push(self.play());
+
+        if self.outcome.is_some() && !self.casual {
+            let score = self.config.modifiers.score(self.state.get_score());
+            // Reloaded first to keep what other instances saved in the meantime.
+            let bests_lock = WeeklyBests::lock();
+            let mut bests = WeeklyBests::load();
+            let new_best = bests.record(&label, score);
+            if let Err(error) = bests.save() {
+                results.push(format!("Could not save the weekly best: {}", error));
+            }
+            drop(bests_lock);
+
+            self.draw_screen(&weekly.result_lines(&bests, score, new_best));
+            self.wait_for_key();
+        }
+        self.weekly = None;
+        self.challenge = None;
+        self.config = own_config;
+        self.set_map(own_map);
+    }
+
+    // Returns whether the player chose to play.
+    fn show_weekly_intro(&mut self, weekly: &Weekly) -> bool {
+        self.draw_screen(&weekly.intro_lines(&WeeklyBests::load(), self.width as usize));
+
+        loop {
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Confirm) => return true,
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return false;
+                }
+                Some(Command::Quit) | Some(Command::Cancel) => return false,
+                _ => {}
+            }
+        }
+    }
+
+    // Note: A level is played with its own board and rules, and the player's are
+    // put back afterwards. Quitting a level or playing it casually earns no stars.
+    fn play_campaign(&mut self, results: &mut Vec<String>) {
+        let mut selected = 0;
+        while !self.force_quit {
+            let level = match self.choose_level(selected) {
+                Some(level) => level,
+                None => return,
+            };
+            selected = level;
+            if !self.progress.is_unlocked(level) {
+                continue;
+            }
+
+            let config = LEVELS[level].config(&self.config);
+            let own_config = std::mem::replace(&mut self.config, config);
+            let own_map = self.set_map(LEVELS[level].map());
+            self.level = Some(level);
+            self.challenge = None;
+            self.restart();
+            results.push(self.play());
+
+            if self.outcome.is_some() && !self.casual {
+                let stars = LEVELS[level].stars_for(self.state.get_score());
+                // Reloaded first to keep what other instances saved in the meantime.
+                let progress_lock = Progress::lock();
+                self.progress = Progress::load();
+                let unlocks = self.progress.record(level, stars);
+                if let Err(error) = self.progress.save() {
+                    results.push(format!("Could not save campaign progress: {}", error));
+                }
+                drop(progress_lock);
+                self.show_level_result(level, stars, &unlocks);
+            }
+            self.level = None;
+            self.config = own_config;
+            self.set_map(own_map);
+        }
+    }
+
+    // Note: Returns the index of the level picked, which may still be locked.
+    fn choose_level(&mut self, selected: usize) -> Option<usize> {
+        let mut menu = self.progress.level_menu();
+        menu.select(selected);
+        let choice = self.choose_from_menu(&mut menu)?;
+        (choice < LEVELS.len()).then_some(choice)
+    }
+
+    fn show_high_scores(&mut self) {
+        let mut browser = ScoreBrowser::load(self.height as usize);
+        loop {
+            self.draw_screen(&browser.lines(self.width as usize));
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(Direction::Up)) => browser.select_previous(),
+                Some(Command::Turn(Direction::Down)) => browser.select_next(),
+                Some(Command::Turn(Direction::Left)) => browser.previous(),
+                Some(Command::Turn(Direction::Right)) => browser.next(),
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return;
+                }
+                Some(Command::Quit) => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn show_level_result(&mut self, level: usize, stars: u8, unlocks: &[Unlock]) {
+        self.draw_screen(&LEVELS[level].result_lines(stars, unlocks));
+        self.wait_for_key();
+    }
+
+    // Note: Modes unlocked in the campaign are played on the current board, on top
+    // of the player's own settings.
+    fn play_mode(&mut self, results: &mut Vec<String>) {
+        let modes = Mode::all();
+        let mode = match self.choose_from_menu(&mut self.progress.mode_menu()) {
+            Some(choice) if choice < modes.len() => modes[choice],
+            _ => return,
+        };
+        if !self.progress.has_unlocked(Unlock::Mode(mode)) {
+            return;
+        }
+
+        let mut config = self.config.clone();
+        mode.apply(&mut config);
+        let own_config = std::mem::replace(&mut self.config, config);
+        self.challenge = None;
+        self.restart();
+        results.push(self.play());
+        self.config = own_config;
+    }
+
+    // Note: Toggling a modifier saves it in the config straight away, and the
+    // title shows what the score is multiplied by with the ones turned on.
+    fn choose_modifiers(&mut self) {
+        let modifiers = Modifier::all();
+        let mut selected = 0;
+        loop {
+            let mut items: Vec<String> = modifiers
+                .iter()
+                .map(|modifier| {
+                    let mark = if self.config.modifiers.contains(*modifier) {
+                        'x'
+                    } else {
+                        ' '
+                    };
+                    format!(
+                        "[{}] {} {}",
+                        mark,
+                        modifier.label(),
+                        Multiplier(modifier.score_percent())
+                    )
+                })
+                .collect();
+            items.push("Back".to_string());
+            let title = format!(
+                "Modifiers {}",
+                Multiplier(self.config.modifiers.score_percent())
+            );
+
+            let mut menu = Menu::new(&title, items);
+            menu.select(selected);
+            selected = match self.choose_from_menu(&mut menu) {
+                Some(choice) if choice < modifiers.len() => choice,
+                _ => return,
+            };
+            self.config.modifiers.toggle(modifiers[selected]);
+            let value = self.config.modifiers.name();
+            if let Err(error) = Config::save_value("modifiers", &value) {
+                self.notice = Some((format!("Config not saved: {}", error), Instant::now()));
+            }
+        }
+    }
+
+    // Note: Left and right change the selected setting. Games are played on an
+    // empty board of the chosen size with the player's other settings, and the
+    // screen comes back afterwards so the settings can be tweaked and tried again.
+    fn build_custom_game(&mut self, results: &mut Vec<String>) {
+        let (columns, rows) = size().unwrap();
+        let max_size = (
+            columns.saturating_sub(self.frame_width() - self.width),
+            rows.saturating_sub(FRAME_EXTRA_HEIGHT),
+        );
+        let mut custom = CustomGame::new(
+            self.config.clone(),
+            (self.width, self.height),
+            (MIN_BOARD_WIDTH, MIN_BOARD_HEIGHT),
+            max_size,
+        );
+        let settings = Setting::all();
+        let mut selected = 0;
+
+        while !self.force_quit {
+            let mut menu = custom.menu();
+            menu.select(selected);
+            self.draw_menu(&menu);
+
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(Direction::Up)) => menu.select_previous(),
+                Some(Command::Turn(Direction::Down)) => menu.select_next(),
+                Some(Command::Turn(direction)) if selected < settings.len() => {
+                    custom.adjust(settings[selected], direction == Direction::Right)
+                }
+                Some(Command::Confirm) if selected < settings.len() => {
+                    custom.adjust(settings[selected], true)
+                }
+                Some(Command::Confirm) if selected == settings.len() => {
+                    self.play_custom_game(&custom, results)
+                }
+                Some(Command::Confirm) if selected == settings.len() + 1 => {
+                    self.save_preset(&custom)
+                }
+                Some(Command::ForceQuit) => self.force_quit = true,
+                Some(Command::Confirm) | Some(Command::Quit) => return,
+                _ => {}
+            }
+            selected = menu.get_selected();
+        }
+    }
+
+    fn play_custom_game(&mut self, custom: &CustomGame, results: &mut Vec<String>) {
+        let map = self.set_map(Map::empty(custom.width, custom.height));
+        let own_config = std::mem::replace(&mut self.config, custom.config.clone());
+        self.challenge = None;
+        self.restart();
+        results.push(self.play());
+        self.config = own_config;
+        self.set_map(map);
+    }
+
+    // Note: Presets are played on their own board when they have one, as long as
+    // it fits the terminal, and on the current board otherwise.
+    fn play_preset(&mut self, results: &mut Vec<String>) {
+        let presets = self.config.get_presets();
+        let preset = match self.choose_from_menu(&mut preset::menu(&presets)) {
+            Some(choice) if choice < presets.len() => &presets[choice],
+            _ => return,
+        };
+
+        let board_size = preset.get_board_size();
+        if let Some((width, height)) = board_size {
+            if let Err(error) = validate_board(width, height, size().unwrap()) {
+                self.draw_screen(&preset.unplayable_lines(&error.to_string()));
+                self.wait_for_key();
+                return;
+            }
+        }
+
+        let mut config = self.config.clone();
+        preset.apply(&mut config);
+        let own_map = board_size.map(|(width, height)| self.set_map(Map::empty(width, height)));
+        let own_config = std::mem::replace(&mut self.config, config);
+        self.challenge = None;
+        self.restart();
+        results.push(self.play());
+        self.config = own_config;
+        if let Some(map) = own_map {
+            self.set_map(map);
+        }
+    }
+
+    fn save_preset(&mut self, custom: &CustomGame) {
+        let name = match self.enter_name("Preset name") {
+            Some(name) => name,
+            None => return,
+        };
+
+        let preset = custom.to_preset(&name);
+        let message = match Config::save_value(&preset.get_key(), &preset.to_value()) {
+            Ok(()) => format!("Saved preset {}", name),
+            Err(error) => format!("Not saved: {}", error),
+        };
+        self.config.presets.retain(|known| known.name != name);
+        self.config.presets.push(preset);
+        self.draw_screen(&[message, String::new(), "Any key: back".to_string()]);
+        self.wait_for_key();
+    }
+
+    // Note: See NumberEntry for how the number is entered. Returns at most `max`.
+    fn enter_number(&mut self, title: &str, value: u64, max: u64) -> Option<u64> {
+        let mut entry = NumberEntry::new(value, max);
+        loop {
+            self.draw_screen(&entry.lines(title));
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(direction)) => entry.press(direction),
+                Some(Command::Confirm) => return Some(entry.get_value()),
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return None;
+                }
+                Some(Command::Quit) | Some(Command::Cancel) => return None,
+                _ => {}
+            }
+        }
+    }
+
+    fn enter_name(&mut self, title: &str) -> Option<String> {
+        self.enter_text(
+            title,
+            "",
+            NAME_CHARACTERS,
+            preset::MAX_NAME_LENGTH,
+            |text| {
+                let name: String = text.chars().filter(|c| *c != ' ').collect();
+                Some(name).filter(|name| Preset::is_valid_name(name))
+            },
+        )
+    }
+
+    // Note: See TextEntry for how the text is entered. Enter is done once
+    // `finish` turns the text into something.
+    fn enter_text(
+        &mut self,
+        title: &str,
+        initial: &str,
+        characters: &str,
+        max_length: usize,
+        finish: fn(&str) -> Option<String>,
+    ) -> Option<String> {
+        let mut entry = TextEntry::new(initial, characters, max_length);
+        loop {
+            self.draw_screen(&entry.lines(title));
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(direction)) => entry.press(direction),
+                Some(Command::Confirm) => {
+                    if let Some(text) = finish(&entry.get_text()) {
+                        return Some(text);
+                    }
+                }
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return None;
+                }
+                Some(Command::Quit) | Some(Command::Cancel) => return None,
+                _ => {}
+            }
+        }
+    }
+
+    // Returns the map that was played on before.
+    fn set_map(&mut self, map: Map) -> Map {
+        self.width = map.get_width();
+        self.height = map.get_height();
+        self.renderer
+            .resize(self.frame_width(), self.height + FRAME_EXTRA_HEIGHT);
+        std::mem::replace(&mut self.map, map)
+    }
+
+    fn frame_width(&self) -> u16 {
+        if self.input.panel().is_empty() {
+            self.width + FRAME_EXTRA_WIDTH
+        } else {
+            self.width + FRAME_EXTRA_WIDTH + PANEL_WIDTH
+        }
+    }
+
+    fn show_about(&mut self) {
+        let features = about::enabled_features();
+        let mut lines = vec![
+            "snake-rs".to_string(),
+            format!("Version {}", about::VERSION),
+            format!("Board {}x{}", self.width, self.height),
+        ];
+        if features.is_empty() {
+            lines.push("Minimal build".to_string());
+        } else {
+            lines.push("Features:".to_string());
+            lines.extend(features.iter().map(|feature| feature.to_string()));
+        }
+        lines.push(String::new());
+        lines.push("Any key: back".to_string());
+
+        self.draw_screen(&lines);
+        self.wait_for_key();
+    }
+
+    // Note: Explains the rules a run is played with, as described by the rules
+    // themselves, plus what the frontend's own settings change about them.
+    fn show_rules(&mut self, rules: &Rules) {
+        let mut text = rules.describe();
+        if self.config.hardcore {
+            text.push("Hardcore: no pausing or restarting".to_string());
+        }
+        if self.casual {
+            text.push("Casual: a fixed speed, and the run is not scored".to_string());
+        }
+        if self.config.modifiers.contains(Modifier::Fog) {
+            text.push("Fog: only the cells around the head can be seen".to_string());
+        }
+        if self.config.modifiers.contains(Modifier::MirrorControls) {
+            text.push("Mirror controls: every direction steers the other way".to_string());
+        }
+        text.push(String::new());
+        text.push("Scoring:".to_string());
+        text.extend(rules.describe_scoring());
+        let percent = self.config.modifiers.score_percent();
+        if percent != 100 && !rules.zen {
+            text.push(format!(
+                "Modifiers multiply the score {}",
+                Multiplier(percent)
+            ));
+        }
+
+        let width = self.width as usize;
+        let mut lines = vec!["Rules".to_string(), String::new()];
+        for line in text {
+            lines.extend(wrap(&line, width));
+        }
+        lines.push(String::new());
+        lines.push("Any key: back".to_string());
+
+        self.draw_screen(&lines);
+        self.wait_for_key();
+    }
+
+    // Note: Only shown when the game actually ended, not when the player quit.
+    fn show_game_over(&mut self, title: &str, run: &RunInfo) {
+        let score = self.state.get_score();
+        let percent = self.config.modifiers.score_percent();
+        let score_line = if percent == 100 {
+            format!("Score: {}", self.score_label(score))
+        } else {
+            format!(
+                "Score: {} ({} {})",
+                self.config.modifiers.score(score),
+                self.score_label(score),
+                Multiplier(percent)
+            )
+        };
+        let mut lines = vec![
+            title.to_string(),
+            score_line,
+            String::new(),
+            format!("Seed {:x}", run.seed),
+            format!("Code {}", challenge::encode_code(run.seed)),
+            format!("Mode {}", run.mode),
+            format!("Board {}", run.get_board_size()),
+            format!("Version {}", run.version),
+            String::new(),
+        ];
+        // Campaign levels, weekly challenges and tournament turns go on with their own screens.
+        let can_rerun = self.level.is_none() && self.weekly.is_none() && self.player.is_none();
+        if can_rerun {
+            lines.push("R: again, shift: same seed".to_string());
+        }
+        lines.push("Any key: menu".to_string());
+
+        self.queue_transition(Transition::Curtain);
+        self.draw_screen(&lines);
+        loop {
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Restart) if can_rerun => self.rerun = Some(Rerun::NewSeed),
+                Some(Command::RestartSameSeed) if can_rerun => self.rerun = Some(Rerun::SameSeed),
+                Some(Command::ForceQuit) => self.force_quit = true,
+                Some(Command::AutoPause) | None => continue,
+                Some(_) => {}
+            }
+            return;
+        }
+    }
+
+    fn queue_transition(&mut self, transition: Transition) {
+        if self.config.transitions {
+            self.renderer.queue_transition(transition);
+        }
+    }
+
+    fn wait_for_key(&mut self) {
+        loop {
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return;
+                }
+                Some(Command::AutoPause) | None => {}
+                Some(_) => return,
+            }
+        }
+    }
+
+    // Note: Every command from the input source goes through here,
+    // whether it came from the keyboard or anywhere else.
+    // Note: Hardcore runs cannot be paused, restarted or slowed down, and the help
+    // overlay and rules screen (which hold the game) stay closed.
+    fn dispatch(&mut self, command: Command) -> Flow {
+        let held_back = matches!(
+            command,
+            Command::Pause
+                | Command::AutoPause
+                | Command::Restart
+                | Command::RestartSameSeed
+                | Command::ToggleHelp
+                | Command::ShowRules
+                | Command::SpeedUp
+                | Command::SpeedDown
+        );
+        if self.config.hardcore && held_back {
+            return Flow::Continue;
+        }
+
+        match command {
+            Command::Quit => {
+                if !self.config.confirm_quit || self.confirm_quit() {
+                    Flow::Quit
+                } else {
+                    Flow::Redraw
+                }
+            }
+            Command::ForceQuit => {
+                self.force_quit = true;
+                Flow::Quit
+            }
+            Command::Pause | Command::AutoPause => {
+                if self.pause() {
+                    Flow::Quit
+                } else {
+                    Flow::Redraw
+                }
+            }
+            Command::Restart | Command::RestartSameSeed => {
+                if !self.confirm("Restart? y/n") {
+                    return Flow::Redraw;
+                }
+                if self.force_quit {
+                    return Flow::Quit;
+                }
+                let rerun = if command == Command::RestartSameSeed {
+                    Rerun::SameSeed
+                } else {
+                    Rerun::NewSeed
+                };
+                self.rerun(rerun);
+                Flow::Redraw
+            }
+            Command::ToggleHelp => {
+                self.show_help = !self.show_help;
+                Flow::Redraw
+            }
+            Command::ShowRules => {
+                let rules = self.state.get_rules().clone();
+                self.show_rules(&rules);
+                Flow::Redraw
+            }
+            Command::ToggleDebug => {
+                self.show_debug = !self.show_debug;
+                Flow::Redraw
+            }
+            Command::ToggleDangerZone => {
+                self.show_danger_zone = !self.show_danger_zone;
+                Flow::Redraw
+            }
+            Command::ToggleMusic => {
+                self.toggle_music();
+                Flow::Redraw
+            }
+            Command::SpeedUp => {
+                self.adjust_casual_interval(true);
+                Flow::Redraw
+            }
+            Command::SpeedDown => {
+                self.adjust_casual_interval(false);
+                Flow::Redraw
+            }
+            Command::Screenshot => {
+                let notice = match self.save_screenshot() {
+                    Ok(file_name) => format!("Saved {}", file_name),
+                    Err(error) => format!("Screenshot failed: {}", error),
+                };
+                self.notice = Some((notice, Instant::now()));
+                Flow::Redraw
+            }
+            Command::Turn(towards) => {
+                if let Some(journal) = &mut self.journal {
+                    let since_tick = self.pacer.since_last_tick().unwrap_or_default();
+                    let millis = since_tick.as_millis().min(u16::MAX as u128) as u16;
+                    journal.record(Event::Key(towards, millis));
+                }
+                let towards = self.steer(towards);
+                // Shows the head lighting up and the preview of the turns straight away.
+                if self.turns.push(towards) {
+                    Flow::Render
+                } else {
+                    Flow::Continue
+                }
+            }
+            Command::Confirm | Command::Cancel | Command::TurnPlayer(..) => Flow::Continue,
+        }
+    }
+
+    // Mirrored controls steer the other way (menus are not mirrored).
+    fn steer(&self, towards: Direction) -> Direction {
+        if self.config.modifiers.contains(Modifier::MirrorControls) {
+            towards.opposite()
+        } else {
+            towards
+        }
+    }
+
+    // Note: Saves the frame as it is currently on screen to a timestamped file
+    // in the working directory, as plain text unless colors are turned on.
+    fn save_screenshot(&self) -> io::Result<String> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let file_name = format!("snake-screenshot-{}.txt", timestamp);
+        let frame = self.renderer.frame();
+        let contents = if self.config.screenshot_colors {
+            frame.to_ansi_text()
+        } else {
+            frame.to_plain_text()
+        };
+        fs::write(&file_name, contents)?;
+
+        Ok(file_name)
+    }
+
+    fn expire_notice(&mut self) -> bool {
+        match &self.notice {
+            Some((_, shown_at)) if shown_at.elapsed() >= NOTICE_DURATION => {
+                self.notice = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn restart(&mut self) {
+        let seed = self.challenge.unwrap_or_else(|| rand::thread_rng().gen());
+        self.restart_with_seed(seed);
+    }
+
+    // Note: Everything about the run is set up again in place, so the game loop
+    // can carry on with the new state from its next tick.
+    fn restart_with_seed(&mut self, seed: u64) {
+        self.state = GameState::from_map(&self.map, self.config.rules(), seed);
+        self.rival_policy = self.config.rival_ai.map(|level| level.new_policy(seed));
+        self.turns = TurnQueue::new();
+        self.casual = false;
+        self.apply_casual_config();
+        self.idle.touch();
+        self.journal = if self.level.is_none() && self.weekly.is_none() && self.player.is_none() {
+            Some(Journal::new(
+                seed,
+                self.map.to_text(),
+                self.config.entries(),
+            ))
+        } else {
+            None
+        };
+        self.autosave = AutosaveTimer::new(self.config.autosave_secs, self.config.autosave_foods);
+        #[cfg(debug_assertions)]
+        self.reported_violations.clear();
+    }
+
+    // Note: Only runs that were quit part of the way through are saved, and a
+    // run that ended on its own leaves nothing to resume. Its journal is
+    // returned instead, to be kept as its replay.
+    fn save_journal(&mut self, interrupted: bool) -> Option<Journal> {
+        match self.journal.take() {
+            Some(journal) if interrupted && journal.get_steps() > 0 => {
+                if let Err(error) = journal.save() {
+                    self.notice = Some((
+                        format!("Interrupted game not saved: {}", error),
+                        Instant::now(),
+                    ));
+                }
+                None
+            }
+            Some(journal) if !interrupted => Some(journal),
+            _ => None,
+        }
+    }
+
+    // Hardcore runs are not autosaved, since loading one would undo a death.
+    fn autosave_if_due(&mut self, ate: bool) {
+        let journal = match &self.journal {
+            Some(journal) if !self.config.hardcore => journal,
+            _ => return,
+        };
+        if !self.autosave.tick(self.state.get_elapsed(), ate) {
+            return;
+        }
+
+        let notice = match Autosave::save(journal, self.state.get_score()) {
+            Ok(()) => "Autosaved".to_string(),
+            Err(error) => format!("Autosave failed: {}", error),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    fn load_autosave(&mut self, results: &mut Vec<String>) {
+        let autosaves = Autosave::load_all();
+        if let Some(autosave) = self
+            .choose_from_menu(&mut Autosave::menu(&autosaves))
+            .and_then(|choice| autosaves.get(choice))
+        {
+            self.resume(autosave.journal.clone(), results);
+        }
+    }
+
+    // Note: A saved run is set up again with its own settings and map and
+    // replayed tick by tick without being drawn, so it carries on from where it
+    // was left. It is skipped when its board no longer fits the terminal.
+    fn resume(&mut self, journal: Journal, results: &mut Vec<String>) {
+        let map = match Map::parse(journal.get_map()) {
+            Ok(map) => map,
+            Err(_) => return,
+        };
+        let size = self.original_terminal_size;
+        if validate_board(map.get_width(), map.get_height(), size).is_err() {
+            return;
+        }
+
+        let mut config = self.config.clone();
+        for (key, value) in journal.get_settings() {
+            config.apply_setting(key, value);
+        }
+        let own_map = self.set_map(map);
+        let own_config = std::mem::replace(&mut self.config, config);
+        self.challenge = None;
+        self.restart_with_seed(journal.get_seed());
+        for step in 0..journal.get_steps() {
+            self.replay_run().step(&journal, step);
+        }
+        self.journal = Some(journal);
+
+        results.push(self.play());
+        self.config = own_config;
+        self.set_map(own_map);
+    }
+
+    // The game's own state, rival and casual interval, for a journal to be played into.
+    fn replay_run(&mut self) -> Run<'_> {
+        Run {
+            state: &mut self.state,
+            rival_policy: &mut self.rival_policy,
+            casual: &mut self.casual,
+        }
+    }
+
+    // Note: Plays a replay on its own, e.g. one given on the command line.
+    // Returns whether any notes were written, which the replay wants saving for.
+    pub fn run_replay(&mut self, replay: &mut Replay) -> bool {
+        self.prepare();
+        let noted = self.watch_replay(replay);
+        self.renderer.restore(self.original_terminal_size);
+        noted
+    }
+
+    // Note: A replay is set up like a resumed run and played back at the speed
+    // it was played at, drawn but without anything being recorded, with where
+    // it has got to shown below the board and the keys controlled by Playback.
+    // It is skipped when its board no longer fits the terminal. The arrow keys
+    // in the corner light up as they were pressed, at the same point in the
+    // tick. A note written at a tick is shown as a notice whenever the replay
+    // gets there. Returns whether any notes were written.
+    fn watch_replay(&mut self, replay: &mut Replay) -> bool {
+        let map = match Map::parse(replay.journal.get_map()) {
+            Ok(map) => map,
+            Err(_) => return false,
+        };
+        let size = self.original_terminal_size;
+        if let Err(error) = validate_board(map.get_width(), map.get_height(), size) {
+            let mut lines = wrap(&error.to_string(), self.width as usize);
+            lines.extend([String::new(), "Any key: back".to_string()]);
+            self.draw_screen(&lines);
+            self.wait_for_key();
+            return false;
+        }
+
+        let mut config = self.config.clone();
+        for (key, value) in replay.journal.get_settings() {
+            config.apply_setting(key, value);
+        }
+        let own_map = self.set_map(map);
+        let own_config = std::mem::replace(&mut self.config, config);
+        self.restart_with_seed(replay.journal.get_seed());
+        self.journal = None;
+        self.notice = Some(("Replay, q: stop".to_string(), Instant::now()));
+        self.queue_transition(Transition::Wipe);
+
+        let mut playback = Playback::new(replay.journal.get_steps());
+        let mut tick_started = Instant::now();
+        let mut noted = false;
+        loop {
+            let step = playback.get_step();
+            let width = (self.width + FRAME_EXTRA_WIDTH) as usize;
+            self.playback_line = Some(playback.progress_line(width));
+            let interval = playback.get_speed().scale(self.state.calculate_interval());
+            let elapsed = tick_started.elapsed();
+            let stopped = playback.is_paused() || playback.is_finished();
+            let shown_until = if stopped { None } else { Some(elapsed) };
+            let (keys, next_key) = playback.keys(&replay.journal, shown_until);
+            self.replay_keys = Some(keys);
+            self.expire_notice();
+            self.render();
+            let wait = if stopped {
+                // Woken up in time to take the notice down.
+                match self.notice {
+                    Some(_) => NOTICE_DURATION,
+                    None => DIALOG_POLL_INTERVAL,
+                }
+            } else {
+                // Also woken up to light up the next key.
+                next_key
+                    .unwrap_or(interval)
+                    .min(interval)
+                    .saturating_sub(elapsed)
+            };
+            let command = self.input.next_command(&self.state, wait);
+            // Also after pausing, so playing on waits a whole tick.
+            if command.is_some() || stopped {
+                tick_started = Instant::now();
+            }
+            match command.map(|command| playback.control(command)) {
+                Some(Some(Control::Stop)) => break,
+                Some(Some(Control::ForceQuit)) => {
+                    self.force_quit = true;
+                    break;
+                }
+                Some(Some(Control::Seek(step))) => {
+                    playback.seek(&replay.journal, &mut self.replay_run(), step)
+                }
+                Some(Some(Control::Jump)) => {
+                    let (step, steps) = (playback.get_step(), playback.get_steps());
+                    if let Some(step) = self.enter_number("Jump to tick", step, steps) {
+                        playback.seek(&replay.journal, &mut self.replay_run(), step);
+                    }
+                    if self.force_quit {
+                        break;
+                    }
+                }
+                Some(Some(Control::Note)) => {
+                    let title = format!("Note at tick {}", step);
+                    let initial = replay.get_note(step).unwrap_or("").to_string();
+                    let note = self.enter_text(
+                        &title,
+                        &initial,
+                        NOTE_CHARACTERS,
+                        replay::MAX_NOTE_LENGTH,
+                        |text| Some(text.trim().to_string()),
+                    );
+                    if self.force_quit {
+                        break;
+                    }
+                    if let Some(note) = note {
+                        replay.set_note(step, &note);
+                        noted = true;
+                        let notice = if note.is_empty() {
+                            "Note removed"
+                        } else {
+                            "Note saved"
+                        };
+                        self.notice = Some((notice.to_string(), Instant::now()));
+                    }
+                }
+                Some(None) => {}
+                None if stopped || tick_started.elapsed() < interval => {}
+                None => {
+                    tick_started = Instant::now();
+                    let outcome = playback.step(&replay.journal, &mut self.replay_run());
+                    if outcome == StepOutcome::LostLife {
+                        let lives = self.state.get_lives();
+                        let notice =
+                            format!("Crashed! {} {} left", lives, plural(lives, "life", "lives"));
+                        self.notice = Some((notice, Instant::now()));
+                    }
+                    if playback.is_finished() {
+                        let notice = format!("End of replay, score {}", replay.score);
+                        self.notice = Some((notice, Instant::now()));
+                    }
+                    if !self.state.get_messages().is_empty() && self.show_map_messages() {
+                        break;
+                    }
+                }
+            }
+            if playback.get_step() != step {
+                if let Some(note) = replay.get_note(playback.get_step()) {
+                    self.notice = Some((note.to_string(), Instant::now()));
+                }
+            }
+        }
+
+        self.playback_line = None;
+        self.replay_keys = None;
+        self.notice = None;
+        self.config = own_config;
+        self.set_map(own_map);
+        noted
+    }
+
+    // Enter opens what can be done with the selected replay.
+    fn show_replays(&mut self, results: &mut Vec<String>) {
+        let mut browser = ReplayBrowser::load();
+        while !self.force_quit {
+            let lines = browser.lines(self.width as usize, self.height as usize);
+            self.draw_screen(&lines);
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Turn(Direction::Up)) => browser.select_previous(),
+                Some(Command::Turn(Direction::Down)) => browser.select_next(),
+                Some(Command::Confirm) => self.choose_replay_action(&mut browser, results),
+                Some(Command::ForceQuit) => self.force_quit = true,
+                Some(Command::Quit) => return,
+                _ => {}
+            }
+        }
+    }
+
+    // A deleted replay is taken out of the list as well.
+    fn choose_replay_action(&mut self, browser: &mut ReplayBrowser, results: &mut Vec<String>) {
+        let replay = match browser.get_selected() {
+            Some(replay) => replay,
+            None => return,
+        };
+        let favorite = if replay.favorite {
+            "Unfavorite"
+        } else {
+            "Favorite"
+        };
+        let items = ["Watch", favorite, "Export", "Delete", "Back"];
+        let mut menu = Menu::new(
+            &replay.get_date(),
+            items.iter().map(|item| item.to_string()).collect(),
+        );
+        let choice = self.choose_from_menu(&mut menu).map(|choice| items[choice]);
+        let replay = browser.get_selected().unwrap();
+        match choice {
+            Some("Watch") => {
+                if self.watch_replay(replay) {
+                    if let Err(error) = replay.save() {
+                        results.push(format!("Could not save the replay: {}", error));
+                    }
+                }
+            }
+            Some("Export") => {
+                let path = PathBuf::from(format!("snake-rs-{}", replay.get_file_name()));
+                let text = match replay.export(&path) {
+                    Ok(()) => format!("Exported to {}", path.display()),
+                    Err(error) => format!("Could not export the replay: {}", error),
+                };
+                let mut lines = wrap(&text, self.width as usize);
+                lines.extend([String::new(), "Any key: back".to_string()]);
+                self.draw_screen(&lines);
+                self.wait_for_key();
+            }
+            Some("Delete") => {
+                if self.confirm("Delete? y/n") {
+                    if let Err(error) = browser.delete_selected() {
+                        results.push(format!("Could not delete the replay: {}", error));
+                    }
+                }
+            }
+            Some("Back") | None => {}
+            Some(_) => {
+                replay.favorite = !replay.favorite;
+                if let Err(error) = replay.save() {
+                    results.push(format!("Could not save the replay: {}", error));
+                }
+            }
+        }
+    }
+
+    // Note: A tournament turn is always replayed on the round's seed, and playing
+    // a challenge on a new seed leaves the challenge.
+    fn rerun(&mut self, rerun: Rerun) {
+        let seed = if rerun == Rerun::SameSeed || self.player.is_some() {
+            self.state.get_seed()
+        } else {
+            self.challenge = None;
+            rand::thread_rng().gen()
+        };
+        self.restart_with_seed(seed);
+    }
+
+    fn apply_casual_config(&mut self) {
+        if self.config.casual && !self.config.hardcore {
+            self.casual = true;
+            self.state
+                .set_manual_interval(self.config.casual_interval_ms);
+        }
+    }
+
+    // Note: Muting or unmuting the music is remembered in the config straight away.
+    #[cfg(feature = "audio")]
+    fn toggle_music(&mut self) {
+        let music = match &self.music {
+            Some(music) => music,
+            None => {
+                let notice = "No audio player found (aplay, paplay or pw-play)".to_string();
+                self.notice = Some((notice, Instant::now()));
+                return;
+            }
+        };
+        self.config.music = !self.config.music;
+        music.set_muted(!self.config.music);
+        let notice = match Config::save_value("music", &self.config.music.to_string()) {
+            Err(error) => format!("Config not saved: {}", error),
+            Ok(()) if self.config.music => "Music on".to_string(),
+            Ok(()) => "Music off".to_string(),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn toggle_music(&mut self) {
+        let notice = "Music needs the audio feature".to_string();
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    #[cfg(feature = "audio")]
+    fn set_music_intensity(&self, speed: u16) {
+        if let Some(music) = &self.music {
+            music.set_intensity(speed);
+        }
+    }
+
+    // Note: Changing the speed turns the run into casual play, and the new
+    // interval is remembered in the config as the preferred casual speed.
+    fn adjust_casual_interval(&mut self, faster: bool) {
+        // Stepped from the interval before double speed and speed zones scale it.
+        let current = self
+            .state
+            .get_manual_interval()
+            .unwrap_or_else(|| state::speed_interval(self.state.get_speed()).as_millis() as u16);
+        let interval = if faster {
+            current.saturating_sub(CASUAL_INTERVAL_STEP)
+        } else {
+            current.saturating_add(CASUAL_INTERVAL_STEP)
+        };
+        self.casual = true;
+        self.state.set_manual_interval(interval);
+        if let Some(journal) = &mut self.journal {
+            journal.record(Event::Interval(interval));
+        }
+
+        // As clamped, and without double speed or the tile under the head in it.
+        let interval = self.state.get_manual_interval().unwrap_or(interval);
+        self.config.casual_interval_ms = interval;
+        if let Err(error) = Config::save_value("casual_interval_ms", &interval.to_string()) {
+            self.notice = Some((format!("Config not saved: {}", error), Instant::now()));
+        }
+    }
+
+    fn confirm_quit(&mut self) -> bool {
+        self.confirm("Quit? y/n")
+    }
+
+    // Note: This blocks the game loop until the player answers, so the snake
+    // stays put while the prompt is on screen. Ctrl+C still quits straight away.
+    fn confirm(&mut self, question: &str) -> bool {
+        self.draw_message(question);
+        self.wait_for_answer()
+    }
+
+    fn wait_for_answer(&mut self) -> bool {
+        loop {
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::Confirm) => return true,
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return true;
+                }
+                Some(Command::Cancel) | Some(Command::Quit) => return false,
+                _ => {}
+            }
+        }
+    }
+
+    // Note: Focus events are ignored while paused, so switching back to the
+    // terminal does not resume the game on its own; the player has to press a key.
+    // Returns true if the player force quit from the pause screen.
+    fn pause(&mut self) -> bool {
+        self.draw_message("Paused");
+
+        loop {
+            match self.input.next_command(&self.state, DIALOG_POLL_INTERVAL) {
+                Some(Command::ForceQuit) => {
+                    self.force_quit = true;
+                    return true;
+                }
+                Some(Command::ShowRules) => {
+                    let rules = self.state.get_rules().clone();
+                    self.show_rules(&rules);
+                    if self.force_quit {
+                        return true;
+                    }
+                    self.render();
+                    self.draw_message("Paused");
+                }
+                Some(Command::AutoPause) | None => {}
+                Some(_) => return false,
+            }
+        }
+    }
+
+    // Note: Messages from the map's timeline hold the game for a few seconds each,
+    // or until a key is pressed, drawn over the board. Hardcore runs cannot be
+    // held, so they get the messages as notices instead. Returns true on a force quit.
+    fn show_map_messages(&mut self) -> bool {
+        let messages = self.state.get_messages().to_vec();
+        if self.config.hardcore {
+            self.notice = Some((messages.join(" "), Instant::now()));
+            self.render();
+            return false;
+        }
+
+        for message in messages {
+            self.render();
+            let lines = wrap(&message, self.width.saturating_sub(2).max(1) as usize);
+            let width = lines
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0);
+            let top = (self.height + 2).saturating_sub(lines.len() as u16) / 2;
+            let frame = self.renderer.frame_mut();
+            for (i, line) in lines.iter().enumerate() {
+                let line = format!(" {:^width$} ", line, width = width);
+                let x = (self.width + FRAME_EXTRA_WIDTH)
+                    .saturating_sub(line.chars().count() as u16)
+                    / 2;
+                frame.put_str(x, top + i as u16, &line, Color::White);
+            }
+            self.renderer.present();
+
+            let shown_at = Instant::now();
+            while shown_at.elapsed() < MAP_MESSAGE_DURATION {
+                match self.input.next_command(
+                    &self.state,
+                    MAP_MESSAGE_DURATION.saturating_sub(shown_at.elapsed()),
+                ) {
+                    Some(Command::ForceQuit) => {
+                        self.force_quit = true;
+                        return true;
+                    }
+                    Some(Command::AutoPause) | None => {}
+                    Some(_) => break,
+                }
+            }
+        }
+        self.idle.touch();
+        self.discard_profile_frame();
+        self.render();
+        false
+    }
+
+    // Note: Debug builds check the board after every tick, and pause with what
+    // is broken drawn over the board instead of playing on with a corrupt state,
+    // which helps when working on new modes. Continuing is possible, and the
+    // same problems are not reported again in the run. Returns true on a force quit.
+    #[cfg(debug_assertions)]
+    fn pause_on_broken_invariants(&mut self) -> bool {
+        let violations = invariants::check(&self.state);
+        if violations
+            .iter()
+            .all(|violation| self.reported_violations.contains(violation))
+        {
+            return false;
+        }
+
+        self.render();
+        let frame = self.renderer.frame_mut();
+        let title = format!("Broken invariant at tick {}", self.state.get_ticks());
+        frame.put_str(1, 1, &title, Color::Red);
+        for (i, violation) in violations.iter().enumerate() {
+            frame.put_str(1, 2 + i as u16, &violation.to_string(), Color::White);
+        }
+        frame.put_str(
+            1,
+            3 + violations.len() as u16,
+            "Press any key to continue",
+            Color::DarkGrey,
+        );
+        self.renderer.present();
+        self.wait_for_key();
+        self.discard_profile_frame();
+
+        for violation in violations {
+            if !self.reported_violations.contains(&violation) {
+                self.reported_violations.push(violation);
+            }
+        }
+        self.force_quit
+    }
+
+    fn steer_rival(&mut self) {
+        if let (Some(policy), Some(rival)) = (self.rival_policy.as_mut(), self.state.get_rival()) {
+            let direction = policy.choose(&self.state, rival);
+            self.state.turn_rival(direction);
+        }
+    }
+
+    fn should_idle_pause(&self) -> bool {
+        if self.config.hardcore {
+            return false;
+        }
+
+        if self.idle.has_been_idle_for(self.config.idle_pause_secs) {
+            return true;
+        }
+
+        self.idle
+            .has_been_idle_for(self.config.idle_danger_pause_secs)
+            && self.state.is_next_step_fatal()
+    }
+
+    fn render(&mut self) {
+        let started = Instant::now();
+        self.renderer.frame_mut().clear();
+        self.draw_board();
+        self.renderer.present();
+        self.profile(Phase::Rendering, started);
+    }
+
+    // Draws the board with everything on it and the lines below it, without presenting.
+    fn draw_board(&mut self) {
+        self.draw_borders();
+        self.draw_grid();
+        self.draw_tiles();
+        self.draw_territory();
+        if self.show_danger_zone {
+            self.draw_danger_zone();
+        }
+        if self.config.clairvoyance {
+            self.draw_next_food();
+        }
+        self.draw_path_preview();
+        self.draw_laser();
+        self.draw_obstacles();
+        self.draw_boss();
+        self.draw_chain_food();
+        self.draw_rival();
+        self.draw_snake();
+        if self.config.modifiers.contains(Modifier::Fog) {
+            self.draw_fog();
+        }
+        self.draw_replay_keys();
+        self.draw_score();
+        self.draw_status_line();
+        self.draw_panel();
+        if self.show_help {
+            self.draw_help();
+        }
+    }
+
+    fn profile(&mut self, phase: Phase, started: Instant) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.add(phase, started.elapsed());
+        }
+    }
+
+    fn discard_profile_frame(&mut self) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.discard_frame();
+        }
+    }
+
+    // Note: The snake blinks while it is invincible after a respawn. In zen mode
+    // it drifts through soft colors instead, a few segments per color.
+    fn draw_snake(&mut self) {
+        if self.state.is_invincible() && !self.renderer.blink_on() {
+            return;
+        }
+
+        let ramp = match self.config.speed_colors.as_slice() {
+            [] => self.theme().speed_ramp(),
+            colors => colors,
+        };
+        let speed_color = theme::ramp_color(ramp, self.state.get_speed());
+        let skin = self.progress.skin(self.config.skin);
+        let zen_step =
+            (self.renderer.get_elapsed().as_millis() / ZEN_COLOR_INTERVAL.as_millis()) as usize;
+        let zen = self.config.zen;
+
+        let frame = self.renderer.frame_mut();
+        for (i, segment) in self.state.get_snake().get_segments().iter().enumerate() {
+            let color = if zen {
+                ZEN_COLORS[(zen_step + i / 3) % ZEN_COLORS.len()]
+            } else {
+                skin.segment_color(i).unwrap_or(speed_color)
+            };
+            let (glyph, color) = segment_look(i, segment, ('S', 's'), color);
+            frame.put(segment.point.x + 1, segment.point.y + 1, glyph, color);
+        }
+
+        // Note: A turn only takes effect on the next tick, so the head lights up
+        // in the meantime to show the key was taken.
+        if self.config.turn_flash && !self.turns.is_empty() {
+            let head = self.state.get_snake().get_head_point();
+            frame.set_background(head.x + 1, head.y + 1, TURN_FLASH_COLOR);
+        }
+    }
+
+    fn draw_rival(&mut self) {
+        let frame = self.renderer.frame_mut();
+        if let Some(rival) = self.state.get_rival() {
+            for (i, segment) in rival.get_segments().iter().enumerate() {
+                let (glyph, color) = segment_look(i, segment, ('R', 'r'), Color::Magenta);
+                frame.put(segment.point.x + 1, segment.point.y + 1, glyph, color);
+            }
+        }
+    }
+
+    // Note: The celebration cycles a rainbow along the snake for a few seconds
+    // after a perfect game. Any key skips straight to the win screen.
+    fn celebrate(&mut self) {
+        for frame in 0..CELEBRATION_FRAMES {
+            self.draw_rainbow_snake(frame);
+            self.renderer.present();
+            if self
+                .input
+                .next_command(&self.state, CELEBRATION_FRAME_INTERVAL)
+                .is_some()
+            {
+                break;
+            }
+        }
+    }
+
+    fn draw_rainbow_snake(&mut self, offset: usize) {
+        let frame = self.renderer.frame_mut();
+        for (i, segment) in self.state.get_snake().get_segments().iter().enumerate() {
+            let color = RAINBOW[(i + offset) % RAINBOW.len()];
+            let (glyph, _) = segment_look(i, segment, ('S', 's'), color);
+            frame.put(segment.point.x + 1, segment.point.y + 1, glyph, color);
+        }
+    }
+
+    // Note: Snake cells are drawn separately by `draw_snake` so the head can stand out.
+    // Note: Food pulses gently between bright and dim so it is easy to spot, and
+    // when it is about to expire it flashes red and disappears in turn instead.
+    fn draw_grid(&mut self) {
+        let expiring = self
+            .state
+            .get_food_time_left()
+            .is_some_and(|time_left| time_left <= FOOD_EXPIRY_WARNING);
+        let pulse_on = self.renderer.pulse_on(if expiring {
+            EXPIRING_FOOD_PULSE_FRAMES
+        } else {
+            FOOD_PULSE_FRAMES
+        });
+        // Food is what hurts the boss, so it looks different while there is one.
+        let boss = self.state.get_boss().is_some();
+        let food = match (expiring, pulse_on) {
+            (true, true) if boss => ('+', Color::Red),
+            (true, true) => ('A', Color::Red),
+            (true, false) => (' ', Color::Reset),
+            (false, true) if boss => ('+', Color::Yellow),
+            (false, false) if boss => ('+', Color::DarkYellow),
+            (false, true) => ('A', Color::White),
+            (false, false) => ('A', Color::Grey),
+        };
+        let background = self.theme().background();
+
+        let frame = self.renderer.frame_mut();
+        for (point, cell) in self.state.get_grid().points() {
+            let (glyph, color) = match cell {
+                Cell::Empty | Cell::Snake => (' ', Color::Reset),
+                Cell::Food => food,
+                Cell::Obstacle => ('#', Color::DarkGrey),
+                Cell::Portal => ('O', Color::Magenta),
+            };
+            frame.put(point.x + 1, point.y + 1, glyph, color);
+            frame.set_background(point.x + 1, point.y + 1, background);
+        }
+    }
+
+    // Note: The food that is due is highlighted; the rest of the chain is dimmed.
+    fn draw_chain_food(&mut self) {
+        let frame = self.renderer.frame_mut();
+        for (i, food) in self.state.get_chain_food().iter().enumerate() {
+            let color = if i == 0 {
+                Color::White
+            } else {
+                Color::DarkGrey
+            };
+            frame.put(food.point.x + 1, food.point.y + 1, food.get_label(), color);
+        }
+    }
+
+    // Note: The warning blinks the line the laser will sweep; once it fires the
+    // beam fills the line, drawn underneath whatever is in it.
+    fn draw_laser(&mut self) {
+        let laser = match self.state.get_laser() {
+            Some(laser) => *laser,
+            None => return,
+        };
+        let blink_on = self.renderer.blink_on();
+        let frame = self.renderer.frame_mut();
+        for (point, cell) in self.state.get_grid().points() {
+            if !laser.get_beam().covers(&point) {
+                continue;
+            }
+            let (x, y) = (point.x + 1, point.y + 1);
+            match laser.get_phase() {
+                LaserPhase::Warning if blink_on => frame.set_background(x, y, LASER_WARNING_COLOR),
+                LaserPhase::Warning => {}
+                LaserPhase::Firing => {
+                    frame.set_background(x, y, LASER_BEAM_COLOR);
+                    if cell == Cell::Empty {
+                        frame.put(x, y, ' ', Color::White);
+                    }
+                }
+            }
+        }
+    }
+
+    // Note: The boss shows how many hits it has left on its top left cell.
+    fn draw_boss(&mut self) {
+        let boss = match self.state.get_boss() {
+            Some(boss) => boss,
+            None => return,
+        };
+        let frame = self.renderer.frame_mut();
+        for point in boss.cells() {
+            frame.put(point.x + 1, point.y + 1, ' ', Color::White);
+            frame.set_background(point.x + 1, point.y + 1, BOSS_COLOR);
+        }
+        let origin = boss.get_origin();
+        let hits_left = (BOSS_HITS - boss.get_hits()) as u32;
+        frame.put(
+            origin.x + 1,
+            origin.y + 1,
+            char::from_digit(hits_left, 10).unwrap_or('!'),
+            Color::White,
+        );
+    }
+
+    fn draw_obstacles(&mut self) {
+        let frame = self.renderer.frame_mut();
+        for obstacle in self.state.get_obstacles() {
+            let point = obstacle.get_point();
+            frame.put(point.x + 1, point.y + 1, 'X', Color::Red);
+        }
+    }
+
+    // Note: Speed and safe zones shade the background, empty one-way tiles show their arrow,
+    // and keys and the doors they open share a letter and a color.
+    fn draw_tiles(&mut self) {
+        let frame = self.renderer.frame_mut();
+        for (point, cell) in self.state.get_grid().points() {
+            let (x, y) = (point.x + 1, point.y + 1);
+            match self.state.get_grid().get_tile(&point) {
+                Tile::Mud => frame.set_background(x, y, MUD_COLOR),
+                Tile::Ice => frame.set_background(x, y, ICE_COLOR),
+                Tile::Safe => frame.set_background(x, y, SAFE_ZONE_COLOR),
+                Tile::OneWay(direction) if cell == Cell::Empty => {
+                    frame.put(x, y, direction_arrow(direction), Color::DarkGrey)
+                }
+                Tile::Key(key) if cell == Cell::Empty => frame.put(
+                    x,
+                    y,
+                    key_label(key),
+                    KEY_COLORS[key as usize % KEY_COLORS.len()],
+                ),
+                Tile::Door(key) if cell == Cell::Empty => {
+                    frame.put(x, y, key_label(key).to_ascii_uppercase(), Color::Black);
+                    frame.set_background(x, y, KEY_COLORS[key as usize % KEY_COLORS.len()]);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw_territory(&mut self) {
+        let frame = self.renderer.frame_mut();
+        for (point, _) in self.state.get_grid().points() {
+            let color = match self.state.get_grid().get_owner(&point) {
+                Some(Owner::Player) => PLAYER_TERRITORY_COLOR,
+                Some(Owner::Rival) => RIVAL_TERRITORY_COLOR,
+                None => continue,
+            };
+            frame.set_background(point.x + 1, point.y + 1, color);
+        }
+    }
+
+    fn draw_danger_zone(&mut self) {
+        let frame = self.renderer.frame_mut();
+        for point in self.state.get_grid().danger_zone() {
+            frame.set_background(point.x + 1, point.y + 1, DANGER_ZONE_COLOR);
+        }
+    }
+
+    fn draw_next_food(&mut self) {
+        if let Some(point) = self.state.get_next_food() {
+            self.renderer
+                .frame_mut()
+                .put(point.x + 1, point.y + 1, '.', Color::DarkGrey);
+        }
+    }
+
+    // Note: The path preview follows the queued turns. Without the assist, an
+    // L-shaped turn of two or more queued keys still shows the cells it takes
+    // the snake through.
+    fn draw_path_preview(&mut self) {
+        let planned = self.turns.planned(self.state.get_snake().get_direction());
+        let ticks = match self.config.path_preview {
+            0 if planned.len() >= 2 => planned.len() as u16,
+            0 => return,
+            ticks => ticks,
+        };
+        let (path, fatal) = self.state.preview_turns(ticks, &planned);
+        let color = if fatal {
+            Color::DarkRed
+        } else {
+            Color::DarkGrey
+        };
+
+        let frame = self.renderer.frame_mut();
+        for point in path {
+            frame.put(point.x + 1, point.y + 1, '·', color);
+        }
+    }
+
+    // Note: Covers everything but a circle around the snake's head, which is drawn
+    // over what is already in the frame, the snake's own body included.
+    fn draw_fog(&mut self) {
+        let head = self.state.get_snake().get_head_point();
+        let background = self.theme().background();
+        let frame = self.renderer.frame_mut();
+        for (point, _) in self.state.get_grid().points() {
+            let (dx, dy) = head.offset_to(&point);
+            if dx * dx + dy * dy > FOG_RADIUS * FOG_RADIUS {
+                frame.put(point.x + 1, point.y + 1, ' ', Color::Reset);
+                frame.set_background(point.x + 1, point.y + 1, background);
+            }
+        }
+    }
+
+    fn draw_borders(&mut self) {
+        let (right, bottom) = (self.width + 1, self.height + 1);
+        let color = self.theme().border_color();
+        // The border is open when the snake wraps around.
+        let glyph = if self.state.get_rules().wraps_around() {
+            '.'
+        } else {
+            '#'
+        };
+        let frame = self.renderer.frame_mut();
+        for y in 0..=bottom {
+            frame.put(0, y, glyph, color);
+            frame.put(right, y, glyph, color);
+        }
+        for x in 0..=right {
+            frame.put(x, 0, glyph, color);
+            frame.put(x, bottom, glyph, color);
+        }
+    }
+
+    fn theme(&self) -> Theme {
+        if self.config.hardcore {
+            Theme::Hardcore
+        } else {
+            self.progress.theme(self.config.theme)
+        }
+    }
+
+    fn draw_message(&mut self, message: &str) {
+        let x = (self.width + FRAME_EXTRA_WIDTH).saturating_sub(message.len() as u16) / 2;
+        let y = (self.height + 2) / 2;
+        self.renderer
+            .frame_mut()
+            .put_str(x, y, message, Color::White);
+        self.renderer.present();
+    }
+
+    fn draw_menu(&mut self, menu: &Menu) {
+        let item_width = menu
+            .get_items()
+            .iter()
+            .map(|item| item.len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec![menu.get_title().to_string(), String::new()];
+        for (i, item) in menu.get_items().iter().enumerate() {
+            let marker = if i == menu.get_selected() { '>' } else { ' ' };
+            lines.push(format!("{} {:<width$}", marker, item, width = item_width));
+        }
+        lines.push(String::new());
+        lines.push(menu.get_hint().to_string());
+
+        self.draw_screen(&lines);
+    }
+
+    // Note: Clears everything inside the borders and centres the lines on the board.
+    fn draw_screen(&mut self, lines: &[String]) {
+        self.renderer.frame_mut().clear();
+        self.draw_borders();
+
+        let top = (self.height + 2).saturating_sub(lines.len() as u16) / 2;
+        let frame = self.renderer.frame_mut();
+        for (i, line) in lines.iter().enumerate() {
+            let x =
+                (self.width + FRAME_EXTRA_WIDTH).saturating_sub(line.chars().count() as u16) / 2;
+            frame.put_str(x, top + i as u16, line, Color::White);
+        }
+        self.renderer.present();
+    }
+
+    // Note: Zen mode has no score to chase, so it shows how long the snake is instead.
+    fn draw_score(&mut self) {
+        let mut line = std::mem::take(&mut self.line);
+        line.clear();
+        if self.config.zen {
+            write!(line, "Zen  Length: {}", self.state.get_snake().len()).unwrap();
+            self.renderer
+                .frame_mut()
+                .put_str(0, self.height + 2, &line, ZEN_COLORS[0]);
+            self.line = line;
+            return;
+        }
+
+        if let Some(player) = &self.player {
+            write!(line, "{}  ", player).unwrap();
+        }
+        line.push_str("Score: ");
+        self.push_score_label(&mut line, self.state.get_score());
+        let percent = self.config.modifiers.score_percent();
+        if percent != 100 {
+            write!(line, " {}", Multiplier(percent)).unwrap();
+        }
+        if self.config.hint_arrow {
+            if let Some(arrow) = self.food_hint_arrow() {
+                line.push(' ');
+                line.push(arrow);
+            }
+        }
+        if self.config.rival_ai.is_some() {
+            line.push_str("  Rival: ");
+            self.push_score_label(&mut line, self.state.get_rival_score());
+        }
+        if self.config.risk {
+            line.push_str("  At risk: ");
+            self.push_score_label(&mut line, self.state.get_at_risk());
+        }
+        if self.config.lives > 1 {
+            write!(line, "  Lives: {}", self.state.get_lives()).unwrap();
+        }
+        if let Some(boss) = self.state.get_boss() {
+            write!(line, "  Boss: {}/{}", boss.get_hits(), BOSS_HITS).unwrap();
+        }
+        let keys = self.state.get_snake().get_keys();
+        if !keys.is_empty() {
+            line.push_str("  Keys: ");
+            line.extend(keys.iter().map(|key| key_label(*key)));
+        }
+        if let Some(time_left) = self.state.get_time_left() {
+            write!(line, "  {}s", time_left.as_secs()).unwrap();
+        }
+        if self.casual {
+            write!(
+                line,
+                "  Casual {}ms",
+                self.state.calculate_interval().as_millis()
+            )
+            .unwrap();
+        }
+        self.renderer
+            .frame_mut()
+            .put_str(0, self.height + 2, &line, Color::White);
+        self.line = line;
+    }
+
+    fn score_label(&self, score: u64) -> String {
+        let mut label = String::new();
+        self.push_score_label(&mut label, score);
+        label
+    }
+
+    // Territory scores are percentages of the board.
+    fn push_score_label(&self, line: &mut String, score: u64) {
+        write!(line, "{}", score).unwrap();
+        if self.state.get_time_left().is_some() {
+            line.push('%');
+        }
+    }
+
+    // Note: A cross of arrow keys in the board's top right corner, over whatever
+    // is there, with the ones pressed lit up.
+    fn draw_replay_keys(&mut self) {
+        let keys = match &self.replay_keys {
+            Some(keys) if self.width >= 3 => keys,
+            _ => return,
+        };
+
+        let right = self.width;
+        let frame = self.renderer.frame_mut();
+        frame.put(right - 2, 1, ' ', Color::Reset);
+        frame.put(right, 1, ' ', Color::Reset);
+        for (direction, x, y) in [
+            (Direction::Up, right - 1, 1),
+            (Direction::Left, right - 2, 2),
+            (Direction::Down, right - 1, 2),
+            (Direction::Right, right, 2),
+        ] {
+            let color = if keys.contains(&direction) {
+                Color::White
+            } else {
+                Color::DarkGrey
+            };
+            frame.put(x, y, direction_arrow(direction), color);
+        }
+    }
+
+    // Note: Points from the snake's head towards the food, diagonally if the food
+    // is not in the same row or column.
+    fn food_hint_arrow(&self) -> Option<char> {
+        let food = self.state.get_food()?;
+        let (dx, dy) = self.state.get_snake().get_head_point().offset_to(&food);
+
+        match (dx.signum(), dy.signum()) {
+            (0, -1) => Some('↑'),
+            (1, -1) => Some('↗'),
+            (1, 0) => Some('→'),
+            (1, 1) => Some('↘'),
+            (0, 1) => Some('↓'),
+            (-1, 1) => Some('↙'),
+            (-1, 0) => Some('←'),
+            (-1, -1) => Some('↖'),
+            _ => None,
+        }
+    }
+
+    // Note: The last row shows a short-lived notice if there is one,
+    // and otherwise the debug info when it is turned on.
+    fn draw_status_line(&mut self) {
+        let y = self.height + 3;
+        if let Some((notice, _)) = &self.notice {
+            self.renderer
+                .frame_mut()
+                .put_str(0, y, notice, Color::White);
+            return;
+        }
+        if let Some(line) = &self.playback_line {
+            self.renderer.frame_mut().put_str(0, y, line, Color::White);
+            return;
+        }
+
+        if !self.show_debug {
+            return;
+        }
+
+        let mut line = std::mem::take(&mut self.line);
+        line.clear();
+        write!(
+            line,
+            "t:{} len:{} {:.1}tps",
+            self.state.get_ticks(),
+            self.state.get_snake().len(),
+            self.state.get_ticks_per_second()
+        )
+        .unwrap();
+        // Note: How steady the ticks actually are: their average interval and the
+        // ticks per second that makes, then how far off they were on average and
+        // at worst, e.g. `~128.3 7.8tps ±0.9/3.1`.
+        if let (Some(average), Some((jitter, worst))) =
+            (self.pacer.get_average_interval(), self.pacer.get_jitter())
+        {
+            write!(
+                line,
+                " ~{:.1} {:.1}tps ±{:.1}/{:.1}",
+                millis(average),
+                1.0 / average.as_secs_f64().max(f64::EPSILON),
+                millis(jitter),
+                millis(worst)
+            )
+            .unwrap();
+        }
+        self.renderer
+            .frame_mut()
+            .put_str(0, y, &line, Color::DarkGrey);
+        self.line = line;
+    }
+
+    fn draw_panel(&mut self) {
+        let x = self.width + 4;
+        let lines = self.input.panel();
+        let frame = self.renderer.frame_mut();
+        for (i, line) in lines.iter().enumerate() {
+            frame.put_str(x, i as u16, line, Color::White);
+        }
+    }
+
+    fn draw_help(&mut self) {
+        let frame = self.renderer.frame_mut();
+        for (i, line) in HELP_LINES.iter().enumerate() {
+            frame.put_str(2, 2 + i as u16, line, Color::White);
+        }
+    }
+}
+
+// Note: Dropped on the way out of a panic too, e.g. when the terminal went away
+// mid-run and drawing failed, so the run being played is still saved.
+impl Drop for Game {
+    fn drop(&mut self) {
+        if let Some(journal) = &self.journal {
+            if journal.get_steps() > 0 {
+                journal.save().ok();
+            }
+        }
+    }
+}
+
+fn validate_board(width: u16, height: u16, (columns, rows): (u16, u16)) -> Result<(), BoardError> {
+    if width == 0 || height == 0 {
+        return Err(BoardError::NoCells { width, height });
+    }
+    if width < MIN_BOARD_WIDTH || height < MIN_BOARD_HEIGHT {
+        return Err(BoardError::TooSmall { width, height });
+    }
+    if width.saturating_add(FRAME_EXTRA_WIDTH) > columns
+        || height.saturating_add(FRAME_EXTRA_HEIGHT) > rows
+    {
+        return Err(BoardError::TooLarge {
+            width,
+            height,
+            columns,
+            rows,
+        });
+    }
+
+    Ok(())
+}
+
+fn direction_arrow(direction: Direction) -> char {
+    match direction {
+        Direction::Up => '↑',
+        Direction::Right => '→',
+        Direction::Down => '↓',
+        Direction::Left => '←',
+    }
+}
+
+fn key_label(key: u8) -> char {
+    (b'a' + key) as char
+}
+
+fn plural(count: u8, one: &'static str, many: &'static str) -> &'static str {
+    if count == 1 {
+        one
+    } else {
+        many
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Note: The head uses the first glyph and plain segments the second.
+fn segment_look(
+    index: usize,
+    segment: &Segment,
+    glyphs: (char, char),
+    color: Color,
+) -> (char, Color) {
+    if index == 0 {
+        return (glyphs.0, color);
+    }
+
+    match segment.kind {
+        SegmentKind::Normal => (glyphs.1, color),
+    }
+}
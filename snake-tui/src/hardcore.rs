@@ -0,0 +1,131 @@
+use crate::about::RunInfo;
+use crate::savefile::{self, SaveFile, SaveLock};
+use std::io;
+
+const HARDCORE_FILE: SaveFile = SaveFile::new("hardcore", &[savefile::unchanged]);
+const MAX_ENTRIES: usize = 10;
+
+// Note: Every hardcore run ends in a death, so the leaderboard keeps the details
+// of how each of the best ones ended.
+#[derive(Debug, Clone)]
+pub struct Death {
+    pub score: u64,
+    pub length: usize,
+    pub ticks: u64,
+    pub secs: u64,
+    // What the snake crashed into, e.g. `border` or `self`.
+    pub cause: String,
+    pub run: RunInfo,
+}
+
+impl Death {
+    // Note: One line per death, the run info followed by the details,
+    // e.g. `version=0.1.0 seed=1f2e mode=hardcore board=20x20 score=12 length=14 ticks=300 secs=21 cause=self`.
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} score={} length={} ticks={} secs={} cause={}",
+            self.run.to_line(),
+            self.score,
+            self.length,
+            self.ticks,
+            self.secs,
+            self.cause
+        )
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut death = Self {
+            score: 0,
+            length: 0,
+            ticks: 0,
+            secs: 0,
+            cause: String::new(),
+            run: RunInfo::parse(line)?,
+        };
+        for field in line.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            match key {
+                "score" => death.score = value.parse().ok()?,
+                "length" => death.length = value.parse().ok()?,
+                "ticks" => death.ticks = value.parse().ok()?,
+                "secs" => death.secs = value.parse().ok()?,
+                "cause" => death.cause = value.to_string(),
+                _ => {}
+            }
+        }
+
+        Some(death)
+    }
+}
+
+// The best hardcore runs, highest score first.
+#[derive(Debug, Default)]
+pub struct Leaderboard {
+    pub deaths: Vec<Death>,
+}
+
+impl Leaderboard {
+    pub fn load() -> Self {
+        HARDCORE_FILE
+            .load()
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    // The whole file, header included, as it goes into a profile bundle.
+    pub fn to_file(&self) -> String {
+        HARDCORE_FILE.with_header(&self.to_contents())
+    }
+
+    pub fn from_file(contents: &str) -> Self {
+        Self::parse(&HARDCORE_FILE.read(contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        Self {
+            deaths: contents.lines().filter_map(Death::parse).collect(),
+        }
+    }
+
+    // Held from loading until saving, see SaveFile::lock.
+    pub fn lock() -> io::Result<SaveLock> {
+        HARDCORE_FILE.lock()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        HARDCORE_FILE.save(&self.to_contents())
+    }
+
+    fn to_contents(&self) -> String {
+        self.deaths
+            .iter()
+            .map(|death| format!("{}\n", death.to_line()))
+            .collect()
+    }
+
+    // Deaths that are on both leaderboards are only kept once.
+    pub fn merge(&mut self, other: Self) {
+        for death in other.deaths {
+            let line = death.to_line();
+            if !self.deaths.iter().any(|known| known.to_line() == line) {
+                self.record(death);
+            }
+        }
+    }
+
+    // Returns the place the death took on the leaderboard, if it made it on.
+    pub fn record(&mut self, death: Death) -> Option<usize> {
+        let place = self
+            .deaths
+            .iter()
+            .position(|entry| death.score > entry.score)
+            .unwrap_or(self.deaths.len());
+        if place >= MAX_ENTRIES {
+            return None;
+        }
+
+        self.deaths.insert(place, death);
+        self.deaths.truncate(MAX_ENTRIES);
+        Some(place)
+    }
+}
@@ -0,0 +1,402 @@
+#[cfg(feature = "async")]
+use crate::async_input::AsyncInput;
+use crate::command::Command;
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use snake_core::direction::Direction;
+use snake_core::state::GameState;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How long the keyboard is waited on at a time, and so about how long a signal
+// takes to be noticed.
+pub const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static TERMINATED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+static FEED: OnceLock<Mutex<Feed>> = OnceLock::new();
+
+// Note: Anything that can drive the game implements this trait, so the keyboard,
+// bots, network peers and replays are interchangeable. Implementations may block
+// for up to `wait_for` and get to look at the current state before deciding.
+pub trait InputSource: Debug {
+    fn next_command(&mut self, state: &GameState, wait_for: Duration) -> Option<Command>;
+
+    // Lines shown in a panel to the right of the board, if the source has any.
+    fn panel(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// Note: The keys one of the players sharing the keyboard steers with. Letters
+// work without shift too, and while players share the keyboard their keys only
+// steer, e.g. I does not show the rules with IJKL in use.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KeyProfile {
+    Arrows,
+    Wasd,
+    Ijkl,
+    // The arrows printed on the number pad's 8, 6, 2 and 4.
+    Numpad,
+}
+
+impl KeyProfile {
+    pub fn all() -> [Self; 4] {
+        [Self::Arrows, Self::Wasd, Self::Ijkl, Self::Numpad]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|profile| profile.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Arrows => "arrows",
+            Self::Wasd => "wasd",
+            Self::Ijkl => "ijkl",
+            Self::Numpad => "numpad",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Arrows => "Arrows",
+            Self::Wasd => "WASD",
+            Self::Ijkl => "IJKL",
+            Self::Numpad => "Numpad",
+        }
+    }
+
+    // The keys for up, right, down and left.
+    pub fn keys(&self) -> [KeyCode; 4] {
+        match self {
+            Self::Arrows => [KeyCode::Up, KeyCode::Right, KeyCode::Down, KeyCode::Left],
+            Self::Wasd => ['w', 'd', 's', 'a'].map(KeyCode::Char),
+            Self::Ijkl => ['i', 'l', 'k', 'j'].map(KeyCode::Char),
+            Self::Numpad => ['8', '6', '2', '4'].map(KeyCode::Char),
+        }
+    }
+
+    pub fn direction(&self, code: KeyCode) -> Option<Direction> {
+        let code = match code {
+            KeyCode::Char(glyph) => KeyCode::Char(glyph.to_ascii_lowercase()),
+            code => code,
+        };
+        let directions = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+        self.keys()
+            .iter()
+            .position(|key| *key == code)
+            .map(|i| directions[i])
+    }
+
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.keys().iter().any(|key| other.keys().contains(key))
+    }
+}
+
+// The first two players whose keys overlap, if any do.
+pub fn find_conflict(profiles: &[KeyProfile]) -> Option<(usize, usize)> {
+    for (i, profile) in profiles.iter().enumerate() {
+        for (j, other) in profiles.iter().enumerate().skip(i + 1) {
+            if profile.conflicts_with(other) {
+                return Some((i, j));
+            }
+        }
+    }
+
+    None
+}
+
+#[derive(Debug, Default)]
+pub struct KeyboardInput {
+    // The keys of each player when several share the keyboard.
+    players: Vec<KeyProfile>,
+}
+
+impl KeyboardInput {
+    pub fn new() -> Self {
+        Self {
+            players: Vec::new(),
+        }
+    }
+
+    // Note: For players sharing the keyboard: each player's keys turn their own
+    // snake, and every other key works as usual.
+    pub fn split(players: &[KeyProfile]) -> Self {
+        Self {
+            players: players.to_vec(),
+        }
+    }
+
+    fn map_key_event(&self, key_event: KeyEvent) -> Option<Command> {
+        for (player, profile) in self.players.iter().enumerate() {
+            if let Some(direction) = profile.direction(key_event.code) {
+                return Some(Command::TurnPlayer(player, direction));
+            }
+        }
+
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => Some(Command::Quit),
+            KeyCode::Char('c') | KeyCode::Char('C') => {
+                if key_event.modifiers == KeyModifiers::CONTROL {
+                    Some(Command::ForceQuit)
+                } else {
+                    None
+                }
+            }
+            KeyCode::Char('p') | KeyCode::Char('P') => Some(Command::Pause),
+            KeyCode::Char('r') => Some(Command::Restart),
+            KeyCode::Char('R') => Some(Command::RestartSameSeed),
+            KeyCode::Char('h') | KeyCode::Char('H') | KeyCode::Char('?') => {
+                Some(Command::ToggleHelp)
+            }
+            KeyCode::Char('i') | KeyCode::Char('I') => Some(Command::ShowRules),
+            KeyCode::F(3) => Some(Command::ToggleDebug),
+            KeyCode::Char('z') | KeyCode::Char('Z') => Some(Command::ToggleDangerZone),
+            KeyCode::Char('m') | KeyCode::Char('M') => Some(Command::ToggleMusic),
+            KeyCode::F(12) => Some(Command::Screenshot),
+            KeyCode::Char('+') | KeyCode::Char('=') => Some(Command::SpeedUp),
+            KeyCode::Char('-') | KeyCode::Char('_') => Some(Command::SpeedDown),
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Some(Command::Confirm),
+            KeyCode::Char('n') | KeyCode::Char('N') => Some(Command::Cancel),
+            KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => {
+                Some(Command::Turn(Direction::Up))
+            }
+            KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('D') => {
+                Some(Command::Turn(Direction::Right))
+            }
+            KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('S') => {
+                Some(Command::Turn(Direction::Down))
+            }
+            KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('A') => {
+                Some(Command::Turn(Direction::Left))
+            }
+            _ => None,
+        }
+    }
+}
+
+// What keyboard inputs wait on: the terminal's events, or a command that did
+// not come from a key.
+#[derive(Debug)]
+pub enum Input {
+    Terminal(Event),
+    Command(Command),
+}
+
+// Where the inputs come from, the same for every keyboard input.
+#[derive(Debug)]
+enum Feed {
+    Reader(Receiver<Event>),
+    #[cfg(feature = "async")]
+    Async(AsyncInput),
+}
+
+impl Feed {
+    fn next(&mut self, wait_for: Duration) -> Option<Input> {
+        match self {
+            Feed::Reader(events) => next_from_reader(events, wait_for),
+            #[cfg(feature = "async")]
+            Feed::Async(input) => input.next(wait_for),
+        }
+    }
+}
+
+fn next_from_reader(events: &Receiver<Event>, wait_for: Duration) -> Option<Input> {
+    let deadline = Instant::now() + wait_for;
+    loop {
+        if is_terminated() {
+            return Some(Input::Command(Command::ForceQuit));
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        let wait = left.min(SIGNAL_POLL_INTERVAL);
+        match events.recv_timeout(wait) {
+            Ok(event) => return Some(Input::Terminal(event)),
+            // Once the terminal cannot be read any more, there is only waiting left.
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(wait),
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        if left <= SIGNAL_POLL_INTERVAL {
+            return None;
+        }
+    }
+}
+
+// Note: Being told to stop, e.g. by `kill` or by the terminal closing, comes in
+// as Ctrl+C, so the run being played is saved on the way out like any other
+// quit instead of the process just ending.
+pub fn is_terminated() -> bool {
+    TERMINATED
+        .get_or_init(|| {
+            let flag = Arc::new(AtomicBool::new(false));
+            let mut signals = signal_hook::consts::TERM_SIGNALS.to_vec();
+            #[cfg(unix)]
+            signals.push(signal_hook::consts::SIGHUP);
+            for signal in signals {
+                signal_hook::flag::register(signal, Arc::clone(&flag)).ok();
+            }
+            flag
+        })
+        .load(Ordering::Relaxed)
+}
+
+// Note: Unless input is read on a tokio runtime, the terminal is read on a
+// thread of its own, started the first time it is needed and shared by every
+// keyboard input, so keys are taken in as soon as they come, however long a
+// tick or a frame takes.
+fn feed() -> &'static Mutex<Feed> {
+    FEED.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(event) = read() {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(Feed::Reader(receiver))
+    })
+}
+
+// Has every keyboard input wait on the tokio runtime from now on. Does nothing
+// once the terminal has been read.
+#[cfg(feature = "async")]
+pub fn use_async_input(input: AsyncInput) {
+    FEED.set(Mutex::new(Feed::Async(input))).ok();
+}
+
+impl InputSource for KeyboardInput {
+    fn next_command(&mut self, _state: &GameState, wait_for: Duration) -> Option<Command> {
+        match feed().lock().unwrap().next(wait_for)? {
+            Input::Terminal(Event::Key(key_event)) if key_event.kind == KeyEventKind::Press => {
+                self.map_key_event(key_event)
+            }
+            Input::Terminal(Event::FocusLost) => Some(Command::AutoPause),
+            Input::Terminal(_) => None,
+            Input::Command(command) => Some(command),
+        }
+    }
+}
+
+// Note: Holding a key down makes the terminal repeat the same key event many
+// times per tick, so identical turns are collapsed and the queue is capped.
+// Only one turn is taken from the queue per tick, which keeps the snake from
+// turning twice (and straight back into itself) within a single step.
+const MAX_QUEUED_TURNS: usize = 3;
+// Keys at most this far apart were pressed together, for a diagonal.
+const DIAGONAL_WINDOW: Duration = Duration::from_millis(60);
+
+// Each turn is kept with when its key was received.
+#[derive(Debug, Default)]
+pub struct TurnQueue {
+    turns: VecDeque<(Direction, Instant)>,
+}
+
+impl TurnQueue {
+    pub fn new() -> Self {
+        Self {
+            turns: VecDeque::new(),
+        }
+    }
+
+    // Returns false when the turn was dropped.
+    pub fn push(&mut self, direction: Direction) -> bool {
+        let repeated = self
+            .turns
+            .back()
+            .is_some_and(|(queued, _)| *queued == direction);
+        if repeated || self.turns.len() >= MAX_QUEUED_TURNS {
+            return false;
+        }
+
+        self.turns.push_back((direction, Instant::now()));
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+
+    // Returns the next turn that changes the direction, with when it was received.
+    pub fn next_turn(&mut self, current: Direction) -> Option<(Direction, Instant)> {
+        self.order_diagonal(current);
+        while let Some((towards, received)) = self.turns.pop_front() {
+            if towards != current && towards != current.opposite() {
+                return Some((towards, received));
+            }
+        }
+
+        None
+    }
+
+    // The directions the queued turns will take the snake in, one per tick.
+    pub fn planned(&self, current: Direction) -> Vec<Direction> {
+        let mut queue = Self {
+            turns: self.turns.clone(),
+        };
+        let mut heading = current;
+        let mut planned = Vec::with_capacity(queue.turns.len());
+        while let Some((towards, _)) = queue.next_turn(heading) {
+            planned.push(towards);
+            heading = towards;
+        }
+
+        planned
+    }
+
+    // Note: Two keys pressed together for a diagonal arrive in either order. When
+    // the first would reverse the snake and the second turns it, they are taken
+    // the other way round, so Left and Up together while heading right go up and
+    // then left: an L-shaped turn over two ticks instead of just going up.
+    fn order_diagonal(&mut self, current: Direction) {
+        if let (Some(&(first, pressed)), Some(&(second, then))) =
+            (self.turns.front(), self.turns.get(1))
+        {
+            let turns_aside = second != current && second != current.opposite();
+            if first == current.opposite()
+                && turns_aside
+                && then.duration_since(pressed) <= DIAGONAL_WINDOW
+            {
+                self.turns.swap(0, 1);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IdleTimer {
+    last_input: Instant,
+}
+
+impl Default for IdleTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdleTimer {
+    pub fn new() -> Self {
+        Self {
+            last_input: Instant::now(),
+        }
+    }
+
+    pub fn touch(&mut self) {
+        self.last_input = Instant::now();
+    }
+
+    pub fn has_been_idle_for(&self, secs: u64) -> bool {
+        secs > 0 && self.last_input.elapsed() >= Duration::from_secs(secs)
+    }
+}
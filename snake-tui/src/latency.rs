@@ -0,0 +1,56 @@
+use crate::profiler::summary_line;
+use std::time::{Duration, Instant};
+
+// Note: Measures how long a turn takes to show up, from the moment its key was
+// received: until the tick that applies it has been simulated, and until that
+// tick has been drawn. Turns that were dropped or did not change the direction
+// are not counted, and only one turn is applied per tick.
+#[derive(Debug, Default)]
+pub struct LatencyMeter {
+    // When the turn applied this tick was received and when the tick was simulated.
+    pending: Option<(Instant, Instant)>,
+    to_tick: Vec<Duration>,
+    to_render: Vec<Duration>,
+}
+
+impl LatencyMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn turn_applied(&mut self, received: Instant) {
+        self.pending = Some((received, Instant::now()));
+    }
+
+    // Called once the tick has been drawn.
+    pub fn tick_rendered(&mut self) {
+        if let Some((received, simulated)) = self.pending.take() {
+            self.to_tick.push(simulated.duration_since(received));
+            self.to_render.push(received.elapsed());
+        }
+    }
+
+    // Drops a turn whose tick is not going to be drawn, e.g. the one the run ended on.
+    pub fn discard(&mut self) {
+        self.pending = None;
+    }
+
+    pub fn report(&self) -> Vec<String> {
+        if self.to_tick.is_empty() {
+            return vec!["Input latency: no turns were made".to_string()];
+        }
+
+        vec![
+            format!(
+                "Input latency of {} turns (microseconds from the key):",
+                self.to_tick.len()
+            ),
+            format!(
+                "{:<12}{:>8}{:>8}{:>8}{:>8}{:>8}",
+                "", "mean", "p50", "p90", "p99", "max"
+            ),
+            summary_line("tick", self.to_tick.clone()),
+            summary_line("rendered", self.to_render.clone()),
+        ]
+    }
+}
@@ -0,0 +1,99 @@
+use crate::campaign::Progress;
+use crate::config::Config;
+use crate::emergency::Journal;
+#[cfg(feature = "audio")]
+use crate::music::Music;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+// Something loading on a thread of its own.
+#[derive(Debug)]
+pub struct Job<T> {
+    receiver: Receiver<T>,
+    value: Option<T>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    pub fn spawn(load: impl FnOnce() -> T + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || sender.send(load()).ok());
+        Self {
+            receiver,
+            value: None,
+        }
+    }
+
+    pub fn is_done(&mut self) -> bool {
+        if self.value.is_none() {
+            self.value = self.receiver.try_recv().ok();
+        }
+        self.value.is_some()
+    }
+
+    // Waits for it if it is not done yet.
+    pub fn wait(self) -> T {
+        match self.value {
+            Some(value) => value,
+            None => self.receiver.recv().unwrap(),
+        }
+    }
+}
+
+// Note: Everything the game reads from disk or starts up before the menu, all
+// loading at once while the splash screen is drawn. The config is not part of
+// it, since it decides the board before there is a game, and stats and scores
+// are read when a run is recorded, under their locks.
+#[derive(Debug)]
+pub struct Startup {
+    progress: Job<Progress>,
+    interrupted: Job<Option<Journal>>,
+    #[cfg(feature = "audio")]
+    music: Job<Option<Music>>,
+}
+
+// What the startup loaded.
+#[derive(Debug)]
+pub struct Loaded {
+    pub progress: Progress,
+    // The run that was going when the game was last left without quitting.
+    pub interrupted: Option<Journal>,
+    #[cfg(feature = "audio")]
+    pub music: Option<Music>,
+}
+
+impl Startup {
+    #[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+    pub fn start(config: &Config) -> Self {
+        #[cfg(feature = "audio")]
+        let (volume, muted) = (config.music_volume, !config.music);
+        Self {
+            progress: Job::spawn(Progress::load),
+            interrupted: Job::spawn(Journal::load),
+            #[cfg(feature = "audio")]
+            music: Job::spawn(move || Music::start(volume, muted)),
+        }
+    }
+
+    // How many of the jobs are done, out of how many.
+    #[cfg_attr(not(feature = "audio"), allow(unused_mut))]
+    pub fn get_done(&mut self) -> (usize, usize) {
+        let mut jobs = 2;
+        let mut done = self.progress.is_done() as usize + self.interrupted.is_done() as usize;
+        #[cfg(feature = "audio")]
+        {
+            jobs += 1;
+            done += self.music.is_done() as usize;
+        }
+
+        (done, jobs)
+    }
+
+    pub fn finish(self) -> Loaded {
+        Loaded {
+            progress: self.progress.wait(),
+            interrupted: self.interrupted.wait(),
+            #[cfg(feature = "audio")]
+            music: self.music.wait(),
+        }
+    }
+}
@@ -0,0 +1,497 @@
+mod about;
+mod allocations;
+#[cfg(feature = "async")]
+mod async_input;
+mod autosave;
+mod bench;
+mod campaign;
+mod challenge;
+#[cfg(feature = "chat")]
+mod chat;
+mod cli;
+mod command;
+mod compress;
+mod config;
+mod custom;
+mod emergency;
+mod entry;
+mod frame;
+mod game;
+mod hardcore;
+mod input;
+mod latency;
+mod loading;
+mod menu;
+mod modifier;
+#[cfg(feature = "audio")]
+mod music;
+mod pacer;
+mod paths;
+mod preset;
+mod profile;
+mod profiler;
+mod renderer;
+mod replay;
+mod ruleset;
+mod savefile;
+mod scores;
+mod stats;
+mod tar;
+mod theme;
+mod tournament;
+mod transition;
+mod versus;
+mod weekly;
+
+use crate::challenge::SeedHistory;
+use crate::cli::{Cli, Command, ConfigAction, MapAction, PlayArgs};
+use crate::config::Config;
+use crate::game::Game;
+use crate::hardcore::Leaderboard;
+use crate::replay::Replay;
+use crate::scores::{DateRange, Ranking, ScoreFilter, ScoreHistory};
+use crate::stats::Stats;
+use crate::tournament::Tournament;
+use clap::Parser;
+use snake_core::map::Map;
+use snake_core::mapcheck;
+use snake_core::mapcode;
+use std::fs;
+use std::io::stdout;
+use std::path::Path;
+use std::process;
+
+const DEFAULT_BOARD_SIZE: u16 = 20;
+const HIGHSCORES_PAGE_SIZE: usize = 10;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: allocations::CountingAllocator = allocations::CountingAllocator;
+
+fn main() {
+    let cli = Cli::parse();
+    if cli.version {
+        println!("{}", about::version_line());
+        return;
+    }
+    if let Some(ticks) = cli.bench_sim {
+        for line in bench::run(ticks) {
+            println!("{}", line);
+        }
+        return;
+    }
+    #[cfg(feature = "compression")]
+    if cli.compact_replays {
+        compact_replays();
+        return;
+    }
+    if cli.dump_ruleset.is_some() {
+        let (config, board_size) = play_config(&cli.play);
+        let (width, height) = board_size.unwrap_or((DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE));
+        println!("{}", ruleset::to_json(&config, width, height));
+        return;
+    }
+
+    match cli.command.unwrap_or(Command::Play(cli.play)) {
+        Command::Play(args) => play(args),
+        Command::Replay { file } => replay(&file),
+        Command::Stats => print_stats(),
+        Command::Highscores {
+            hardcore,
+            mode,
+            board,
+            date,
+            profile,
+            rank_by,
+            page,
+        } => {
+            let filter = ScoreFilter {
+                mode,
+                board,
+                date: date.unwrap_or(DateRange::All),
+                profile,
+            };
+            print_highscores(hardcore, &filter, rank_by.unwrap_or(Ranking::Score), page)
+        }
+        Command::Serve { port } => unavailable(&format!("serve on port {}", port)),
+        Command::Tournament { players, rounds } => tournament(&players, rounds),
+        #[cfg(feature = "chat")]
+        Command::Chat(args) => chat(args),
+        Command::Config { action } => config(action),
+        Command::Map { action } => map(action),
+    }
+}
+
+fn play(args: PlayArgs) {
+    let (profile, measure_latency) = (args.profile, args.measure_latency);
+    let mut game = new_game(args);
+    if profile {
+        game.start_profiling();
+    }
+    if measure_latency {
+        game.start_measuring_latency();
+    }
+    game.run();
+
+    if let Some(profiler) = game.get_profiler() {
+        for line in profiler.report() {
+            println!("{}", line);
+        }
+    }
+    if let Some(latency) = game.get_latency_meter() {
+        for line in latency.report() {
+            println!("{}", line);
+        }
+    }
+}
+
+// The config a game with these options is played with, and the size of the
+// board its preset asks for, if any.
+fn play_config(args: &PlayArgs) -> (Config, Option<(u16, u16)>) {
+    let mut config = Config::load();
+    let preset = args.preset.as_ref().map(|name| {
+        config
+            .find_preset(name)
+            .unwrap_or_else(|| fail(&format!("Unknown preset '{}'", name)))
+    });
+    if let Some(preset) = &preset {
+        preset.apply(&mut config);
+    }
+    if args.rival_ai.is_some() {
+        config.rival_ai = args.rival_ai;
+    }
+
+    (config, preset.and_then(|preset| preset.get_board_size()))
+}
+
+fn new_game(args: PlayArgs) -> Game {
+    #[cfg(feature = "async")]
+    if args.async_input {
+        let input = async_input::AsyncInput::start()
+            .unwrap_or_else(|error| fail(&format!("Could not start reading input: {}", error)));
+        input::use_async_input(input);
+    }
+    let (config, board_size) = play_config(&args);
+    let (width, height) = board_size.unwrap_or((DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE));
+    let map = match (&args.map, &args.map_code, args.map_gen) {
+        (Some(path), _, _) => load_map(path),
+        (None, Some(code), _) => decode_map(code),
+        (None, None, Some(kind)) => kind.generate(
+            width,
+            height,
+            args.map_seed.unwrap_or_else(rand::random),
+            args.map_symmetry,
+        ),
+        (None, None, None) => Map::empty(width, height),
+    };
+
+    let mut game =
+        Game::new(stdout(), map, config).unwrap_or_else(|error| fail(&error.to_string()));
+    if let Some(seed) = args.code {
+        let _lock = SeedHistory::lock();
+        let mut history = SeedHistory::load();
+        history.add_shared(seed);
+        if let Err(error) = history.save() {
+            eprintln!("Could not save the challenge code: {}", error);
+        }
+    }
+    if let Some(seed) = args.seed.or(args.code) {
+        game.set_challenge(seed);
+    }
+
+    game
+}
+
+#[cfg(feature = "chat")]
+fn chat(args: cli::ChatArgs) {
+    let channel = if args.channel.starts_with('#') {
+        args.channel.to_lowercase()
+    } else {
+        format!("#{}", args.channel.to_lowercase())
+    };
+    let server = chat::ChatServer {
+        host: args.server,
+        port: args.port,
+        channel: channel.clone(),
+        nick: args.nick,
+        password: std::env::var("SNAKE_CHAT_PASSWORD").ok(),
+    };
+    let votes = chat::connect(&server).unwrap_or_else(|error| {
+        fail(&format!(
+            "Could not connect to {}:{}: {}",
+            server.host, server.port, error
+        ))
+    });
+
+    let mut game = new_game(args.play);
+    game.set_input(Box::new(chat::ChatInput::new(&channel, votes, args.window)));
+    game.run();
+}
+
+#[cfg(feature = "compression")]
+fn compact_replays() {
+    let mut paths = Replay::file_paths();
+    paths.extend(autosave::Autosave::file_paths());
+    paths.extend(emergency::Journal::file_path());
+
+    let (mut files, mut before, mut after) = (0, 0, 0);
+    for path in paths.iter().filter(|path| path.exists()) {
+        match compress::compact(path) {
+            Ok(Some((old_size, new_size))) => {
+                files += 1;
+                before += old_size;
+                after += new_size;
+            }
+            Ok(None) => {}
+            Err(error) => eprintln!("Could not compact {}: {}", path.display(), error),
+        }
+    }
+    println!(
+        "Compacted {} files from {} to {} bytes",
+        files, before, after
+    );
+}
+
+fn replay(path: &Path) {
+    let mut replay = Replay::load(path).unwrap_or_else(|error| fail(&error.to_string()));
+    let map = Map::parse(replay.journal.get_map())
+        .unwrap_or_else(|error| fail(&format!("{}: {}", path.display(), error)));
+    let mut game =
+        Game::new(stdout(), map, Config::load()).unwrap_or_else(|error| fail(&error.to_string()));
+    // Notes go into the file the replay came from.
+    if game.run_replay(&mut replay) {
+        if let Err(error) = replay.export(path) {
+            fail(&format!("Could not save the notes: {}", error));
+        }
+    }
+}
+
+fn tournament(players: &[String], rounds: u16) {
+    let map = Map::empty(DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE);
+    let mut game =
+        Game::new(stdout(), map, Config::load()).unwrap_or_else(|error| fail(&error.to_string()));
+    let mut tournament = Tournament::new(players, rounds);
+    game.run_tournament(&mut tournament);
+
+    if !tournament.is_finished() {
+        println!(
+            "The tournament was stopped in round {}",
+            tournament.get_round() + 1
+        );
+    }
+    for (i, standing) in tournament.get_standings().iter().enumerate() {
+        let scores: Vec<String> = standing
+            .scores
+            .iter()
+            .map(|score| score.to_string())
+            .collect();
+        println!(
+            "{}. {}: {} {} won, {} points ({})",
+            i + 1,
+            standing.name,
+            standing.round_wins,
+            if standing.round_wins == 1 {
+                "round"
+            } else {
+                "rounds"
+            },
+            standing.total_score,
+            scores.join(", ")
+        );
+    }
+}
+
+fn load_map(path: &Path) -> Map {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|error| fail(&format!("Could not read {}: {}", path.display(), error)));
+    Map::parse(&text).unwrap_or_else(|error| fail(&format!("{}: {}", path.display(), error)))
+}
+
+fn decode_map(code: &str) -> Map {
+    mapcode::decode(code)
+        .unwrap_or_else(|error| fail(&format!("Could not load the map: {}", error)))
+}
+
+fn map(action: MapAction) {
+    match action {
+        MapAction::Code { file } => println!("{}", mapcode::encode(&load_map(&file))),
+        MapAction::Show { code } => print!("{}", decode_map(&code).to_text()),
+        MapAction::Validate { file, fix } => validate_map(&file, fix),
+        MapAction::Gen {
+            kind,
+            seed,
+            symmetry,
+            width,
+            height,
+        } => print!(
+            "{}",
+            kind.generate(width, height, seed.unwrap_or_else(rand::random), symmetry)
+                .to_text()
+        ),
+    }
+}
+
+// Exits with 1 while the map still has issues, so maps can be checked from scripts.
+fn validate_map(path: &Path, fix: bool) {
+    let mut map = load_map(path);
+    if fix {
+        let (repaired, fixed) = mapcheck::repair(&map);
+        if !fixed.is_empty() {
+            // The comments are kept, at the top.
+            let mut text: String = fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .filter(|line| line.starts_with(';'))
+                .map(|line| format!("{}\n", line))
+                .collect();
+            text.push_str(&repaired.to_text());
+            fs::write(path, text).unwrap_or_else(|error| {
+                fail(&format!("Could not write {}: {}", path.display(), error))
+            });
+        }
+        for issue in &fixed {
+            println!("Fixed: {}", issue);
+        }
+        map = repaired;
+    }
+
+    let issues = mapcheck::check(&map);
+    if issues.is_empty() {
+        println!("{}: no issues found", path.display());
+        return;
+    }
+    for issue in &issues {
+        println!("{}: {}", path.display(), issue);
+    }
+    process::exit(1);
+}
+
+fn print_stats() {
+    let stats = Stats::load();
+    println!("Games played: {}", stats.games_played);
+    println!("Perfect games: {}", stats.perfect_games);
+    println!("Best score: {}", stats.best_score);
+}
+
+// Note: Scores from before every game was kept only have the best one in the
+// stats, so that is shown when there is no history to go through yet.
+fn print_highscores(hardcore: bool, filter: &ScoreFilter, ranking: Ranking, page: u16) {
+    if hardcore {
+        print_hardcore_leaderboard();
+        return;
+    }
+
+    let history = ScoreHistory::load();
+    if history.entries.is_empty() {
+        let stats = Stats::load();
+        match &stats.best_run {
+            Some(run) => println!("1. {} ({})", stats.best_score, run.to_line()),
+            None if stats.best_score > 0 => println!("1. {}", stats.best_score),
+            None => println!("No high scores yet"),
+        }
+        return;
+    }
+
+    let ranked = history.ranked(filter, ranking);
+    if ranked.is_empty() {
+        println!("No high scores match");
+        return;
+    }
+    let pages = ranked.len().div_ceil(HIGHSCORES_PAGE_SIZE);
+    let first = (page as usize - 1) * HIGHSCORES_PAGE_SIZE;
+    for (i, entry) in ranked
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(HIGHSCORES_PAGE_SIZE)
+    {
+        println!(
+            "{}. {} ({} ranking points, {}, {}, {})",
+            i + 1,
+            entry.score,
+            entry.get_points(),
+            entry.get_date(),
+            entry.profile,
+            entry.run.to_line()
+        );
+    }
+    println!(
+        "Page {} of {} ({} {})",
+        page,
+        pages,
+        ranked.len(),
+        if ranked.len() == 1 { "score" } else { "scores" }
+    );
+}
+
+fn print_hardcore_leaderboard() {
+    let leaderboard = Leaderboard::load();
+    if leaderboard.deaths.is_empty() {
+        println!("No hardcore runs yet");
+    }
+    for (i, death) in leaderboard.deaths.iter().enumerate() {
+        println!(
+            "{}. {} (length {}, {} ticks, {}s, died to {}, {})",
+            i + 1,
+            death.score,
+            death.length,
+            death.ticks,
+            death.secs,
+            death.cause,
+            death.run.to_line()
+        );
+    }
+}
+
+fn config(action: Option<ConfigAction>) {
+    let config = Config::load();
+    match action {
+        None => {
+            for (key, value) in config.entries() {
+                println!("{} = {}", key, value);
+            }
+        }
+        Some(ConfigAction::Path) => match Config::path() {
+            Some(path) => println!("{}", path.display()),
+            None => fail("No config directory could be determined"),
+        },
+        Some(ConfigAction::Get { key }) => {
+            match config.entries().into_iter().find(|(name, _)| *name == key) {
+                Some((_, value)) => println!("{}", value),
+                None => fail(&format!("Unknown setting '{}'", key)),
+            }
+        }
+        Some(ConfigAction::Set { key, value }) => {
+            if !config.entries().iter().any(|(name, _)| *name == key) {
+                fail(&format!("Unknown setting '{}'", key));
+            }
+            if let Err(error) = Config::save_value(&key, &value) {
+                fail(&format!("Could not save config: {}", error));
+            }
+        }
+        Some(ConfigAction::Export { file }) => match profile::export(&file) {
+            Ok(names) => println!("Exported {} to {}", names.join(", "), file.display()),
+            Err(error) => fail(&format!(
+                "Could not export to {}: {}",
+                file.display(),
+                error
+            )),
+        },
+        Some(ConfigAction::Import { file }) => match profile::import(&file) {
+            Ok(names) if names.is_empty() => println!("{} has nothing to import", file.display()),
+            Ok(names) => println!("Imported {} from {}", names.join(", "), file.display()),
+            Err(error) => fail(&format!("Could not import {}: {}", file.display(), error)),
+        },
+    }
+}
+
+// Note: The subcommands for subsystems that do not exist yet are already part of
+// the interface, so scripts can rely on them, but they only report that for now.
+fn unavailable(what: &str) {
+    fail(&format!("Cannot {}: not supported by this build yet", what));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
@@ -0,0 +1,72 @@
+// Note: A vertical list of choices. The game draws it and moves the selection
+// with the same commands it uses for steering, so it works with any input source.
+#[derive(Debug)]
+pub struct Menu {
+    title: String,
+    items: Vec<String>,
+    selected: usize,
+    // Shown under the items, saying which keys do what.
+    hint: String,
+}
+
+impl Menu {
+    pub fn new(title: &str, items: Vec<String>) -> Self {
+        Self {
+            title: title.to_string(),
+            items,
+            selected: 0,
+            hint: "Enter: select".to_string(),
+        }
+    }
+
+    pub fn set_hint(&mut self, hint: &str) {
+        self.hint = hint.to_string();
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn get_hint(&self) -> &str {
+        &self.hint
+    }
+
+    pub fn get_items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn get_selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len() - 1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.items.len();
+    }
+}
+
+// Breaks text into lines of at most `width` characters at spaces, keeping
+// empty lines. Words longer than a line are left whole.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if line.is_empty() {
+            line.push_str(word);
+        } else if line.chars().count() + 1 + word.chars().count() <= width {
+            line.push(' ');
+            line.push_str(word);
+        } else {
+            lines.push(word.to_string());
+        }
+    }
+
+    lines
+}
@@ -0,0 +1,170 @@
+use snake_core::rules::Rules;
+use std::fmt;
+
+// Note: Modifiers stack on top of whatever else a run is played with, in any
+// combination. Each one says what it changes and how much it is worth, so a new
+// modifier only needs adding here.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Modifier {
+    WrapAround,
+    Fog,
+    MirrorControls,
+    DoubleSpeed,
+}
+
+impl Modifier {
+    pub fn all() -> [Modifier; 4] {
+        [
+            Modifier::WrapAround,
+            Modifier::Fog,
+            Modifier::MirrorControls,
+            Modifier::DoubleSpeed,
+        ]
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|modifier| modifier.name() == name)
+    }
+
+    // The name used in the config file and in the mode of recorded runs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Modifier::WrapAround => "wrap",
+            Modifier::Fog => "fog",
+            Modifier::MirrorControls => "mirror",
+            Modifier::DoubleSpeed => "double_speed",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Modifier::WrapAround => "Wrap-around",
+            Modifier::Fog => "Fog",
+            Modifier::MirrorControls => "Mirror controls",
+            Modifier::DoubleSpeed => "Double speed",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Modifier::WrapAround => "The border wraps around to the other side",
+            Modifier::Fog => "Only the cells around the head can be seen",
+            Modifier::MirrorControls => "Every direction steers the other way",
+            Modifier::DoubleSpeed => "Every tick takes half as long",
+        }
+    }
+
+    // Percent of the score that counts with this modifier on. Only the ones
+    // that make the game harder are worth more; wrap-around counts as it is.
+    pub fn score_percent(&self) -> u32 {
+        match self {
+            Modifier::WrapAround => 100,
+            Modifier::Fog => 150,
+            Modifier::MirrorControls => 150,
+            Modifier::DoubleSpeed => 200,
+        }
+    }
+
+    // Fog and mirror controls only change what the frontend draws and reads,
+    // so they leave the rules alone.
+    pub fn apply(&self, rules: &mut Rules) {
+        match self {
+            Modifier::WrapAround => rules.wrap_around = true,
+            Modifier::DoubleSpeed => rules.double_speed = true,
+            Modifier::Fog | Modifier::MirrorControls => {}
+        }
+    }
+}
+
+// The modifiers turned on, always in the order of `Modifier::all`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Modifiers {
+    enabled: Vec<Modifier>,
+}
+
+impl Modifiers {
+    // Note: A comma separated list like `fog,mirror`, or `none`. Unknown names
+    // are skipped, like unknown config keys.
+    pub fn parse(value: &str) -> Self {
+        let mut modifiers = Self::default();
+        for name in value.split(',') {
+            if let Some(modifier) = Modifier::parse(name.trim()) {
+                modifiers.set(modifier, true);
+            }
+        }
+
+        modifiers
+    }
+
+    pub fn name(&self) -> String {
+        if self.enabled.is_empty() {
+            return "none".to_string();
+        }
+
+        let names: Vec<&str> = self
+            .enabled
+            .iter()
+            .map(|modifier| modifier.name())
+            .collect();
+        names.join(",")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Modifier> + '_ {
+        self.enabled.iter().copied()
+    }
+
+    pub fn contains(&self, modifier: Modifier) -> bool {
+        self.enabled.contains(&modifier)
+    }
+
+    pub fn set(&mut self, modifier: Modifier, enabled: bool) {
+        self.enabled.retain(|known| *known != modifier);
+        if enabled {
+            self.enabled.push(modifier);
+        }
+        self.enabled
+            .sort_by_key(|known| Modifier::all().iter().position(|any| any == known));
+    }
+
+    pub fn toggle(&mut self, modifier: Modifier) {
+        self.set(modifier, !self.contains(modifier));
+    }
+
+    pub fn apply(&self, rules: &mut Rules) {
+        for modifier in self.iter() {
+            modifier.apply(rules);
+        }
+    }
+
+    // Note: The multipliers of all the modifiers multiplied together, so two
+    // modifiers worth 1.5 each make the score count 2.25 times.
+    pub fn score_percent(&self) -> u32 {
+        self.iter().fold(100, |percent, modifier| {
+            percent * modifier.score_percent() / 100
+        })
+    }
+
+    pub fn score(&self, score: u64) -> u64 {
+        score.saturating_mul(self.score_percent() as u64) / 100
+    }
+}
+
+// Note: Shows a percentage as a multiplier, e.g. 225 as `x2.25` and 300 as `x3`.
+#[derive(Debug, Copy, Clone)]
+pub struct Multiplier(pub u32);
+
+impl fmt::Display for Multiplier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (whole, fraction) = (self.0 / 100, self.0 % 100);
+        if fraction == 0 {
+            write!(f, "x{}", whole)
+        } else if fraction % 10 == 0 {
+            write!(f, "x{}.{}", whole, fraction / 10)
+        } else {
+            write!(f, "x{}.{:02}", whole, fraction)
+        }
+    }
+}
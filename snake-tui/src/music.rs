@@ -0,0 +1,227 @@
+use snake_core::state::MAX_SPEED;
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SAMPLE_RATE: u32 = 22_050;
+// Samples generated at a time, a fiftieth of a second.
+const CHUNK: usize = SAMPLE_RATE as usize / 50;
+// How far the music is generated ahead of what has been played, which is also
+// about how long a change of intensity or muting takes to be heard.
+const LEAD: Duration = Duration::from_millis(100);
+// Loudness at full volume, leaving headroom for the voices playing together.
+const LEVEL: f32 = 0.25;
+
+// Note: There is no audio library in the build, so the music is raw 16-bit mono
+// samples piped into the first of these players that is installed.
+const PLAYERS: [&[&str]; 3] = [
+    &[
+        "aplay", "-q", "-t", "raw", "-f", "S16_LE", "-r", "22050", "-c", "1",
+    ],
+    &[
+        "paplay",
+        "--raw",
+        "--format=s16le",
+        "--rate=22050",
+        "--channels=1",
+    ],
+    &[
+        "pw-play",
+        "--format",
+        "s16",
+        "--rate",
+        "22050",
+        "--channels",
+        "1",
+        "-",
+    ],
+];
+
+// The lead tune as MIDI note numbers, one per eighth note, with 0 for a rest.
+const MELODY: [u8; 32] = [
+    69, 0, 72, 69, 76, 0, 74, 72, //
+    71, 0, 74, 71, 79, 0, 77, 76, //
+    72, 0, 76, 72, 81, 0, 79, 77, //
+    76, 74, 72, 71, 69, 0, 0, 0, //
+];
+// The bass note of each bar of the melody.
+const BASS: [u8; 4] = [45, 43, 41, 40];
+
+#[derive(Debug)]
+struct Controls {
+    intensity: AtomicU16,
+    volume: AtomicU8,
+    muted: AtomicBool,
+    stopped: AtomicBool,
+}
+
+// Note: A looping chiptune generated on its own thread: a square wave lead over
+// a square wave bass. It gets faster with the snake's speed, the bass starts
+// jumping octaves from speed 3 and a hi-hat joins in from speed 6.
+#[derive(Debug)]
+pub struct Music {
+    controls: Arc<Controls>,
+    player: Child,
+}
+
+impl Music {
+    // Returns None when none of the players could be started.
+    pub fn start(volume: u8, muted: bool) -> Option<Self> {
+        let mut player = PLAYERS.iter().find_map(|command| {
+            Command::new(command[0])
+                .args(&command[1..])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()
+        })?;
+        let stdin = player.stdin.take()?;
+        let controls = Arc::new(Controls {
+            intensity: AtomicU16::new(0),
+            volume: AtomicU8::new(volume.min(100)),
+            muted: AtomicBool::new(muted),
+            stopped: AtomicBool::new(false),
+        });
+
+        let shared = Arc::clone(&controls);
+        thread::spawn(move || stream(stdin, &shared));
+
+        Some(Self { controls, player })
+    }
+
+    // The intensity follows the snake's speed, from 0 to MAX_SPEED.
+    pub fn set_intensity(&self, speed: u16) {
+        self.controls
+            .intensity
+            .store(speed.min(MAX_SPEED), Ordering::Relaxed);
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.controls.muted.store(muted, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Music {
+    fn drop(&mut self) {
+        self.controls.stopped.store(true, Ordering::Relaxed);
+        let _ = self.player.kill();
+        let _ = self.player.wait();
+    }
+}
+
+// Writes the music to the player, keeping only LEAD ahead of it, until the
+// music is stopped or the player goes away.
+fn stream(mut stdin: ChildStdin, controls: &Controls) {
+    let mut synth = Synth::default();
+    let mut bytes = Vec::with_capacity(CHUNK * 2);
+    let started = Instant::now();
+    let mut written: u64 = 0;
+
+    while !controls.stopped.load(Ordering::Relaxed) {
+        let played = Duration::from_secs_f64(written as f64 / SAMPLE_RATE as f64);
+        if played > started.elapsed() + LEAD {
+            thread::sleep(Duration::from_millis(10));
+            continue;
+        }
+
+        let intensity = controls.intensity.load(Ordering::Relaxed);
+        let gain = if controls.muted.load(Ordering::Relaxed) {
+            0.0
+        } else {
+            LEVEL * controls.volume.load(Ordering::Relaxed) as f32 / 100.0
+        };
+        bytes.clear();
+        for _ in 0..CHUNK {
+            let sample = (synth.next_sample(intensity) * gain * i16::MAX as f32) as i16;
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        if stdin.write_all(&bytes).is_err() {
+            break;
+        }
+        written += CHUNK as u64;
+    }
+}
+
+#[derive(Debug)]
+struct Synth {
+    step: usize,
+    // Samples played of the current step.
+    position: u32,
+    lead_phase: f32,
+    bass_phase: f32,
+    noise: u16,
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Self {
+            step: 0,
+            position: 0,
+            lead_phase: 0.0,
+            bass_phase: 0.0,
+            noise: 0xACE1,
+        }
+    }
+}
+
+impl Synth {
+    // The next sample, between -1 and 1.
+    fn next_sample(&mut self, intensity: u16) -> f32 {
+        // Eighth notes at 120 beats per minute, up to 168 at the top speed.
+        let beats_per_minute = 120 + 6 * intensity as u32;
+        let step_length = SAMPLE_RATE * 60 / beats_per_minute / 2;
+        if self.position >= step_length {
+            self.position = 0;
+            self.step = (self.step + 1) % MELODY.len();
+        }
+        let progress = self.position as f32 / step_length as f32;
+        self.position += 1;
+
+        let mut sample = 0.0;
+        let note = MELODY[self.step];
+        if note != 0 {
+            sample += 0.5 * envelope(progress) * square(&mut self.lead_phase, note, 0.5);
+        }
+
+        let mut bass = BASS[self.step / 8];
+        if intensity >= 3 && self.step % 2 == 1 {
+            bass += 12;
+        }
+        sample += 0.35 * envelope(progress) * square(&mut self.bass_phase, bass, 0.25);
+
+        if intensity >= 6 && progress < 0.15 {
+            // A 16-bit linear feedback shift register makes the noise.
+            let bit = (self.noise ^ (self.noise >> 2) ^ (self.noise >> 3) ^ (self.noise >> 5)) & 1;
+            self.noise = (self.noise >> 1) | (bit << 15);
+            let noise = if self.noise & 1 == 1 { 1.0 } else { -1.0 };
+            sample += 0.15 * (1.0 - progress / 0.15) * noise;
+        }
+
+        sample
+    }
+}
+
+// A short fade in against clicks, then a decay over the rest of the step.
+fn envelope(progress: f32) -> f32 {
+    if progress < 0.02 {
+        progress / 0.02
+    } else {
+        1.0 - 0.7 * progress
+    }
+}
+
+// Advances a square wave of the given MIDI note by one sample. The duty cycle is
+// the part of each period spent high.
+fn square(phase: &mut f32, note: u8, duty: f32) -> f32 {
+    let frequency = 440.0 * 2f32.powf((note as f32 - 69.0) / 12.0);
+    *phase = (*phase + frequency / SAMPLE_RATE as f32).fract();
+    if *phase < duty {
+        1.0
+    } else {
+        -1.0
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+// Ticks measured for the jitter stats in the debug info.
+const SAMPLES: usize = 64;
+
+// Note: Ticks are scheduled on deadlines one interval apart rather than by waiting
+// a full interval after each tick, so when input polling or a slow frame makes a
+// tick late, the next wait is that much shorter and the average rate holds.
+// Falling a whole interval behind (after a pause or a dialog) starts over from
+// now instead of catching up with a burst of ticks.
+#[derive(Debug)]
+pub struct TickPacer {
+    deadline: Option<Instant>,
+    last_tick: Option<Instant>,
+    // How late each recent tick was, in microseconds; early ticks count as negative.
+    lateness: [i64; SAMPLES],
+    intervals: [u64; SAMPLES],
+    count: usize,
+    next: usize,
+}
+
+impl Default for TickPacer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TickPacer {
+    pub fn new() -> Self {
+        Self {
+            deadline: None,
+            last_tick: None,
+            lateness: [0; SAMPLES],
+            intervals: [0; SAMPLES],
+            count: 0,
+            next: 0,
+        }
+    }
+
+    // The time the next tick is due, `interval` after the last one was.
+    pub fn next_deadline(&mut self, interval: Duration) -> Instant {
+        let now = Instant::now();
+        let deadline = match self.deadline {
+            Some(last) if last + interval + interval > now => last + interval,
+            _ => return self.restart(interval),
+        };
+        self.deadline = Some(deadline);
+        deadline
+    }
+
+    // Waits a full interval from now, without measuring the time since the last tick.
+    pub fn restart(&mut self, interval: Duration) -> Instant {
+        let deadline = Instant::now() + interval;
+        self.deadline = Some(deadline);
+        self.last_tick = None;
+        deadline
+    }
+
+    // Records when a tick happened, to measure it against the interval it was due after.
+    pub fn tick(&mut self, interval: Duration) {
+        let now = Instant::now();
+        if let Some(last) = self.last_tick {
+            let actual = now.duration_since(last).as_micros() as i64;
+            self.lateness[self.next] = actual - interval.as_micros() as i64;
+            self.intervals[self.next] = actual as u64;
+            self.next = (self.next + 1) % SAMPLES;
+            self.count = (self.count + 1).min(SAMPLES);
+        }
+        self.last_tick = Some(now);
+    }
+
+    // None until the first tick, and again after a restart.
+    pub fn since_last_tick(&self) -> Option<Duration> {
+        self.last_tick.map(|last| last.elapsed())
+    }
+
+    // The average time between the recent ticks, or None before there are any.
+    pub fn get_average_interval(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let total: u64 = self.intervals[..self.count].iter().sum();
+        Some(Duration::from_micros(total / self.count as u64))
+    }
+
+    // The average and the largest difference between when the recent ticks were
+    // due and when they happened.
+    pub fn get_jitter(&self) -> Option<(Duration, Duration)> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let samples = &self.lateness[..self.count];
+        let total: u64 = samples.iter().map(|late| late.unsigned_abs()).sum();
+        let max = samples
+            .iter()
+            .map(|late| late.unsigned_abs())
+            .max()
+            .unwrap();
+        Some((
+            Duration::from_micros(total / self.count as u64),
+            Duration::from_micros(max),
+        ))
+    }
+}
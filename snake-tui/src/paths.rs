@@ -0,0 +1,62 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR_NAME: &str = "snake-rs";
+// Keeps every file in this one directory instead, e.g. for a portable install.
+pub const HOME_VAR: &str = "SNAKE_RS_HOME";
+
+// Note: The config file goes in the platform's config directory, and everything
+// the game saves on its own in its data directory:
+//
+//   Linux and others: $XDG_CONFIG_HOME/snake-rs (~/.config) and $XDG_DATA_HOME/snake-rs (~/.local/share)
+//   macOS: ~/Library/Application Support/snake-rs for both
+//   Windows: %APPDATA%\snake-rs for both
+pub fn config_dir() -> Option<PathBuf> {
+    app_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    app_dir("XDG_DATA_HOME", ".local/share")
+}
+
+// Note: Files used to be kept straight in the home directory, e.g. `~/.snake-rs.stats`.
+// They are still read until the first save writes them to the new place, but
+// not when SNAKE_RS_HOME is set, which should only ever see its own files.
+pub fn legacy_path(file_name: &str) -> Option<PathBuf> {
+    if env_dir(HOME_VAR).is_some() {
+        return None;
+    }
+
+    home_dir().map(|home| home.join(file_name))
+}
+
+fn app_dir(xdg_var: &str, xdg_default: &str) -> Option<PathBuf> {
+    if let Some(dir) = env_dir(HOME_VAR) {
+        return Some(dir);
+    }
+
+    let base = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")?
+    } else if cfg!(target_os = "macos") {
+        home_dir()?.join("Library").join("Application Support")
+    } else {
+        // XDG says relative paths are to be ignored.
+        match env_dir(xdg_var).filter(|dir| dir.is_absolute()) {
+            Some(dir) => dir,
+            None => home_dir()?.join(xdg_default),
+        }
+    };
+
+    Some(base.join(APP_DIR_NAME))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env_dir("HOME").or_else(|| env_dir("USERPROFILE"))
+}
+
+// Empty variables count as unset.
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
@@ -0,0 +1,121 @@
+use crate::config::Config;
+use crate::menu::Menu;
+
+pub const MAX_NAME_LENGTH: usize = 12;
+// Note: Presets that come with the game, for `--preset` and the Presets menu.
+// A preset of the same name in the config file takes the place of one of these.
+const BUILTIN_PRESETS: [(&str, &str); 2] = [
+    (
+        "chill",
+        "board=30x15 food_count=3 speed_ramp=off modifiers=wrap theme=night",
+    ),
+    (
+        "nightmare",
+        "board=20x20 obstacles=6 start_speed=4 speed_ramp=fast laser_secs=10 modifiers=fog,mirror,double_speed",
+    ),
+];
+
+// Note: A preset is a named set of settings kept on one line of the config file,
+// `preset.<name> = board=30x15 food_count=3 modifiers=fog,mirror`, in the same
+// `key=value` form as the rest of the file but without spaces. `board` is the
+// size of the empty board it is played on; every other key is a setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    settings: Vec<(String, String)>,
+}
+
+impl Preset {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            settings: Vec::new(),
+        }
+    }
+
+    pub fn parse(name: &str, value: &str) -> Self {
+        let mut preset = Self::new(name);
+        for field in value.split_whitespace() {
+            if let Some((key, value)) = field.split_once('=') {
+                preset.set(key, value);
+            }
+        }
+
+        preset
+    }
+
+    // Names go into config keys, so they are kept to letters, digits, `-` and `_`.
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= MAX_NAME_LENGTH
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    pub fn get_key(&self) -> String {
+        format!("preset.{}", self.name)
+    }
+
+    pub fn builtin() -> Vec<Preset> {
+        BUILTIN_PRESETS
+            .iter()
+            .map(|(name, value)| Self::parse(name, value))
+            .collect()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.settings
+            .iter()
+            .find(|(known, _)| known == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.settings.iter_mut().find(|(known, _)| known == key) {
+            Some(setting) => setting.1 = value.to_string(),
+            None => self.settings.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn get_board_size(&self) -> Option<(u16, u16)> {
+        let (width, height) = self.get("board")?.split_once('x')?;
+        Some((width.parse().ok()?, height.parse().ok()?))
+    }
+
+    pub fn to_value(&self) -> String {
+        let fields: Vec<String> = self
+            .settings
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        fields.join(" ")
+    }
+
+    // The screen shown instead of playing when the preset's board cannot be played.
+    pub fn unplayable_lines(&self, reason: &str) -> Vec<String> {
+        vec![
+            format!("Cannot play {}:", self.name),
+            reason.to_string(),
+            String::new(),
+            "Any key: back".to_string(),
+        ]
+    }
+
+    // Settings the config does not know are ignored, like in the file itself.
+    pub fn apply(&self, config: &mut Config) {
+        for (key, value) in &self.settings {
+            if key != "board" {
+                config.apply_setting(key, value);
+            }
+        }
+    }
+}
+
+// The Presets menu, with `presets` in order followed by Back.
+pub fn menu(presets: &[Preset]) -> Menu {
+    let mut items: Vec<String> = presets.iter().map(|preset| preset.name.clone()).collect();
+    items.push("Back".to_string());
+
+    Menu::new("Presets", items)
+}
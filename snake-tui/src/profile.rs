@@ -0,0 +1,113 @@
+use crate::campaign::Progress;
+use crate::challenge::SeedHistory;
+use crate::config::Config;
+use crate::hardcore::Leaderboard;
+use crate::scores::ScoreHistory;
+use crate::stats::Stats;
+use crate::tar;
+use crate::weekly::WeeklyBests;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Note: A profile bundle is a tar file with the config file and every save file
+// in it, named as they are in the data directory. Returns the names of the
+// files that went into the bundle.
+pub fn export(path: &Path) -> io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    if let Some(config) = Config::read_file() {
+        files.push(("config", config));
+    }
+    files.push(("stats", Stats::load().to_file()));
+    files.push(("seeds", SeedHistory::load().to_file()));
+    files.push(("campaign", Progress::load().to_file()));
+    files.push(("hardcore", Leaderboard::load().to_file()));
+    files.push(("scores", ScoreHistory::load().to_file()));
+    files.push(("weekly", WeeklyBests::load().to_file()));
+
+    let entries: Vec<(&str, &[u8])> = files
+        .iter()
+        .map(|(name, contents)| (*name, contents.as_bytes()))
+        .collect();
+    fs::write(path, tar::write(&entries))?;
+
+    Ok(files.iter().map(|(name, _)| name.to_string()).collect())
+}
+
+// Note: Importing merges the bundle into what is already here instead of
+// replacing it: the best scores, scored games, weekly bests, stars and hardcore
+// runs of both are kept, and the bundle's settings are applied on top of the
+// local config file. Files a newer version put in the bundle are skipped.
+// Returns the names of the files that were imported.
+pub fn import(path: &Path) -> io::Result<Vec<String>> {
+    let archive = fs::read(path)?;
+    let files =
+        tar::read(&archive).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    let mut imported = Vec::new();
+    for (name, contents) in files {
+        let contents = String::from_utf8(contents).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} in the bundle is not text", name),
+            )
+        })?;
+        match name.as_str() {
+            "config" => import_config(&contents)?,
+            "stats" => {
+                let _lock = Stats::lock();
+                let mut stats = Stats::load();
+                stats.merge(Stats::from_file(&contents));
+                stats.save()?;
+            }
+            "seeds" => {
+                let _lock = SeedHistory::lock();
+                let mut history = SeedHistory::load();
+                history.merge(SeedHistory::from_file(&contents));
+                history.save()?;
+            }
+            "campaign" => {
+                let _lock = Progress::lock();
+                let mut progress = Progress::load();
+                progress.merge(Progress::from_file(&contents));
+                progress.save()?;
+            }
+            "hardcore" => {
+                let _lock = Leaderboard::lock();
+                let mut leaderboard = Leaderboard::load();
+                leaderboard.merge(Leaderboard::from_file(&contents));
+                leaderboard.save()?;
+            }
+            "scores" => {
+                let _lock = ScoreHistory::lock();
+                let mut history = ScoreHistory::load();
+                history.merge(ScoreHistory::from_file(&contents));
+                history.save()?;
+            }
+            "weekly" => {
+                let _lock = WeeklyBests::lock();
+                let mut bests = WeeklyBests::load();
+                bests.merge(WeeklyBests::from_file(&contents));
+                bests.save()?;
+            }
+            _ => continue,
+        }
+        imported.push(name);
+    }
+
+    Ok(imported)
+}
+
+fn import_config(contents: &str) -> io::Result<()> {
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            Config::save_value(key.trim(), value.trim())?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Phase {
+    Input,
+    Simulation,
+    Rendering,
+}
+
+impl Phase {
+    pub fn all() -> [Phase; 3] {
+        [Phase::Input, Phase::Simulation, Phase::Rendering]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Input => "input",
+            Phase::Simulation => "simulation",
+            Phase::Rendering => "rendering",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Phase::Input => 0,
+            Phase::Simulation => 1,
+            Phase::Rendering => 2,
+        }
+    }
+}
+
+// Note: Adds up the time spent in each phase over a frame, which is one tick of
+// the game, and keeps every frame for the report. Time spent waiting for the
+// next tick is not counted anywhere, so the numbers are the work done per tick.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    current: [Duration; 3],
+    frames: Vec<[Duration; 3]>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, phase: Phase, elapsed: Duration) {
+        self.current[phase.index()] += elapsed;
+    }
+
+    pub fn end_frame(&mut self) {
+        self.frames.push(self.current);
+        self.current = [Duration::ZERO; 3];
+    }
+
+    // Drops what was measured outside of a game, e.g. while drawing menus.
+    pub fn discard_frame(&mut self) {
+        self.current = [Duration::ZERO; 3];
+    }
+
+    pub fn report(&self) -> Vec<String> {
+        if self.frames.is_empty() {
+            return vec!["Profile: no frames were played".to_string()];
+        }
+
+        let mut lines = vec![
+            format!(
+                "Profile of {} frames (microseconds per frame):",
+                self.frames.len()
+            ),
+            format!(
+                "{:<12}{:>8}{:>8}{:>8}{:>8}{:>8}",
+                "", "mean", "p50", "p90", "p99", "max"
+            ),
+        ];
+        for phase in Phase::all().iter() {
+            let times = self
+                .frames
+                .iter()
+                .map(|frame| frame[phase.index()])
+                .collect();
+            lines.push(summary_line(phase.name(), times));
+        }
+        let totals = self.frames.iter().map(|frame| frame.iter().sum()).collect();
+        lines.push(summary_line("frame", totals));
+
+        lines
+    }
+}
+
+pub fn summary_line(name: &str, mut times: Vec<Duration>) -> String {
+    times.sort();
+    let mean = times.iter().sum::<Duration>() / times.len() as u32;
+
+    format!(
+        "{:<12}{:>8}{:>8}{:>8}{:>8}{:>8}",
+        name,
+        mean.as_micros(),
+        percentile(&times, 50).as_micros(),
+        percentile(&times, 90).as_micros(),
+        percentile(&times, 99).as_micros(),
+        times[times.len() - 1].as_micros()
+    )
+}
+
+// Nearest-rank percentile of sorted, non-empty times.
+fn percentile(times: &[Duration], percent: usize) -> Duration {
+    let rank = (times.len() * percent).div_ceil(100).max(1);
+    times[rank - 1]
+}
@@ -0,0 +1,252 @@
+use crate::frame::Frame;
+use crate::transition::{self, Transition};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{DisableFocusChange, EnableFocusChange};
+use crossterm::style::{Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, SetSize};
+use crossterm::{ExecutableCommand, QueueableCommand};
+use std::io::{Stdout, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const BLINK_INTERVAL: Duration = Duration::from_millis(250);
+
+// What the writer thread is asked to do with the terminal, in order.
+#[derive(Debug)]
+enum Output {
+    Show(Frame, Option<Transition>),
+    // Sets the terminal to the size and clears it, forgetting what was shown.
+    Resize(u16, u16),
+    Prepare(u16, u16),
+    // Answers once the terminal is back to the size, with everything before written.
+    Restore((u16, u16), Sender<()>),
+}
+
+// Note: The renderer owns the terminal. Callers draw into `frame` and then call
+// `present`, which hands a copy of it to a thread of its own that only writes
+// the cells that differ from what is already shown. A slow terminal only holds
+// up that thread, never input or the ticks: it skips straight to the newest
+// frame when it falls behind. The copies go back and forth between the two
+// threads, so presenting does not allocate.
+#[derive(Debug)]
+pub struct Renderer {
+    frame: Frame,
+    output: Sender<Output>,
+    spare_frames: Receiver<Frame>,
+    started: Instant,
+    // Frames presented so far, which drives the frame based animations.
+    frames: u64,
+    // Played on the next present, from what is shown to the new frame.
+    transition: Option<Transition>,
+}
+
+impl Renderer {
+    pub fn new(stdout: Stdout, width: u16, height: u16) -> Self {
+        let (output, requests) = mpsc::channel();
+        let (spares, spare_frames) = mpsc::channel();
+        let mut writer = Writer {
+            stdout,
+            presented: None,
+            spares,
+        };
+        thread::spawn(move || writer.run(requests));
+
+        Self {
+            frame: Frame::new(width, height),
+            output,
+            spare_frames,
+            started: Instant::now(),
+            frames: 0,
+            transition: None,
+        }
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.frame
+    }
+
+    pub fn frame_mut(&mut self) -> &mut Frame {
+        &mut self.frame
+    }
+
+    // Note: Everything that blinks shares one clock, so blinking cells stay in step.
+    pub fn blink_on(&self) -> bool {
+        (self.started.elapsed().as_millis() / BLINK_INTERVAL.as_millis()).is_multiple_of(2)
+    }
+
+    // Note: Animations that should move with the picture rather than the clock,
+    // like pulsing food, count presented frames instead: each phase of the pulse
+    // lasts `frames` frames, so it speeds up along with the snake.
+    pub fn pulse_on(&self, frames: u64) -> bool {
+        (self.frames / frames.max(1)).is_multiple_of(2)
+    }
+
+    // Note: Used when the board changes size, e.g. for a campaign level. The
+    // whole screen is cleared and redrawn on the next present.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.frame = Frame::new(width, height);
+        self.send(Output::Resize(width + 1, height));
+        self.transition = None;
+    }
+
+    // Time since the renderer was created, for slow animations.
+    pub fn get_elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn prepare(&mut self) {
+        enable_raw_mode().unwrap();
+        self.send(Output::Prepare(
+            self.frame.get_width() + 1,
+            self.frame.get_height(),
+        ));
+    }
+
+    pub fn restore(&mut self, size: (u16, u16)) {
+        let (done, restored) = mpsc::channel();
+        self.send(Output::Restore(size, done));
+        restored.recv().unwrap();
+        disable_raw_mode().unwrap();
+    }
+
+    // Note: Only takes effect when a screen of the same size is already shown,
+    // e.g. not for the first screen or after a resize.
+    pub fn queue_transition(&mut self, transition: Transition) {
+        self.transition = Some(transition);
+    }
+
+    pub fn present(&mut self) {
+        let mut copy = match self.spare_frames.try_recv() {
+            Ok(spare) => spare,
+            Err(_) => Frame::new(self.frame.get_width(), self.frame.get_height()),
+        };
+        copy.clone_from(&self.frame);
+        let transition = self.transition.take();
+        self.send(Output::Show(copy, transition));
+        self.frames += 1;
+    }
+
+    // Note: The writer thread only stops when writing to the terminal failed,
+    // and then the game panics as well, like it did when it wrote itself.
+    fn send(&self, output: Output) {
+        self.output.send(output).unwrap();
+    }
+}
+
+#[derive(Debug)]
+struct Writer {
+    stdout: Stdout,
+    presented: Option<Frame>,
+    spares: Sender<Frame>,
+}
+
+impl Writer {
+    fn run(&mut self, requests: Receiver<Output>) {
+        let mut next = None;
+        while let Some(output) = next.take().or_else(|| requests.recv().ok()) {
+            match output {
+                Output::Show(mut frame, transition) => {
+                    // Frames that were never going to be seen are skipped.
+                    while transition.is_none() {
+                        match requests.try_recv() {
+                            Ok(Output::Show(newer, None)) => {
+                                self.spares.send(frame).ok();
+                                frame = newer;
+                            }
+                            Ok(other) => {
+                                next = Some(other);
+                                break;
+                            }
+                            Err(_) => break,
+                        }
+                    }
+                    if let Some(transition) = transition {
+                        self.play(transition, &frame);
+                    }
+                    self.write_changes(&frame);
+                    self.spares.send(frame).ok();
+                }
+                Output::Resize(width, height) => {
+                    self.stdout
+                        .execute(SetSize(width, height))
+                        .unwrap()
+                        .execute(Clear(ClearType::All))
+                        .unwrap();
+                    self.presented = None;
+                }
+                Output::Prepare(width, height) => {
+                    self.stdout
+                        .execute(SetSize(width, height))
+                        .unwrap()
+                        .execute(Clear(ClearType::All))
+                        .unwrap()
+                        .execute(Hide)
+                        .unwrap()
+                        .execute(EnableFocusChange)
+                        .unwrap();
+                    self.presented = None;
+                }
+                Output::Restore((cols, rows), done) => {
+                    self.stdout
+                        .execute(SetSize(cols, rows))
+                        .unwrap()
+                        .execute(Clear(ClearType::All))
+                        .unwrap()
+                        .execute(Show)
+                        .unwrap()
+                        .execute(ResetColor)
+                        .unwrap()
+                        .execute(DisableFocusChange)
+                        .unwrap();
+                    done.send(()).ok();
+                }
+            }
+        }
+    }
+
+    fn play(&mut self, transition: Transition, to: &Frame) {
+        let from = self.presented.clone().filter(|presented| {
+            presented.get_width() == to.get_width() && presented.get_height() == to.get_height()
+        });
+        if let Some(from) = from {
+            for step in 0..transition::STEPS {
+                self.write_changes(&transition.frame_at(&from, to, step));
+                thread::sleep(transition::STEP_INTERVAL);
+            }
+        }
+    }
+
+    // Writes the cells that differ from what is shown, and remembers the frame as shown.
+    fn write_changes(&mut self, frame: &Frame) {
+        for y in 0..frame.get_height() {
+            for x in 0..frame.get_width() {
+                let cell = frame.get(x, y).unwrap();
+                let unchanged = self
+                    .presented
+                    .as_ref()
+                    .and_then(|presented| presented.get(x, y))
+                    == Some(cell);
+                if unchanged {
+                    continue;
+                }
+
+                self.stdout
+                    .queue(MoveTo(x, y))
+                    .unwrap()
+                    .queue(SetForegroundColor(cell.fg))
+                    .unwrap()
+                    .queue(SetBackgroundColor(cell.bg))
+                    .unwrap()
+                    .queue(Print(cell.glyph))
+                    .unwrap();
+            }
+        }
+        self.stdout.flush().unwrap();
+
+        match self.presented.as_mut() {
+            Some(presented) => presented.clone_from(frame),
+            None => self.presented = Some(frame.clone()),
+        }
+    }
+}
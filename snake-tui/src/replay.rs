@@ -0,0 +1,624 @@
+use crate::about::RunInfo;
+use crate::challenge;
+use crate::command::Command;
+use crate::compress;
+use crate::emergency::{Event, Journal};
+use crate::menu::wrap;
+use crate::paths;
+use crate::savefile::{self, SaveFile};
+use snake_core::direction::Direction;
+use snake_core::policy::Policy;
+use snake_core::state::{GameState, StepOutcome};
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const REPLAY_FILE: SaveFile = SaveFile::new("replay", &[savefile::unchanged]).compressed();
+const REPLAYS_DIR: &str = "replays";
+// Favorites do not count towards this.
+const MAX_REPLAYS: usize = 20;
+// Steps between keyframes, and so the most a jump has to play through.
+const KEYFRAME_INTERVAL: u64 = 100;
+pub const MAX_NOTE_LENGTH: usize = 32;
+// Lines of the replays screen besides the replays: the title, blank lines, details and hint.
+const BROWSER_LINES: usize = 8;
+// Lines of details about the selected replay, under the list.
+const DETAIL_LINES: usize = 4;
+
+// Note: Every run that ends on its own is kept as a replay: its journal, with
+// what it scored and when, in a file of its own in the `replays` directory of
+// the data directory. Only the newest MAX_REPLAYS are kept, besides favorites,
+// which stay until they are deleted. The file is the journal's `key = value`
+// lines after a few of its own, including the notes written while watching,
+// at most one per tick:
+//
+//   played_at = 1791979200
+//   score = 12
+//   secs = 95
+//   favorite = true
+//   run = version=0.1.0 seed=1f2e mode=classic board=20x20
+//   note = 120 should have gone left
+#[derive(Debug, Clone)]
+pub struct Replay {
+    // Seconds since the Unix epoch.
+    pub played_at: u64,
+    pub score: u64,
+    // Play time, without pauses.
+    pub secs: u64,
+    pub favorite: bool,
+    pub run: RunInfo,
+    // By tick, in order.
+    notes: Vec<(u64, String)>,
+    pub journal: Journal,
+}
+
+impl Replay {
+    pub fn new(journal: Journal, score: u64, secs: u64, run: &RunInfo) -> Self {
+        let played_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Self {
+            played_at,
+            score,
+            secs,
+            favorite: false,
+            run: run.clone(),
+            notes: Vec::new(),
+            journal,
+        }
+    }
+
+    // The saved replays, newest first. Files that cannot be read are left out.
+    pub fn load_all() -> Vec<Self> {
+        let entries = match replays_dir().and_then(|dir| fs::read_dir(dir).ok()) {
+            Some(entries) => entries,
+            None => return Vec::new(),
+        };
+        let mut replays: Vec<Self> = entries
+            .filter_map(|entry| Self::load(&entry.ok()?.path()).ok())
+            .collect();
+        replays.sort_by_key(|replay| Reverse(replay.played_at));
+        replays
+    }
+
+    // Also reads exported replays, from anywhere.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = compress::read(path)?;
+        Self::parse(&REPLAY_FILE.read(&contents)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a replay", path.display()),
+            )
+        })
+    }
+
+    // Saves over the replay's file if it has one already, and drops the oldest
+    // replays past MAX_REPLAYS.
+    pub fn save(&self) -> io::Result<()> {
+        let dir = replays_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no data directory could be determined",
+            )
+        })?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(self.get_file_name());
+        // Written next to the file and renamed over it, so it is never seen half written.
+        let temporary = path.with_extension(format!("{}.tmp", process::id()));
+        fs::write(&temporary, REPLAY_FILE.to_bytes(&self.to_contents()))?;
+        fs::rename(&temporary, &path)?;
+
+        for old in Self::load_all()
+            .iter()
+            .filter(|replay| !replay.favorite)
+            .skip(MAX_REPLAYS)
+        {
+            old.delete()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(&self) -> io::Result<()> {
+        match replays_dir() {
+            Some(dir) => fs::remove_file(dir.join(self.get_file_name())),
+            None => Ok(()),
+        }
+    }
+
+    // Exported replays stay plain text, so builds without compression can watch them.
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, REPLAY_FILE.with_header(&self.to_contents()))
+    }
+
+    // Every file in the replays directory, for `--compact-replays`.
+    #[cfg(feature = "compression")]
+    pub fn file_paths() -> Vec<PathBuf> {
+        replays_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.path()))
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension == "replay")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Unique enough, since two runs would have to end in the same second on the same seed.
+    pub fn get_file_name(&self) -> String {
+        format!("{}-{:x}.replay", self.played_at, self.run.seed)
+    }
+
+    // e.g. `2026-10-14 13:05`, in UTC.
+    pub fn get_date(&self) -> String {
+        let minutes = self.played_at % 86_400 / 60;
+        format!(
+            "{} {:02}:{:02}",
+            challenge::date_label(self.played_at / 86_400),
+            minutes / 60,
+            minutes % 60
+        )
+    }
+
+    // e.g. `1:35`.
+    pub fn get_duration(&self) -> String {
+        format!("{}:{:02}", self.secs / 60, self.secs % 60)
+    }
+
+    pub fn get_note(&self, step: u64) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|(at, _)| *at == step)
+            .map(|(_, note)| note.as_str())
+    }
+
+    // Replaces the tick's note, or removes it when given an empty one.
+    pub fn set_note(&mut self, step: u64, note: &str) {
+        self.notes.retain(|(at, _)| *at != step);
+        if !note.is_empty() {
+            self.notes.push((step, note.to_string()));
+            self.notes.sort();
+        }
+    }
+
+    fn parse(contents: &str) -> Option<Self> {
+        let mut played_at = None;
+        let mut score = None;
+        let mut secs = 0;
+        let mut favorite = false;
+        let mut run = None;
+        let mut notes = Vec::new();
+        for line in contents.lines() {
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue,
+            };
+            match key {
+                "played_at" => played_at = value.parse().ok(),
+                "score" => score = value.parse().ok(),
+                "secs" => secs = value.parse().unwrap_or(0),
+                "favorite" => favorite = value == "true",
+                "run" => run = RunInfo::parse(value),
+                "note" => {
+                    if let Some((at, note)) = value.split_once(' ') {
+                        if let Ok(at) = at.parse() {
+                            notes.push((at, note.to_string()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            played_at: played_at?,
+            score: score?,
+            secs,
+            favorite,
+            run: run?,
+            notes,
+            journal: Journal::parse(contents)?,
+        })
+    }
+
+    fn to_contents(&self) -> String {
+        let notes: String = self
+            .notes
+            .iter()
+            .map(|(at, note)| format!("note = {} {}\n", at, note))
+            .collect();
+        format!(
+            "played_at = {}\nscore = {}\nsecs = {}\nfavorite = {}\nrun = {}\n{}{}",
+            self.played_at,
+            self.score,
+            self.secs,
+            self.favorite,
+            self.run.to_line(),
+            notes,
+            self.journal.to_contents()
+        )
+    }
+}
+
+// Note: The list of saved replays on the replays screen. It only has room for
+// when each replay was played and what it scored, so the rest of the selected
+// one is spelled out under it.
+#[derive(Debug)]
+pub struct ReplayBrowser {
+    replays: Vec<Replay>,
+    selected: usize,
+}
+
+impl ReplayBrowser {
+    pub fn load() -> Self {
+        Self {
+            replays: Replay::load_all(),
+            selected: 0,
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.replays.is_empty() {
+            self.selected = (self.selected + self.replays.len() - 1) % self.replays.len();
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.replays.is_empty() {
+            self.selected = (self.selected + 1) % self.replays.len();
+        }
+    }
+
+    pub fn get_selected(&mut self) -> Option<&mut Replay> {
+        self.replays.get_mut(self.selected)
+    }
+
+    // Takes the file away as well, and the selection moves up past the end.
+    pub fn delete_selected(&mut self) -> io::Result<()> {
+        let deleted = self.replays.remove(self.selected).delete();
+        self.selected = self.selected.min(self.replays.len().saturating_sub(1));
+        deleted
+    }
+
+    // The screen for a board `width` wide and `height` high.
+    pub fn lines(&self, width: usize, height: usize) -> Vec<String> {
+        let mut lines = vec!["Replays".to_string(), String::new()];
+        let page_size = height.saturating_sub(BROWSER_LINES);
+        let first = (self.selected / page_size.max(1)) * page_size;
+        if self.replays.is_empty() {
+            lines.push("No replays yet".to_string());
+        }
+        for (i, replay) in self.replays.iter().enumerate().skip(first).take(page_size) {
+            let marker = if i == self.selected { '>' } else { ' ' };
+            let favorite = if replay.favorite { '*' } else { ' ' };
+            lines.push(format!(
+                "{}{}{} {:>4}",
+                marker,
+                favorite,
+                &replay.get_date()[5..],
+                replay.score
+            ));
+        }
+        lines.push(String::new());
+
+        let mut details = Vec::new();
+        if let Some(replay) = self.replays.get(self.selected) {
+            details.push(format!(
+                "{} {} ticks",
+                replay.get_duration(),
+                replay.journal.get_steps()
+            ));
+            details.push(format!("Board {}", replay.run.get_board_size()));
+            details.extend(wrap(&replay.run.mode.replace('+', " "), width));
+        }
+        details.resize(DETAIL_LINES, String::new());
+        lines.extend(details);
+        lines.push("Enter: open, q: back".to_string());
+
+        lines
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlaybackSpeed {
+    Half,
+    Normal,
+    Double,
+    Quadruple,
+}
+
+impl PlaybackSpeed {
+    pub fn all() -> [PlaybackSpeed; 4] {
+        [
+            PlaybackSpeed::Half,
+            PlaybackSpeed::Normal,
+            PlaybackSpeed::Double,
+            PlaybackSpeed::Quadruple,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlaybackSpeed::Half => "0.5x",
+            PlaybackSpeed::Normal => "1x",
+            PlaybackSpeed::Double => "2x",
+            PlaybackSpeed::Quadruple => "4x",
+        }
+    }
+
+    // How long a tick that took `interval` when it was played is shown for.
+    pub fn scale(&self, interval: Duration) -> Duration {
+        match self {
+            PlaybackSpeed::Half => interval * 2,
+            PlaybackSpeed::Normal => interval,
+            PlaybackSpeed::Double => interval / 2,
+            PlaybackSpeed::Quadruple => interval / 4,
+        }
+    }
+}
+
+// Everything playing a replay changes, as it was before a step.
+#[derive(Debug, Clone)]
+pub struct Keyframe {
+    pub step: u64,
+    pub state: GameState,
+    pub rival_policy: Option<Box<dyn Policy>>,
+    pub casual: bool,
+}
+
+// Note: The run a journal is played back into, which is the game's own state,
+// rival and casual interval borrowed, so what is drawn is what was played.
+#[derive(Debug)]
+pub struct Run<'a> {
+    pub state: &'a mut GameState,
+    pub rival_policy: &'a mut Option<Box<dyn Policy>>,
+    pub casual: &'a mut bool,
+}
+
+impl Run<'_> {
+    // Plays a step of the journal: what the player did before it, the rival's
+    // turn and the tick.
+    pub fn step(&mut self, journal: &Journal, step: u64) -> StepOutcome {
+        for event in journal.events_before(step) {
+            match event {
+                Event::Turn(towards) => self.state.turn(towards),
+                Event::Interval(interval) => {
+                    *self.casual = true;
+                    self.state.set_manual_interval(interval);
+                }
+                Event::Key(..) => {}
+            }
+        }
+        if let (Some(policy), Some(rival)) = (self.rival_policy.as_mut(), self.state.get_rival()) {
+            let direction = policy.choose(self.state, rival);
+            self.state.turn_rival(direction);
+        }
+        self.state.step()
+    }
+}
+
+// What a key does to a replay being watched, besides what Playback does itself.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Control {
+    Stop,
+    ForceQuit,
+    Seek(u64),
+    // Asks for a tick to jump to.
+    Jump,
+    // Asks for a note at the tick.
+    Note,
+}
+
+// Note: Where playback of a replay has got to, and how it goes on. Steps only
+// ever go forward, so going back starts again from the keyframe before the step
+// wanted and plays up to it. Keyframes are kept every KEYFRAME_INTERVAL steps
+// as the replay gets to them.
+#[derive(Debug)]
+pub struct Playback {
+    step: u64,
+    steps: u64,
+    speed: PlaybackSpeed,
+    paused: bool,
+    keyframes: Vec<Keyframe>,
+}
+
+impl Playback {
+    pub fn new(steps: u64) -> Self {
+        Self {
+            step: 0,
+            steps,
+            speed: PlaybackSpeed::Normal,
+            paused: false,
+            keyframes: Vec::new(),
+        }
+    }
+
+    pub fn get_step(&self) -> u64 {
+        self.step
+    }
+
+    pub fn get_steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn get_speed(&self) -> PlaybackSpeed {
+        self.speed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.steps
+    }
+
+    fn faster(&mut self) {
+        let speeds = PlaybackSpeed::all();
+        let i = speeds
+            .iter()
+            .position(|speed| *speed == self.speed)
+            .unwrap();
+        self.speed = speeds[(i + 1).min(speeds.len() - 1)];
+    }
+
+    fn slower(&mut self) {
+        let speeds = PlaybackSpeed::all();
+        let i = speeds
+            .iter()
+            .position(|speed| *speed == self.speed)
+            .unwrap();
+        self.speed = speeds[i.saturating_sub(1)];
+    }
+
+    // Note: P pauses, + and - change the speed, and left and right pause and go
+    // a tick back or forward. Enter and N pause for the game to ask for a tick
+    // to jump to or a note.
+    pub fn control(&mut self, command: Command) -> Option<Control> {
+        match command {
+            Command::Quit => return Some(Control::Stop),
+            Command::ForceQuit => return Some(Control::ForceQuit),
+            Command::Pause => self.paused = !self.paused,
+            Command::SpeedUp => self.faster(),
+            Command::SpeedDown => self.slower(),
+            Command::Turn(Direction::Right) => {
+                self.paused = true;
+                return Some(Control::Seek(self.step + 1));
+            }
+            Command::Turn(Direction::Left) => {
+                self.paused = true;
+                return Some(Control::Seek(self.step.saturating_sub(1)));
+            }
+            Command::Confirm => {
+                self.paused = true;
+                return Some(Control::Jump);
+            }
+            Command::Cancel => {
+                self.paused = true;
+                return Some(Control::Note);
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    // Plays the step the playback is on, keeping a keyframe first if it wants one.
+    pub fn step(&mut self, journal: &Journal, run: &mut Run) -> StepOutcome {
+        if self.needs_keyframe() {
+            self.keyframes.push(Keyframe {
+                step: self.step,
+                state: run.state.clone(),
+                rival_policy: run.rival_policy.clone(),
+                casual: *run.casual,
+            });
+        }
+        let outcome = run.step(journal, self.step);
+        self.step += 1;
+        outcome
+    }
+
+    // Stops on the last step when given one past it.
+    pub fn seek(&mut self, journal: &Journal, run: &mut Run, step: u64) {
+        let step = step.min(self.steps);
+        if let Some(keyframe) = self.rewind_to(step) {
+            *run.state = keyframe.state.clone();
+            *run.rival_policy = keyframe.rival_policy.clone();
+            *run.casual = keyframe.casual;
+        }
+        while self.step < step {
+            self.step(journal, run);
+        }
+    }
+
+    // Note: The keys pressed while waiting for the step light up as far into the
+    // wait as they were pressed into the tick when it was played, and stay lit
+    // until the next one. Until something is pressed, the keys that led to the
+    // step just played stay lit. Everything is lit right away when `elapsed` is
+    // None. Also returns when the next key lights up, if one does.
+    pub fn keys(
+        &self,
+        journal: &Journal,
+        elapsed: Option<Duration>,
+    ) -> (Vec<Direction>, Option<Duration>) {
+        let keys_at = |step| {
+            journal.events_before(step).filter_map(|event| match event {
+                Event::Key(direction, millis) => Some((
+                    direction,
+                    self.speed.scale(Duration::from_millis(millis.into())),
+                )),
+                _ => None,
+            })
+        };
+
+        let mut pressed = Vec::new();
+        let mut next_key = None;
+        for (direction, at) in keys_at(self.step) {
+            match elapsed {
+                Some(elapsed) if at > elapsed => {
+                    next_key = next_key.or(Some(at));
+                }
+                _ => pressed.push(direction),
+            }
+        }
+        if pressed.is_empty() && self.step > 0 {
+            pressed.extend(keys_at(self.step - 1).map(|(direction, _)| direction));
+        }
+
+        (pressed, next_key)
+    }
+
+    // Whether the step about to be played wants a keyframe that is not there yet.
+    fn needs_keyframe(&self) -> bool {
+        self.step.is_multiple_of(KEYFRAME_INTERVAL)
+            && self
+                .keyframes
+                .last()
+                .is_none_or(|keyframe| keyframe.step < self.step)
+    }
+
+    // The keyframe to start from to get to the step, or None when carrying on
+    // from the current step gets there sooner.
+    fn rewind_to(&mut self, step: u64) -> Option<&Keyframe> {
+        let keyframe = self
+            .keyframes
+            .iter()
+            .rev()
+            .find(|keyframe| keyframe.step <= step)?;
+        if step >= self.step && keyframe.step <= self.step {
+            return None;
+        }
+
+        self.step = keyframe.step;
+        Some(keyframe)
+    }
+
+    // e.g. `> ######------ 120/300 2x`, as wide as `width`.
+    pub fn progress_line(&self, width: usize) -> String {
+        let marker = if self.paused { "||" } else { ">" };
+        let numbers = format!("{}/{} {}", self.step, self.steps, self.speed.name());
+        let bar_width = width.saturating_sub(marker.len() + numbers.len() + 2);
+        let filled = (self.step * bar_width as u64)
+            .checked_div(self.steps)
+            .unwrap_or(0) as usize;
+        format!(
+            "{} {}{} {}",
+            marker,
+            "#".repeat(filled),
+            "-".repeat(bar_width - filled),
+            numbers
+        )
+    }
+}
+
+fn replays_dir() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join(REPLAYS_DIR))
+}
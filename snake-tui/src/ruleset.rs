@@ -0,0 +1,227 @@
+use crate::about::VERSION;
+use crate::config::Config;
+use crate::modifier::Modifier;
+use snake_core::boss::BOSS_HITS;
+use snake_core::food::CHAIN_LENGTH;
+use snake_core::state::{self, Crash, CHAIN_PENALTY, MAX_SPEED, RESPAWN_INVINCIBILITY};
+
+// Note: The ruleset is everything a run would be played with, spelled out for
+// tools, bots and other frontends that want to stay in step with the engine:
+// the rules themselves, the speeds, how scoring and crashes work and what each
+// modifier does. It is built from the same values the game uses, so it cannot
+// drift from what is played. Only the shape of the output is chosen here.
+pub fn to_json(config: &Config, width: u16, height: u16) -> String {
+    let mut out = String::new();
+    ruleset(config, width, height).write(&mut out, 0);
+    out
+}
+
+fn ruleset(config: &Config, width: u16, height: u16) -> Json {
+    let rules = config.rules();
+    let cells = width as u64 * height as u64;
+    let ticks_per_second = (0..=MAX_SPEED)
+        .map(|speed| {
+            let ticks = state::speed_ticks_per_second(speed) as u64;
+            Json::Number(if rules.double_speed { ticks * 2 } else { ticks })
+        })
+        .collect();
+    let fatal = Crash::all()
+        .iter()
+        .filter(|crash| match crash {
+            _ if rules.zen => false,
+            Crash::Border => !rules.wraps_around(),
+            _ => true,
+        })
+        .map(|crash| Json::text(crash.name()))
+        .collect();
+    let modifiers = Modifier::all()
+        .iter()
+        .map(|modifier| {
+            Json::Object(vec![
+                ("name", Json::text(modifier.name())),
+                ("label", Json::text(modifier.label())),
+                ("enabled", Json::Bool(config.modifiers.contains(*modifier))),
+                (
+                    "score_percent",
+                    Json::Number(modifier.score_percent() as u64),
+                ),
+                ("effect", Json::text(modifier.describe())),
+            ])
+        })
+        .collect();
+
+    Json::Object(vec![
+        ("version", Json::text(VERSION)),
+        (
+            "board",
+            Json::Object(vec![
+                ("width", Json::Number(width as u64)),
+                ("height", Json::Number(height as u64)),
+            ]),
+        ),
+        (
+            "rules",
+            Json::Object(vec![
+                ("food_reachability", Json::Bool(rules.food_reachability)),
+                ("rival", Json::Bool(rules.rival)),
+                (
+                    "rival_ai",
+                    config
+                        .rival_ai
+                        .map_or(Json::Null, |level| Json::text(level.name())),
+                ),
+                (
+                    "food_expiry_secs",
+                    Json::Number(rules.food_expiry_secs as u64),
+                ),
+                ("chain_food", Json::Bool(rules.chain_food)),
+                ("chain_length", Json::Number(CHAIN_LENGTH as u64)),
+                ("territory_secs", Json::Number(rules.territory_secs as u64)),
+                ("lives", Json::Number(rules.lives.max(1) as u64)),
+                ("laser_secs", Json::Number(rules.laser_secs as u64)),
+                ("boss", Json::Bool(rules.boss)),
+                ("boss_hits", Json::Number(BOSS_HITS as u64)),
+                ("endless_every", Json::Number(rules.endless_every as u64)),
+                ("risk", Json::Bool(rules.risk)),
+                ("zen", Json::Bool(rules.zen)),
+                ("hardcore", Json::Bool(config.hardcore)),
+                ("food_spawn", Json::text(rules.food_spawn.name())),
+                ("wrap_around", Json::Bool(rules.wraps_around())),
+                ("food_count", Json::Number(rules.food_count.max(1) as u64)),
+                ("obstacles", Json::Number(rules.obstacles as u64)),
+            ]),
+        ),
+        (
+            "speed",
+            Json::Object(vec![
+                ("start", Json::Number(rules.start_speed as u64)),
+                ("max", Json::Number(MAX_SPEED as u64)),
+                ("ramp", Json::text(rules.speed_ramp.name())),
+                ("model", Json::text(rules.speed_model.name())),
+                (
+                    "foods_per_step",
+                    rules
+                        .speed_ramp
+                        .foods_per_step(cells)
+                        .map_or(Json::Null, Json::Number),
+                ),
+                ("double_speed", Json::Bool(rules.double_speed)),
+                // One for every speed from 0 up.
+                ("ticks_per_second", Json::Array(ticks_per_second)),
+            ]),
+        ),
+        (
+            "scoring",
+            Json::Object(vec![
+                ("variant", Json::text(rules.scoring.name())),
+                ("chain_penalty", Json::Number(CHAIN_PENALTY)),
+                ("perfect_game_bonus", Json::Number(cells)),
+                (
+                    "score_percent",
+                    Json::Number(config.modifiers.score_percent() as u64),
+                ),
+                (
+                    "description",
+                    Json::Array(
+                        rules
+                            .describe_scoring()
+                            .into_iter()
+                            .map(Json::Text)
+                            .collect(),
+                    ),
+                ),
+            ]),
+        ),
+        (
+            "collisions",
+            Json::Object(vec![
+                ("fatal", Json::Array(fatal)),
+                ("tail_moves_away", Json::Bool(true)),
+                (
+                    "respawn_invincibility_ms",
+                    Json::Number(RESPAWN_INVINCIBILITY.as_millis() as u64),
+                ),
+            ]),
+        ),
+        ("modifiers", Json::Array(modifiers)),
+        (
+            "description",
+            Json::Array(rules.describe().into_iter().map(Json::Text).collect()),
+        ),
+    ])
+}
+
+// Just enough JSON for the ruleset: objects keep their keys in order, and
+// everything is written indented by two spaces.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(u64),
+    Text(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn text(text: &str) -> Self {
+        Json::Text(text.to_string())
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => out.push_str(&value.to_string()),
+            Json::Text(text) => write_string(out, text),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, indent + 1);
+                    item.write(out, indent + 1);
+                }
+                newline(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, indent + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                newline(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    out.push_str(&"  ".repeat(indent));
+}
+
+fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            character if (character as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => out.push(character),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,233 @@
+use crate::compress;
+use crate::paths;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const HEADER_PREFIX: &str = "# snake-rs ";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+// A lock this old was left behind by an instance that did not get to remove it.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10);
+
+// Turns the contents of one version of a file into the next version.
+pub type Migration = fn(&str) -> String;
+
+// Note: Every file the game saves in its data directory (see paths) starts
+// with a header naming the file and the version of its format,
+// e.g. `# snake-rs stats 1`. Older files are migrated one version at a time as
+// they are read. Files from a newer version are read as far as they are
+// understood, since unknown keys and fields are skipped, but they are never
+// overwritten so the newer version keeps what it wrote.
+#[derive(Debug)]
+pub struct SaveFile {
+    // Also the name of the file.
+    kind: &'static str,
+    // migrations[n] turns version n into version n + 1, so there is one per version.
+    migrations: &'static [Migration],
+    // Written gzipped with the `compression` feature, see compress.
+    compressed: bool,
+}
+
+impl SaveFile {
+    pub const fn new(kind: &'static str, migrations: &'static [Migration]) -> Self {
+        Self {
+            kind,
+            migrations,
+            compressed: false,
+        }
+    }
+
+    // For files that keep a run's turns, which grow with every tick.
+    pub const fn compressed(self) -> Self {
+        Self {
+            compressed: true,
+            ..self
+        }
+    }
+
+    pub fn get_version(&self) -> usize {
+        self.migrations.len()
+    }
+
+    pub fn path(&self) -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join(self.kind))
+    }
+
+    // Returns the contents without the header, in the current format.
+    pub fn load(&self) -> Option<String> {
+        let contents = compress::read(&self.path()?)
+            .ok()
+            .or_else(|| fs::read_to_string(paths::legacy_path(&self.legacy_name())?).ok())?;
+
+        Some(self.read(&contents))
+    }
+
+    // Like `load`, for contents that did not come from the file, e.g. from a profile bundle.
+    pub fn read(&self, contents: &str) -> String {
+        let (version, body) = self.split_header(contents);
+        self.migrations
+            .iter()
+            .skip(version)
+            .fold(body.to_string(), |body, migrate| migrate(&body))
+    }
+
+    pub fn with_header(&self, body: &str) -> String {
+        format!(
+            "{}{} {}\n{}",
+            HEADER_PREFIX,
+            self.kind,
+            self.get_version(),
+            body
+        )
+    }
+
+    // The whole file as it is written, header included.
+    pub fn to_bytes(&self, body: &str) -> Vec<u8> {
+        let contents = self.with_header(body);
+        if self.compressed {
+            compress::encode(&contents)
+        } else {
+            contents.into_bytes()
+        }
+    }
+
+    pub fn save(&self, body: &str) -> io::Result<()> {
+        let path = self.prepare_dir()?.join(self.kind);
+        if let Ok(contents) = compress::read(&path) {
+            if self.split_header(&contents).0 > self.get_version() {
+                return Err(io::Error::other(format!(
+                    "{} was written by a newer version of snake-rs",
+                    path.display()
+                )));
+            }
+        }
+
+        // Written next to the file and renamed over it, so it is never seen half written.
+        let temporary = path.with_file_name(format!("{}.{}.tmp", self.kind, process::id()));
+        fs::write(&temporary, self.to_bytes(body))?;
+        fs::rename(&temporary, &path)
+    }
+
+    // Note: Two instances finishing at once would each load the file, add their
+    // own result and save, and the second save would drop the first result. So
+    // loading and saving again happens while holding a lock, an extra file that
+    // only one instance can create at a time. The lock is released when the
+    // returned guard is dropped.
+    pub fn lock(&self) -> io::Result<SaveLock> {
+        let path = self.prepare_dir()?.join(format!("{}.lock", self.kind));
+        let started = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(SaveLock { path }),
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                    if is_stale(&path) {
+                        fs::remove_file(&path).ok();
+                    } else if started.elapsed() >= LOCK_TIMEOUT {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WouldBlock,
+                            format!("{} is held by another instance", path.display()),
+                        ));
+                    } else {
+                        thread::sleep(LOCK_RETRY_INTERVAL);
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn prepare_dir(&self) -> io::Result<PathBuf> {
+        let dir = paths::data_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no data directory could be determined",
+            )
+        })?;
+        fs::create_dir_all(&dir)?;
+
+        Ok(dir)
+    }
+
+    fn legacy_name(&self) -> String {
+        format!(".snake-rs.{}", self.kind)
+    }
+
+    // Files from before versioning have no header and count as version 0.
+    fn split_header<'a>(&self, contents: &'a str) -> (usize, &'a str) {
+        let (first, rest) = contents.split_once('\n').unwrap_or((contents, ""));
+        let version = first
+            .strip_prefix(HEADER_PREFIX)
+            .and_then(|header| header.trim().strip_prefix(self.kind))
+            .and_then(|version| version.trim().parse().ok());
+
+        match version {
+            Some(version) => (version, rest),
+            None => (0, contents),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SaveLock {
+    path: PathBuf,
+}
+
+impl Drop for SaveLock {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+fn is_stale(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age >= STALE_LOCK_AGE)
+}
+
+// The first versioned format of every file is the same as the unversioned one.
+pub fn unchanged(body: &str) -> String {
+    body.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    // Version 1 renamed `best` to `high`.
+    fn rename_best(body: &str) -> String {
+        body.replace("best", "high")
+    }
+
+    const TEST_FILE: SaveFile = SaveFile::new("test", &[unchanged, rename_best]);
+
+    #[test]
+    fn old_files_are_migrated_to_the_current_version() {
+        assert_eq!(TEST_FILE.read("best 40\n"), "high 40\n");
+        assert_eq!(TEST_FILE.read("# snake-rs test 1\nbest 40\n"), "high 40\n");
+        assert_eq!(TEST_FILE.read("# snake-rs test 2\nbest 40\n"), "best 40\n");
+    }
+
+    #[test]
+    fn files_from_a_newer_version_are_not_overwritten() {
+        let dir = env::temp_dir().join(format!("snake-rs-savefile-{}", process::id()));
+        env::set_var(paths::HOME_VAR, &dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test");
+        let newer = "# snake-rs test 3\nhigh 40\nlow 2\n";
+        fs::write(&path, newer).unwrap();
+
+        let saved = TEST_FILE.save("high 10\n");
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(saved.is_err());
+        assert_eq!(contents, newer);
+    }
+}
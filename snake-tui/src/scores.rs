@@ -0,0 +1,583 @@
+use crate::about::RunInfo;
+use crate::challenge;
+use crate::menu::wrap;
+use crate::savefile::{self, SaveFile, SaveLock};
+use std::cmp::Reverse;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCORES_FILE: SaveFile = SaveFile::new("scores", &[savefile::unchanged]);
+// Ranking points are the score scaled to a board with this many cells, the default 20x20.
+const REFERENCE_CELLS: u64 = 400;
+// Lines of the high score screen besides the scores: the title, filters, column names, count, details and hint.
+const BROWSER_LINES: usize = 14;
+// Lines of details about the selected score, under the list.
+const DETAIL_LINES: usize = 3;
+
+// A scored game, with when it was played and by whom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreEntry {
+    pub score: u64,
+    // Seconds since the Unix epoch.
+    pub played_at: u64,
+    // The `player` the game was played as, which tells imported scores from one's own.
+    pub profile: String,
+    pub run: RunInfo,
+}
+
+impl ScoreEntry {
+    pub fn new(score: u64, profile: &str, run: &RunInfo) -> Self {
+        let played_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Self {
+            score,
+            played_at,
+            profile: profile.to_string(),
+            run: run.clone(),
+        }
+    }
+
+    // Note: Filling the board is what a score is up against, so ranking points
+    // are the score scaled by how many cells the board has against a 20x20 one:
+    // 50 on a 10x10 board is worth 200, and 200 on a 40x20 board 100. Modifiers
+    // are already counted in the score, and the scores of scoring rules other
+    // than classic are only comparable among themselves, as the mode says.
+    pub fn get_points(&self) -> u64 {
+        let cells = (self.run.width as u64 * self.run.height as u64).max(1);
+        (self.score * REFERENCE_CELLS + cells / 2) / cells
+    }
+
+    // e.g. `2026-10-14`, in UTC.
+    pub fn get_date(&self) -> String {
+        challenge::date_label(self.played_at / 86_400)
+    }
+
+    // Note: One line per game, the run info followed by the details,
+    // e.g. `version=0.1.0 seed=1f2e mode=classic board=20x20 score=12 played_at=1791979200 profile=eli`.
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} score={} played_at={} profile={}",
+            self.run.to_line(),
+            self.score,
+            self.played_at,
+            self.profile
+        )
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut entry = Self {
+            score: 0,
+            played_at: 0,
+            profile: String::new(),
+            run: RunInfo::parse(line)?,
+        };
+        for field in line.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            match key {
+                "score" => entry.score = value.parse().ok()?,
+                "played_at" => entry.played_at = value.parse().ok()?,
+                "profile" => entry.profile = value.to_string(),
+                _ => {}
+            }
+        }
+
+        Some(entry)
+    }
+}
+
+// What the scores are ranked by, the score itself or its ranking points.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Ranking {
+    Score,
+    Points,
+}
+
+impl Ranking {
+    pub fn all() -> [Ranking; 2] {
+        [Ranking::Score, Ranking::Points]
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|ranking| ranking.name() == value)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ranking::Score => "score",
+            Ranking::Points => "points",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DateRange {
+    All,
+    Today,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateRange {
+    pub fn all() -> [DateRange; 5] {
+        [
+            DateRange::All,
+            DateRange::Today,
+            DateRange::Week,
+            DateRange::Month,
+            DateRange::Year,
+        ]
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|range| range.name() == value)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DateRange::All => "all",
+            DateRange::Today => "today",
+            DateRange::Week => "week",
+            DateRange::Month => "month",
+            DateRange::Year => "year",
+        }
+    }
+
+    // How many days back from today the range goes, today included.
+    fn days(&self) -> Option<u64> {
+        match self {
+            DateRange::All => None,
+            DateRange::Today => Some(1),
+            DateRange::Week => Some(7),
+            DateRange::Month => Some(30),
+            DateRange::Year => Some(365),
+        }
+    }
+}
+
+// Note: What the scores are narrowed down to. A mode matches any of the parts
+// of a run's mode, e.g. `chain` matches `classic+chain+wrap`, so a filter does
+// not have to name every setting the run was played with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreFilter {
+    pub mode: Option<String>,
+    pub board: Option<(u16, u16)>,
+    pub date: DateRange,
+    pub profile: Option<String>,
+}
+
+impl Default for ScoreFilter {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            board: None,
+            date: DateRange::All,
+            profile: None,
+        }
+    }
+}
+
+impl ScoreFilter {
+    // `today` counts days since the Unix epoch, as challenge::days_since_epoch.
+    pub fn matches(&self, entry: &ScoreEntry, today: u64) -> bool {
+        if let Some(mode) = &self.mode {
+            if !entry.run.mode.split('+').any(|part| part == mode) {
+                return false;
+            }
+        }
+        if let Some(board) = self.board {
+            if (entry.run.width, entry.run.height) != board {
+                return false;
+            }
+        }
+        if let Some(days) = self.date.days() {
+            if entry.played_at / 86_400 + days <= today {
+                return false;
+            }
+        }
+        if let Some(profile) = &self.profile {
+            if entry.profile != *profile {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+// Note: Every scored game is kept, not just the best few, so the scores can be
+// narrowed down to a mode or a board long after they were set. Entries are
+// kept in the order they were played and sorted when they are shown.
+#[derive(Debug, Default)]
+pub struct ScoreHistory {
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl ScoreHistory {
+    pub fn load() -> Self {
+        SCORES_FILE
+            .load()
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    // The whole file, header included, as it goes into a profile bundle.
+    pub fn to_file(&self) -> String {
+        SCORES_FILE.with_header(&self.to_contents())
+    }
+
+    pub fn from_file(contents: &str) -> Self {
+        Self::parse(&SCORES_FILE.read(contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        Self {
+            entries: contents.lines().filter_map(ScoreEntry::parse).collect(),
+        }
+    }
+
+    // Held from loading until saving, see SaveFile::lock.
+    pub fn lock() -> io::Result<SaveLock> {
+        SCORES_FILE.lock()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        SCORES_FILE.save(&self.to_contents())
+    }
+
+    fn to_contents(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}\n", entry.to_line()))
+            .collect()
+    }
+
+    // Games that are in both histories are only kept once.
+    pub fn merge(&mut self, other: Self) {
+        for entry in other.entries {
+            if !self.entries.contains(&entry) {
+                self.entries.push(entry);
+            }
+        }
+        self.entries.sort_by_key(|entry| entry.played_at);
+    }
+
+    pub fn record(&mut self, entry: ScoreEntry) {
+        self.entries.push(entry);
+    }
+
+    // The entries the filter lets through, best first and the earlier of equal ones first.
+    pub fn ranked(&self, filter: &ScoreFilter, ranking: Ranking) -> Vec<&ScoreEntry> {
+        let today = challenge::days_since_epoch();
+        let mut ranked: Vec<&ScoreEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| filter.matches(entry, today))
+            .collect();
+        ranked.sort_by_key(|entry| {
+            let value = match ranking {
+                Ranking::Score => entry.score,
+                Ranking::Points => entry.get_points(),
+            };
+            (Reverse(value), entry.played_at)
+        });
+        ranked
+    }
+
+    // The parts of modes the entries were played with, for picking a mode filter.
+    pub fn modes(&self) -> Vec<String> {
+        let mut modes: Vec<String> = self
+            .entries
+            .iter()
+            .flat_map(|entry| entry.run.mode.split('+'))
+            .map(|part| part.to_string())
+            .collect();
+        modes.sort();
+        modes.dedup();
+        modes
+    }
+
+    pub fn boards(&self) -> Vec<(u16, u16)> {
+        let mut boards: Vec<(u16, u16)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.run.width, entry.run.height))
+            .collect();
+        boards.sort();
+        boards.dedup();
+        boards
+    }
+
+    pub fn profiles(&self) -> Vec<String> {
+        let mut profiles: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| entry.profile.clone())
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+        profiles
+    }
+}
+
+// The filters of the high score screen, in the order they are listed, and
+// what the scores are ranked by under them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FilterKind {
+    Mode,
+    Board,
+    Date,
+    Profile,
+    Ranking,
+}
+
+impl FilterKind {
+    pub fn all() -> [FilterKind; 5] {
+        [
+            FilterKind::Mode,
+            FilterKind::Board,
+            FilterKind::Date,
+            FilterKind::Profile,
+            FilterKind::Ranking,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterKind::Mode => "Mode",
+            FilterKind::Board => "Board",
+            FilterKind::Date => "Date",
+            FilterKind::Profile => "Profile",
+            FilterKind::Ranking => "Rank by",
+        }
+    }
+}
+
+// Note: The high score screen lists the filters above the scores, and the same
+// up and down commands move through both: on a filter, left and right change
+// it, and on the scores they go a page up or down. The list scrolls to keep the
+// selected score on the page.
+#[derive(Debug)]
+pub struct ScoreBrowser {
+    history: ScoreHistory,
+    filter: ScoreFilter,
+    ranking: Ranking,
+    // Filters first, then the scores.
+    selected: usize,
+    // The first score on the page.
+    scroll: usize,
+    page_size: usize,
+}
+
+impl ScoreBrowser {
+    // For a board `height` high, which decides how many scores fit on a page.
+    pub fn load(height: usize) -> Self {
+        Self {
+            history: ScoreHistory::load(),
+            filter: ScoreFilter::default(),
+            ranking: Ranking::Score,
+            selected: FilterKind::all().len(),
+            scroll: 0,
+            page_size: height.saturating_sub(BROWSER_LINES).max(1),
+        }
+    }
+
+    // The filter the selection is on, if it is not on a score.
+    pub fn get_selected_filter(&self) -> Option<FilterKind> {
+        FilterKind::all().get(self.selected).copied()
+    }
+
+    // The place of the selected score in the ranking, if a score is selected.
+    pub fn get_selected_rank(&self) -> Option<usize> {
+        self.selected.checked_sub(FilterKind::all().len())
+    }
+
+    pub fn ranked(&self) -> Vec<&ScoreEntry> {
+        self.history.ranked(&self.filter, self.ranking)
+    }
+
+    // The places of the scores on the page, from the first to one past the last.
+    pub fn page(&self) -> (usize, usize) {
+        let count = self.ranked().len();
+        (self.scroll, (self.scroll + self.page_size).min(count))
+    }
+
+    pub fn filter_label(&self, kind: FilterKind) -> String {
+        let value = match kind {
+            FilterKind::Mode => self.filter.mode.clone(),
+            FilterKind::Board => self
+                .filter
+                .board
+                .map(|(width, height)| format!("{}x{}", width, height)),
+            FilterKind::Date => Some(self.filter.date.name().to_string()),
+            FilterKind::Profile => self.filter.profile.clone(),
+            FilterKind::Ranking => Some(self.ranking.name().to_string()),
+        };
+        value.unwrap_or_else(|| "all".to_string())
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+        self.scroll_to_selected();
+    }
+
+    pub fn select_next(&mut self) {
+        let last = FilterKind::all().len() + self.ranked().len().max(1) - 1;
+        self.selected = (self.selected + 1).min(last);
+        self.scroll_to_selected();
+    }
+
+    // Changes the selected filter to its next value, or goes a page down.
+    pub fn next(&mut self) {
+        match self.get_selected_filter() {
+            Some(kind) => self.cycle_filter(kind, true),
+            None => self.move_by_page(true),
+        }
+    }
+
+    pub fn previous(&mut self) {
+        match self.get_selected_filter() {
+            Some(kind) => self.cycle_filter(kind, false),
+            None => self.move_by_page(false),
+        }
+    }
+
+    // Note: The screen for a board `width` wide. The list only has room for the
+    // place, score and ranking points of each score, so the rest of the
+    // selected one is spelled out under it.
+    pub fn lines(&self, width: usize) -> Vec<String> {
+        let mut lines = vec!["High scores".to_string()];
+        let filters: Vec<String> = FilterKind::all()
+            .iter()
+            .map(|kind| {
+                let marker = if self.get_selected_filter() == Some(*kind) {
+                    '>'
+                } else {
+                    ' '
+                };
+                format!("{} {}: {}", marker, kind.name(), self.filter_label(*kind))
+            })
+            .collect();
+        // Lined up on the left rather than centred one by one.
+        let filter_width = filters.iter().map(|line| line.len()).max().unwrap_or(0);
+        lines.extend(
+            filters
+                .iter()
+                .map(|line| format!("{:<width$}", line, width = filter_width)),
+        );
+        lines.push(String::new());
+
+        let ranked = self.ranked();
+        let (first, last) = self.page();
+        if ranked.is_empty() {
+            lines.push("No high scores yet".to_string());
+        } else {
+            lines.push(format!(" {:>4} {:>5} {:>6}", "#", "Score", "Points"));
+        }
+        for (i, entry) in ranked.iter().enumerate().take(last).skip(first) {
+            let marker = if self.get_selected_rank() == Some(i) {
+                '>'
+            } else {
+                ' '
+            };
+            lines.push(format!(
+                "{}{:>3}. {:>5} {:>6}",
+                marker,
+                i + 1,
+                entry.score,
+                entry.get_points()
+            ));
+        }
+        lines.push(String::new());
+
+        let mut details = Vec::new();
+        if !ranked.is_empty() {
+            lines.push(format!("{}-{} of {}", first + 1, last, ranked.len()));
+            if let Some(entry) = self.get_selected_rank().and_then(|i| ranked.get(i)) {
+                details.push(format!(
+                    "{} {}",
+                    entry.get_date(),
+                    entry.run.get_board_size()
+                ));
+                let text = format!("{}: {}", entry.profile, entry.run.mode.replace('+', " "));
+                details.extend(wrap(&text, width));
+            }
+        }
+        details.resize(DETAIL_LINES, String::new());
+        lines.extend(details);
+        lines.push("Arrows, q: back".to_string());
+
+        lines
+    }
+
+    fn move_by_page(&mut self, down: bool) {
+        let count = self.ranked().len();
+        let rank = self.get_selected_rank().unwrap_or(0);
+        let rank = if down {
+            (rank + self.page_size).min(count.saturating_sub(1))
+        } else {
+            rank.saturating_sub(self.page_size)
+        };
+        self.selected = FilterKind::all().len() + rank;
+        self.scroll = rank - rank % self.page_size;
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let rank = self.get_selected_rank().unwrap_or(0);
+        if rank < self.scroll {
+            self.scroll = rank;
+        } else if rank >= self.scroll + self.page_size {
+            self.scroll = rank + 1 - self.page_size;
+        }
+    }
+
+    // Note: Every filter goes through `all` and then each value the history has,
+    // wrapping around, and changing one starts the list from the top again.
+    fn cycle_filter(&mut self, kind: FilterKind, forward: bool) {
+        match kind {
+            FilterKind::Mode => {
+                self.filter.mode = cycle(&self.history.modes(), &self.filter.mode, forward)
+            }
+            FilterKind::Board => {
+                self.filter.board = cycle(&self.history.boards(), &self.filter.board, forward)
+            }
+            FilterKind::Date => {
+                let ranges = &DateRange::all()[1..];
+                let current = Some(self.filter.date).filter(|date| *date != DateRange::All);
+                self.filter.date = cycle(ranges, &current, forward).unwrap_or(DateRange::All);
+            }
+            FilterKind::Profile => {
+                self.filter.profile = cycle(&self.history.profiles(), &self.filter.profile, forward)
+            }
+            FilterKind::Ranking => {
+                self.ranking = match self.ranking {
+                    Ranking::Score => Ranking::Points,
+                    Ranking::Points => Ranking::Score,
+                }
+            }
+        }
+        self.scroll = 0;
+    }
+}
+
+fn cycle<T: Clone + PartialEq>(values: &[T], current: &Option<T>, forward: bool) -> Option<T> {
+    let position = current
+        .as_ref()
+        .and_then(|current| values.iter().position(|value| value == current));
+    // `all` comes before the first value.
+    let index = match (position, forward) {
+        (None, true) => Some(0),
+        (None, false) => values.len().checked_sub(1),
+        (Some(position), true) => Some(position + 1).filter(|next| *next < values.len()),
+        (Some(position), false) => position.checked_sub(1),
+    };
+    index.and_then(|index| values.get(index).cloned())
+}
@@ -0,0 +1,97 @@
+use crate::about::RunInfo;
+use crate::savefile::{self, SaveFile, SaveLock};
+use std::io;
+
+const STATS_FILE: SaveFile = SaveFile::new("stats", &[savefile::unchanged]);
+
+// Note: Stats are stored in the same `key = value` format as the config file.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub games_played: u64,
+    pub perfect_games: u64,
+    pub best_score: u64,
+    // Which version, seed, mode and board the best score was set on.
+    pub best_run: Option<RunInfo>,
+}
+
+impl Stats {
+    pub fn load() -> Self {
+        STATS_FILE
+            .load()
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    // The whole file, header included, as it goes into a profile bundle.
+    pub fn to_file(&self) -> String {
+        STATS_FILE.with_header(&self.to_contents())
+    }
+
+    pub fn from_file(contents: &str) -> Self {
+        Self::parse(&STATS_FILE.read(contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut stats = Self::default();
+        for line in contents.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "best_run" {
+                    stats.best_run = RunInfo::parse(value.trim());
+                    continue;
+                }
+                let value = value.trim().parse().unwrap_or(0);
+                match key.trim() {
+                    "games_played" => stats.games_played = value,
+                    "perfect_games" => stats.perfect_games = value,
+                    "best_score" => stats.best_score = value,
+                    _ => {}
+                }
+            }
+        }
+
+        stats
+    }
+
+    // Held from loading until saving, see SaveFile::lock.
+    pub fn lock() -> io::Result<SaveLock> {
+        STATS_FILE.lock()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        STATS_FILE.save(&self.to_contents())
+    }
+
+    fn to_contents(&self) -> String {
+        let mut contents = format!(
+            "games_played = {}\nperfect_games = {}\nbest_score = {}\n",
+            self.games_played, self.perfect_games, self.best_score
+        );
+        if let Some(run) = &self.best_run {
+            contents.push_str(&format!("best_run = {}\n", run.to_line()));
+        }
+        contents
+    }
+
+    // Note: The same games may well be counted on both sides, e.g. when a profile
+    // is imported twice, so the counts are not added up: the larger ones are kept,
+    // along with the best score.
+    pub fn merge(&mut self, other: Self) {
+        self.games_played = self.games_played.max(other.games_played);
+        self.perfect_games = self.perfect_games.max(other.perfect_games);
+        if other.best_score > self.best_score {
+            self.best_score = other.best_score;
+            self.best_run = other.best_run;
+        }
+    }
+
+    pub fn record_game(&mut self, score: u64, perfect: bool, run: &RunInfo) {
+        self.games_played += 1;
+        if score > self.best_score {
+            self.best_score = score;
+            self.best_run = Some(run.clone());
+        }
+        if perfect {
+            self.perfect_games += 1;
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BLOCK_SIZE: usize = 512;
+const NAME_LENGTH: usize = 100;
+
+// Note: Just enough of the ustar format for profile bundles: a flat list of
+// regular files with short names. Every file is a 512 byte header followed by
+// its contents padded to whole blocks, and two empty blocks end the archive.
+// Anything else found in an archive, like directories, is skipped.
+pub fn write(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+
+    let mut archive = Vec::new();
+    for (name, contents) in files {
+        let mut header = [0u8; BLOCK_SIZE];
+        let name = &name.as_bytes()[..name.len().min(NAME_LENGTH)];
+        header[..name.len()].copy_from_slice(name);
+        put_octal(&mut header[100..108], 0o644);
+        put_octal(&mut header[108..116], 0);
+        put_octal(&mut header[116..124], 0);
+        put_octal(&mut header[124..136], contents.len() as u64);
+        put_octal(&mut header[136..148], mtime);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        // The checksum is worked out with its own field filled with spaces.
+        header[148..156].copy_from_slice(b"        ");
+        let checksum = header.iter().map(|byte| *byte as u64).sum();
+        put_octal(&mut header[148..155], checksum);
+
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(contents);
+        archive.resize(padded(archive.len()), 0);
+    }
+    archive.resize(archive.len() + 2 * BLOCK_SIZE, 0);
+
+    archive
+}
+
+pub fn read(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut files = Vec::new();
+    let mut offset = 0;
+    while offset + BLOCK_SIZE <= archive.len() {
+        let header = &archive[offset..offset + BLOCK_SIZE];
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+
+        let checksum: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| if (148..156).contains(&i) { b' ' } else { *byte } as u64)
+            .sum();
+        if parse_octal(&header[148..156]) != Some(checksum) {
+            return Err(format!("bad header checksum at byte {}", offset));
+        }
+        let size = parse_octal(&header[124..136])
+            .ok_or_else(|| format!("bad file size at byte {}", offset))?
+            as usize;
+        let start = offset + BLOCK_SIZE;
+        let contents = archive
+            .get(start..start + size)
+            .ok_or_else(|| "the archive is cut short".to_string())?;
+
+        // A type of 0, or NUL in old archives, is a regular file.
+        if header[156] == b'0' || header[156] == 0 {
+            let name_end = header[..NAME_LENGTH]
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(NAME_LENGTH);
+            let name = String::from_utf8_lossy(&header[..name_end]).to_string();
+            files.push((name, contents.to_vec()));
+        }
+        offset = padded(start + size);
+    }
+
+    Ok(files)
+}
+
+// Fills the field with zero padded octal digits followed by a NUL.
+fn put_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let digits = format!("{:0width$o}", value, width = width);
+    field[..width].copy_from_slice(&digits.as_bytes()[digits.len() - width..]);
+    field[width] = 0;
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let digits = String::from_utf8_lossy(field);
+    let digits = digits.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(digits, 8).ok()
+}
+
+fn padded(length: usize) -> usize {
+    length.div_ceil(BLOCK_SIZE) * BLOCK_SIZE
+}
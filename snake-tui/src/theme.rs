@@ -0,0 +1,100 @@
+use crossterm::style::Color;
+use snake_core::state::MAX_SPEED;
+
+// Note: Themes and skins only change colors. Both are unlocked in the campaign,
+// and picking one that is still locked falls back to the classic look.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Theme {
+    Classic,
+    Night,
+    // Always used in hardcore mode, and never unlocked for anything else.
+    Hardcore,
+}
+
+impl Theme {
+    pub fn parse(theme: &str) -> Option<Self> {
+        match theme.to_lowercase().as_str() {
+            "classic" => Some(Self::Classic),
+            "night" => Some(Self::Night),
+            "hardcore" => Some(Self::Hardcore),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Night => "night",
+            Self::Hardcore => "hardcore",
+        }
+    }
+
+    pub fn border_color(&self) -> Color {
+        match self {
+            Self::Classic => Color::DarkGrey,
+            Self::Night => Color::DarkBlue,
+            Self::Hardcore => Color::Red,
+        }
+    }
+
+    pub fn background(&self) -> Color {
+        match self {
+            Self::Classic => Color::Reset,
+            Self::Night => Color::AnsiValue(17),
+            Self::Hardcore => Color::AnsiValue(52),
+        }
+    }
+
+    // Note: 256-color palette numbers the classic skin goes through from the
+    // slowest speed to the fastest, getting brighter and warmer as it speeds up.
+    pub fn speed_ramp(&self) -> &'static [u8] {
+        match self {
+            Self::Classic => &[22, 28, 34, 40, 46, 82, 118, 154, 190],
+            Self::Night => &[24, 25, 31, 32, 38, 39, 45, 51, 123],
+            Self::Hardcore => &[88, 124, 160, 196, 202, 208, 214, 220, 226],
+        }
+    }
+}
+
+// Picks the color for `speed` along a ramp, which can have any number of colors.
+pub fn ramp_color(ramp: &[u8], speed: u16) -> Color {
+    let index = speed.min(MAX_SPEED) as usize * (ramp.len() - 1) / MAX_SPEED as usize;
+    Color::AnsiValue(ramp[index])
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Skin {
+    Classic,
+    Gold,
+    Neon,
+}
+
+impl Skin {
+    pub fn parse(skin: &str) -> Option<Self> {
+        match skin.to_lowercase().as_str() {
+            "classic" => Some(Self::Classic),
+            "gold" => Some(Self::Gold),
+            "neon" => Some(Self::Neon),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Gold => "gold",
+            Self::Neon => "neon",
+        }
+    }
+
+    // Returns None for the classic skin, whose color follows the speed instead.
+    pub fn segment_color(&self, index: usize) -> Option<Color> {
+        match self {
+            Self::Classic => None,
+            Self::Gold if index == 0 => Some(Color::White),
+            Self::Gold => Some(Color::Yellow),
+            Self::Neon if index.is_multiple_of(2) => Some(Color::Magenta),
+            Self::Neon => Some(Color::Cyan),
+        }
+    }
+}
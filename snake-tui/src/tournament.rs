@@ -0,0 +1,103 @@
+use std::cmp::Reverse;
+
+// Note: A hotseat tournament is played in rounds. Every round has its own seed,
+// and each player plays it once in turn; the best score wins the round. The
+// standings only last for the session.
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub name: String,
+    pub round_wins: u16,
+    pub total_score: u64,
+    // The score of every round played so far.
+    pub scores: Vec<u64>,
+}
+
+#[derive(Debug)]
+pub struct Tournament {
+    rounds: u16,
+    round: u16,
+    standings: Vec<Standing>,
+}
+
+impl Tournament {
+    pub fn new(players: &[String], rounds: u16) -> Self {
+        let standings = players
+            .iter()
+            .map(|name| Standing {
+                name: name.clone(),
+                round_wins: 0,
+                total_score: 0,
+                scores: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            rounds: rounds.max(1),
+            round: 0,
+            standings,
+        }
+    }
+
+    pub fn get_rounds(&self) -> u16 {
+        self.rounds
+    }
+
+    // The round being played, starting at 0.
+    pub fn get_round(&self) -> u16 {
+        self.round
+    }
+
+    pub fn get_players(&self) -> impl Iterator<Item = &str> {
+        self.standings.iter().map(|standing| standing.name.as_str())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.round >= self.rounds
+    }
+
+    pub fn record_score(&mut self, player: usize, score: u64) {
+        let standing = &mut self.standings[player];
+        standing.scores.push(score);
+        standing.total_score = standing.total_score.saturating_add(score);
+    }
+
+    // Note: Every player with the best score of the round is given the win, so a
+    // tie counts for all of them. Returns the names of the round's winners.
+    pub fn finish_round(&mut self) -> Vec<String> {
+        let round = self.round as usize;
+        let best = self
+            .standings
+            .iter()
+            .filter_map(|standing| standing.scores.get(round))
+            .max()
+            .copied();
+        let mut winners = Vec::new();
+        for standing in self.standings.iter_mut() {
+            if best.is_some() && standing.scores.get(round).copied() == best {
+                standing.round_wins += 1;
+                winners.push(standing.name.clone());
+            }
+        }
+        self.round += 1;
+
+        winners
+    }
+
+    // Ranked by rounds won, then by total score.
+    pub fn get_standings(&self) -> Vec<&Standing> {
+        let mut standings: Vec<&Standing> = self.standings.iter().collect();
+        standings.sort_by_key(|standing| Reverse((standing.round_wins, standing.total_score)));
+        standings
+    }
+
+    // Returns None when the top of the standings is a tie.
+    pub fn get_champion(&self) -> Option<&Standing> {
+        let standings = self.get_standings();
+        let rank = |standing: &Standing| (standing.round_wins, standing.total_score);
+        match standings.as_slice() {
+            [first, second, ..] if rank(first) == rank(second) => None,
+            [first, ..] => Some(first),
+            [] => None,
+        }
+    }
+}
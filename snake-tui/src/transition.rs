@@ -0,0 +1,81 @@
+use crate::frame::{Frame, FrameCell};
+use crossterm::style::Color;
+use std::time::Duration;
+
+pub const STEPS: u16 = 10;
+pub const STEP_INTERVAL: Duration = Duration::from_millis(20);
+const CURTAIN_GLYPH: char = '#';
+const CURTAIN_COLOR: Color = Color::DarkGrey;
+const FADED_COLOR: Color = Color::DarkGrey;
+
+// Note: A short animation from the screen that is shown to the next one, played
+// by the renderer on the next present. Each only decides what a cell looks like
+// at a given step, so any two screens of the same size can be animated between.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Transition {
+    // The new screen sweeps in from the left, column by column.
+    Wipe,
+    // A row of `#` comes down, with the new screen behind it.
+    Curtain,
+    // The old screen dims, then the new one appears dimmed and brightens.
+    Fade,
+}
+
+impl Transition {
+    // The frame at `step`, from 0 up to (but not including) STEPS.
+    pub fn frame_at(&self, from: &Frame, to: &Frame, step: u16) -> Frame {
+        let (width, height) = (to.get_width(), to.get_height());
+        let mut frame = to.clone();
+        for y in 0..height {
+            for x in 0..width {
+                let old = *from.get(x, y).unwrap();
+                let new = *to.get(x, y).unwrap();
+                frame.set(x, y, self.cell_at(old, new, x, y, step, (width, height)));
+            }
+        }
+
+        frame
+    }
+
+    fn cell_at(
+        &self,
+        old: FrameCell,
+        new: FrameCell,
+        x: u16,
+        y: u16,
+        step: u16,
+        (width, height): (u16, u16),
+    ) -> FrameCell {
+        match self {
+            Self::Wipe => {
+                if x < width * (step + 1) / STEPS {
+                    new
+                } else {
+                    old
+                }
+            }
+            Self::Curtain => {
+                let edge = height * (step + 1) / STEPS;
+                if y + 1 == edge {
+                    FrameCell {
+                        glyph: CURTAIN_GLYPH,
+                        fg: CURTAIN_COLOR,
+                        bg: Color::Reset,
+                    }
+                } else if y < edge {
+                    new
+                } else {
+                    old
+                }
+            }
+            Self::Fade => {
+                let cell = if step < STEPS / 2 { old } else { new };
+                FrameCell {
+                    fg: FADED_COLOR,
+                    bg: Color::Reset,
+                    ..cell
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use crate::frame::{Frame, Viewport};
+use crate::input::TurnQueue;
+use crossterm::style::Color;
+use snake_core::direction::Direction;
+use snake_core::map::Map;
+use snake_core::policy::Policy;
+use snake_core::rules::Rules;
+use snake_core::state::{GameState, StepOutcome};
+use std::time::Instant;
+
+pub const PLAYER_NAMES: [&str; 2] = ["P1", "P2"];
+pub const PLAYER_COLORS: [Color; 2] = [Color::Green, Color::Cyan];
+// Columns between boards side by side, for the score comparison bar.
+const BAR_COLUMNS: u16 = 4;
+
+// Note: One player's side of the race: a board of their own with its own turns
+// and its own tick, since the snakes speed up separately.
+#[derive(Debug)]
+pub struct Racer {
+    pub state: GameState,
+    pub turns: TurnQueue,
+    pub next_tick: Instant,
+    // How the board ended, once it has.
+    pub outcome: Option<StepOutcome>,
+}
+
+impl Racer {
+    pub fn is_playing(&self) -> bool {
+        self.outcome.is_none()
+    }
+
+    // Schedules the next tick an interval after the last, or an interval from
+    // now when the board has fallen a whole interval behind.
+    pub fn schedule(&mut self, now: Instant) {
+        let interval = self.state.calculate_interval();
+        self.next_tick = if self.next_tick + interval + interval > now {
+            self.next_tick + interval
+        } else {
+            now + interval
+        };
+    }
+
+    // Turns the snake and moves it on a tick, steering the board's rival with
+    // `rival_policy` if there is one.
+    pub fn step(&mut self, rival_policy: &mut Option<Box<dyn Policy>>) {
+        if self.state.can_turn() {
+            if let Some((towards, _)) = self.turns.next_turn(self.state.get_snake().get_direction())
+            {
+                self.state.turn(towards);
+            }
+        }
+        if let (Some(policy), Some(rival)) = (rival_policy.as_mut(), self.state.get_rival()) {
+            let direction = policy.choose(&self.state, rival);
+            self.state.turn_rival(direction);
+        }
+        match self.state.step() {
+            StepOutcome::Moved | StepOutcome::Ate | StepOutcome::AteOutOfOrder => {}
+            StepOutcome::LostLife => self.turns = TurnQueue::new(),
+            outcome => self.outcome = Some(outcome),
+        }
+    }
+
+    // Filling the board reaches the target whatever the score is.
+    fn has_finished(&self, target: u64) -> bool {
+        self.state.get_score() >= target || self.outcome == Some(StepOutcome::Won)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RaceResult {
+    Won(usize),
+    Draw,
+}
+
+// Note: Local versus: both players start on the same board from the same seed,
+// so they get the same food, and the first to reach the target score wins. A
+// board that ends any other way, like a crash, hands the race to the other
+// player, and the race is drawn when neither board is left.
+#[derive(Debug)]
+pub struct Race {
+    pub racers: Vec<Racer>,
+    target: u64,
+    seed: u64,
+}
+
+impl Race {
+    pub fn new(map: &Map, rules: &Rules, seed: u64, target: u16) -> Self {
+        let now = Instant::now();
+        let racers = PLAYER_NAMES
+            .iter()
+            .map(|_| Racer {
+                state: GameState::from_map(map, rules.clone(), seed),
+                turns: TurnQueue::new(),
+                next_tick: now,
+                outcome: None,
+            })
+            .collect();
+
+        Self {
+            racers,
+            target: target.max(1) as u64,
+            seed,
+        }
+    }
+
+    // Every board starts its first tick a full interval from now.
+    pub fn start(&mut self) {
+        let now = Instant::now();
+        for racer in &mut self.racers {
+            racer.next_tick = now + racer.state.calculate_interval();
+        }
+    }
+
+    // When the next board still being played is due to tick.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.racers
+            .iter()
+            .filter(|racer| racer.is_playing())
+            .map(|racer| racer.next_tick)
+            .min()
+    }
+
+    // Queues a turn for `player`, returning whether it was taken. Turns for a
+    // board that has ended are dropped.
+    pub fn push_turn(&mut self, player: usize, towards: Direction) -> bool {
+        self.racers
+            .get_mut(player)
+            .is_some_and(|racer| racer.is_playing() && racer.turns.push(towards))
+    }
+
+    // Steps every board that is due to tick by `now`.
+    pub fn tick(&mut self, now: Instant, rival_policy: &mut Option<Box<dyn Policy>>) {
+        for racer in self.racers.iter_mut() {
+            if racer.is_playing() && racer.next_tick <= now {
+                racer.step(rival_policy);
+                racer.schedule(now);
+            }
+        }
+    }
+
+    // Returns None while the race goes on.
+    pub fn get_result(&self) -> Option<RaceResult> {
+        if let Some(winner) = self
+            .racers
+            .iter()
+            .position(|racer| racer.has_finished(self.target))
+        {
+            return Some(RaceResult::Won(winner));
+        }
+
+        let playing: Vec<usize> = (0..self.racers.len())
+            .filter(|i| self.racers[*i].is_playing())
+            .collect();
+        match playing.as_slice() {
+            [] => Some(RaceResult::Draw),
+            [last] if self.racers.len() > 1 => Some(RaceResult::Won(*last)),
+            _ => None,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let scores: Vec<String> = self
+            .racers
+            .iter()
+            .zip(PLAYER_NAMES)
+            .map(|(racer, name)| format!("{} {}", name, racer.state.get_score()))
+            .collect();
+        let result = match self.get_result() {
+            Some(RaceResult::Won(winner)) => format!("{} won", PLAYER_NAMES[winner]),
+            Some(RaceResult::Draw) => "draw".to_string(),
+            None => "stopped".to_string(),
+        };
+        format!(
+            "Versus to {} on seed {:x}: {}, {}",
+            self.target,
+            self.seed,
+            scores.join(", "),
+            result
+        )
+    }
+    // The lines of the screen shown once the race is over.
+    pub fn result_lines(&self, result: RaceResult) -> Vec<String> {
+        let title = match result {
+            RaceResult::Won(winner) => format!("{} wins!", PLAYER_NAMES[winner]),
+            RaceResult::Draw => "It's a draw!".to_string(),
+        };
+        let mut lines = vec![title, String::new()];
+        for (racer, name) in self.racers.iter().zip(PLAYER_NAMES) {
+            lines.push(format!(
+                "{}: {}/{}",
+                name,
+                racer.state.get_score(),
+                self.target
+            ));
+        }
+        lines.push(String::new());
+        lines.push(format!("Seed {:x}", self.seed));
+        lines.push("Any key: menu".to_string());
+
+        lines
+    }
+
+    // Note: How close each player is to the target, drawn into the bar's
+    // viewport. Side by side, each player has a column that fills up from the
+    // bottom of the board; stacked, the first player's half of the row fills from
+    // the left and the second's from the right.
+    pub fn draw_bar(&self, frame: &mut Frame, layout: &Layout, board_height: u16) {
+        let scores: Vec<u64> = self
+            .racers
+            .iter()
+            .map(|racer| racer.state.get_score())
+            .collect();
+        match layout.stacking {
+            Stacking::SideBySide => {
+                let length = board_height;
+                for (i, score) in scores.iter().enumerate() {
+                    let x = 1 + i as u16;
+                    frame.put(
+                        x,
+                        0,
+                        PLAYER_NAMES[i].chars().last().unwrap(),
+                        PLAYER_COLORS[i],
+                    );
+                    let filled = filled(*score, self.target, length);
+                    for y in 0..length {
+                        let (glyph, color) = if length - y <= filled {
+                            ('█', PLAYER_COLORS[i])
+                        } else {
+                            ('│', Color::DarkGrey)
+                        };
+                        frame.put(x, 1 + y, glyph, color);
+                    }
+                }
+            }
+            Stacking::Stacked => {
+                let half = layout.bar.width / 2;
+                let filled = [
+                    filled(scores[0], self.target, half),
+                    filled(scores[1], self.target, half),
+                ];
+                for x in 0..half * 2 {
+                    let (glyph, color) = if x < filled[0] {
+                        ('█', PLAYER_COLORS[0])
+                    } else if half * 2 - x <= filled[1] {
+                        ('█', PLAYER_COLORS[1])
+                    } else {
+                        ('─', Color::DarkGrey)
+                    };
+                    frame.put(x, 0, glyph, color);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Stacking {
+    SideBySide,
+    Stacked,
+}
+
+// Where the boards and the score comparison bar go in the frame.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub boards: [Viewport; 2],
+    pub bar: Viewport,
+    pub stacking: Stacking,
+}
+
+impl Layout {
+    // Note: The two boards, each `width` by `height`, go side by side when the
+    // terminal is wide enough, with the score comparison as a column per player
+    // between them. Otherwise they go one above the other with the comparison as
+    // a row between them. Returns None when neither fits in the terminal.
+    pub fn new(width: u16, height: u16, (columns, rows): (u16, u16)) -> Option<Self> {
+        if width * 2 + BAR_COLUMNS <= columns && height <= rows {
+            return Some(Self {
+                boards: [
+                    Viewport {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height,
+                    },
+                    Viewport {
+                        x: width + BAR_COLUMNS,
+                        y: 0,
+                        width,
+                        height,
+                    },
+                ],
+                bar: Viewport {
+                    x: width,
+                    y: 0,
+                    width: BAR_COLUMNS,
+                    height,
+                },
+                stacking: Stacking::SideBySide,
+            });
+        }
+
+        if width <= columns && height * 2 < rows {
+            return Some(Self {
+                boards: [
+                    Viewport {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height,
+                    },
+                    Viewport {
+                        x: 0,
+                        y: height + 1,
+                        width,
+                        height,
+                    },
+                ],
+                bar: Viewport {
+                    x: 0,
+                    y: height,
+                    width,
+                    height: 1,
+                },
+                stacking: Stacking::Stacked,
+            });
+        }
+
+        None
+    }
+
+    pub fn get_width(&self) -> u16 {
+        self.boards[1].x + self.boards[1].width
+    }
+
+    pub fn get_height(&self) -> u16 {
+        self.boards[1].y + self.boards[1].height
+    }
+}
+
+// How much of a bar `length` cells long the score fills on the way to the target.
+fn filled(score: u64, target: u64, length: u16) -> u16 {
+    (score.min(target) * length as u64 / target.max(1)) as u16
+}
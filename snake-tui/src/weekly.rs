@@ -0,0 +1,257 @@
+use crate::challenge;
+use crate::config::Config;
+use crate::menu::wrap;
+use crate::modifier::Modifier;
+use crate::savefile::{self, SaveFile, SaveLock};
+use snake_core::map::Map;
+use snake_core::mapgen::MapGen;
+use std::io;
+
+const WEEKLY_FILE: SaveFile = SaveFile::new("weekly", &[savefile::unchanged]);
+const WEEKLY_BOARD_SIZE: u16 = 20;
+// Mixed into the week number, so a week's seed is not one of the daily seeds.
+const WEEKLY_SALT: u64 = 0x7765_656b_6c79;
+
+// Note: A weekly preset is a generated board plus the rules it is played
+// with, in the same form as a campaign level.
+#[derive(Debug)]
+pub struct WeeklyPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    map_gen: MapGen,
+    rules: fn(&mut Config),
+}
+
+pub const ROTATION: [WeeklyPreset; 6] = [
+    WeeklyPreset {
+        name: "Maze runner",
+        description: "A maze, classic rules",
+        map_gen: MapGen::Maze,
+        rules: |_| {},
+    },
+    WeeklyPreset {
+        name: "Foggy caves",
+        description: "Caves, with fog",
+        map_gen: MapGen::Caves,
+        rules: |config| config.modifiers.set(Modifier::Fog, true),
+    },
+    WeeklyPreset {
+        name: "Looking glass",
+        description: "Rooms, with mirrored controls",
+        map_gen: MapGen::Rooms,
+        rules: |config| config.modifiers.set(Modifier::MirrorControls, true),
+    },
+    WeeklyPreset {
+        name: "Buffet",
+        description: "A maze that wraps around, with three foods at a time",
+        map_gen: MapGen::Maze,
+        rules: |config| {
+            config.modifiers.set(Modifier::WrapAround, true);
+            config.food_count = 3;
+        },
+    },
+    WeeklyPreset {
+        name: "Rush hour",
+        description: "Rooms, at double speed",
+        map_gen: MapGen::Rooms,
+        rules: |config| config.modifiers.set(Modifier::DoubleSpeed, true),
+    },
+    WeeklyPreset {
+        name: "Laser caves",
+        description: "Caves with lasers, and two lives",
+        map_gen: MapGen::Caves,
+        rules: |config| {
+            config.laser_secs = 10;
+            config.lives = 2;
+        },
+    },
+];
+
+// Note: Everyone gets the same challenge all week, going by the ISO week of the
+// day (in UTC) so weeks start on Mondays, and the rotation moves on by one
+// preset every week.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Weekly {
+    year: i64,
+    week: u64,
+    // Weeks since the one the Unix epoch fell in.
+    index: u64,
+}
+
+impl Weekly {
+    // `day` counts days since the Unix epoch, as challenge::days_since_epoch.
+    pub fn for_day(day: u64) -> Self {
+        // The epoch was a Thursday, and an ISO week belongs to the year its Thursday is in.
+        let weekday = (day + 3) % 7;
+        let thursday = day + 3 - weekday;
+        let (year, _, _) = challenge::civil_date(thursday);
+        let january_first = days_from_civil(year, 1, 1);
+
+        Self {
+            year,
+            week: (thursday as i64 - january_first) as u64 / 7 + 1,
+            index: (day + 3) / 7,
+        }
+    }
+
+    pub fn this_week() -> Self {
+        Self::for_day(challenge::days_since_epoch())
+    }
+
+    // e.g. `2026-W42`.
+    pub fn get_label(&self) -> String {
+        format!("{:04}-W{:02}", self.year, self.week)
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        challenge::daily_seed(self.index ^ WEEKLY_SALT)
+    }
+
+    pub fn get_preset(&self) -> &'static WeeklyPreset {
+        &ROTATION[(self.index % ROTATION.len() as u64) as usize]
+    }
+
+    pub fn config(&self, base: &Config) -> Config {
+        let mut config = base.without_rules();
+        (self.get_preset().rules)(&mut config);
+
+        config
+    }
+
+    pub fn map(&self) -> Map {
+        self.get_preset().map_gen.generate(
+            WEEKLY_BOARD_SIZE,
+            WEEKLY_BOARD_SIZE,
+            self.get_seed(),
+            None,
+        )
+    }
+
+    // The screen shown before playing, for a board `width` wide.
+    pub fn intro_lines(&self, bests: &WeeklyBests, width: usize) -> Vec<String> {
+        let preset = self.get_preset();
+        let mut lines = vec![
+            format!("Weekly {}", self.get_label()),
+            String::new(),
+            preset.name.to_string(),
+        ];
+        lines.extend(wrap(preset.description, width));
+        lines.push(String::new());
+        lines.push(match bests.get_best(&self.get_label()) {
+            Some(best) => format!("Best {}", best),
+            None => "Not played yet".to_string(),
+        });
+        lines.push(String::new());
+        lines.push("Enter: play, q: back".to_string());
+
+        lines
+    }
+
+    // The screen shown after a run scoring `score`, once it is in `bests`.
+    pub fn result_lines(&self, bests: &WeeklyBests, score: u64, new_best: bool) -> Vec<String> {
+        let label = self.get_label();
+        let mut lines = vec![format!("Weekly {}", label), format!("Score {}", score)];
+        if new_best {
+            lines.push("New best this week!".to_string());
+        } else if let Some(best) = bests.get_best(&label) {
+            lines.push(format!("Best {}", best));
+        }
+        lines.push(String::new());
+        lines.push("Any key: back".to_string());
+
+        lines
+    }
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Howard Hinnant's civil-to-days conversion, the inverse of challenge::civil_date.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Note: The weekly file keeps the best score of every week played, in the
+// same `key = value` format as the stats, as `<week> = <score>`.
+#[derive(Debug, Default)]
+pub struct WeeklyBests {
+    bests: Vec<(String, u64)>,
+}
+
+impl WeeklyBests {
+    pub fn load() -> Self {
+        Self::parse(&WEEKLY_FILE.load().unwrap_or_default())
+    }
+
+    // The whole file, header included, as it goes into a profile bundle.
+    pub fn to_file(&self) -> String {
+        WEEKLY_FILE.with_header(&self.to_contents())
+    }
+
+    pub fn from_file(contents: &str) -> Self {
+        Self::parse(&WEEKLY_FILE.read(contents))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut bests = Self::default();
+        for line in contents.lines() {
+            if let Some((week, score)) = line.split_once('=') {
+                if let Ok(score) = score.trim().parse() {
+                    bests.record(week.trim(), score);
+                }
+            }
+        }
+
+        bests
+    }
+
+    // Held from loading until saving, see SaveFile::lock.
+    pub fn lock() -> io::Result<SaveLock> {
+        WEEKLY_FILE.lock()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        WEEKLY_FILE.save(&self.to_contents())
+    }
+
+    fn to_contents(&self) -> String {
+        self.bests
+            .iter()
+            .map(|(week, score)| format!("{} = {}\n", week, score))
+            .collect()
+    }
+
+    // Keeps the best score of each week.
+    pub fn merge(&mut self, other: Self) {
+        for (week, score) in other.bests {
+            self.record(&week, score);
+        }
+    }
+
+    pub fn get_best(&self, week: &str) -> Option<u64> {
+        self.bests
+            .iter()
+            .find(|(known, _)| known == week)
+            .map(|(_, score)| *score)
+    }
+
+    // Keeps the best score. Returns whether this one beat the week's best so far.
+    pub fn record(&mut self, week: &str, score: u64) -> bool {
+        match self.bests.iter_mut().find(|(known, _)| known == week) {
+            Some((_, best)) if score > *best => {
+                *best = score;
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.bests.push((week.to_string(), score));
+                self.bests.sort();
+                true
+            }
+        }
+    }
+}
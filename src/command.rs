@@ -1,6 +0,0 @@
-use crate::direction::Direction;
-
-pub enum Command {
-    Quit,
-    Turn(Direction),
-}
@@ -1,20 +0,0 @@
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-// Note: Without this opposite functionality,
-// the body segments would be placed in front of the head,
-// which would immediately cause a collision when the snake starts moving.
-impl Direction {
-    pub fn opposite(&self) -> Self {
-        match self {
-            Self::Up => Self::Down,
-            Self::Right => Self::Left,
-            Self::Down => Self::Up,
-            Self::Left => Self::Right,
-        }
-    }
-}